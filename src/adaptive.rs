@@ -0,0 +1,138 @@
+use crate::util::lock;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The spacing between requests imposed when adaptive backoff first engages
+const BASE_SPACING: Duration = Duration::from_secs(1);
+
+/// The maximum spacing between requests that adaptive backoff will impose
+const MAX_SPACING: Duration = Duration::from_secs(60);
+
+/// Client-wide backoff that increases the spacing between requests when a
+/// burst of server errors and secondary rate limits is observed across
+/// requests, and that decays back to normal once the burst has passed
+#[derive(Debug)]
+pub(crate) struct AdaptiveBackoff {
+    /// The number of strained responses within `window` that triggers an
+    /// increase in spacing
+    threshold: u32,
+
+    /// The period over which strained responses are counted, which is also
+    /// how long each level of spacing lasts before decaying
+    window: Duration,
+
+    /// The backoff's mutable state
+    state: Mutex<AdaptiveState>,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct AdaptiveState {
+    /// The times at which recent strained responses were received
+    strains: VecDeque<Instant>,
+
+    /// The current extra spacing between requests
+    spacing: Duration,
+
+    /// If `spacing` is nonzero, the time at which it will next be halved
+    decay_at: Option<Instant>,
+}
+
+impl AdaptiveBackoff {
+    pub(crate) fn new(threshold: u32, window: Duration) -> AdaptiveBackoff {
+        AdaptiveBackoff {
+            threshold,
+            window,
+            state: Mutex::new(AdaptiveState::default()),
+        }
+    }
+
+    /// Record that a response indicating that the server is under strain (a
+    /// 5xx response or a secondary rate limit) was received
+    pub(crate) fn record_strain(&self) {
+        let now = Instant::now();
+        let mut state = lock(&self.state);
+        state.strains.push_back(now);
+        while state
+            .strains
+            .front()
+            .is_some_and(|&t| now.saturating_duration_since(t) > self.window)
+        {
+            state.strains.pop_front();
+        }
+        if u32::try_from(state.strains.len()).unwrap_or(u32::MAX) >= self.threshold {
+            state.strains.clear();
+            state.spacing = state
+                .spacing
+                .saturating_mul(2)
+                .clamp(BASE_SPACING, MAX_SPACING);
+            state.decay_at = Some(now + self.window);
+            log::debug!(
+                "Burst of server errors or secondary rate limits observed; spacing requests by {:?}",
+                state.spacing
+            );
+        }
+    }
+
+    /// Returns the minimum spacing that should currently be left between
+    /// requests, decaying it first if its time is up
+    pub(crate) fn spacing(&self) -> Duration {
+        let now = Instant::now();
+        let mut state = lock(&self.state);
+        while let Some(decay_at) = state.decay_at
+            && decay_at <= now
+        {
+            state.spacing /= 2;
+            if state.spacing < BASE_SPACING {
+                log::debug!("Server strain has subsided; no longer spacing requests");
+                state.spacing = Duration::ZERO;
+                state.decay_at = None;
+            } else {
+                state.decay_at = Some(decay_at + self.window);
+            }
+        }
+        state.spacing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread::sleep;
+
+    #[test]
+    fn escalates_and_decays() {
+        let backoff = AdaptiveBackoff::new(3, Duration::from_millis(100));
+        backoff.record_strain();
+        backoff.record_strain();
+        assert_eq!(backoff.spacing(), Duration::ZERO);
+        backoff.record_strain();
+        assert_eq!(backoff.spacing(), BASE_SPACING);
+        for _ in 0..3 {
+            backoff.record_strain();
+        }
+        assert_eq!(backoff.spacing(), BASE_SPACING * 2);
+        sleep(Duration::from_millis(150));
+        assert_eq!(backoff.spacing(), BASE_SPACING);
+        sleep(Duration::from_millis(100));
+        assert_eq!(backoff.spacing(), Duration::ZERO);
+    }
+
+    #[test]
+    fn spread_out_strains_ignored() {
+        let backoff = AdaptiveBackoff::new(2, Duration::from_millis(50));
+        backoff.record_strain();
+        sleep(Duration::from_millis(80));
+        backoff.record_strain();
+        assert_eq!(backoff.spacing(), Duration::ZERO);
+    }
+
+    #[test]
+    fn capped() {
+        let backoff = AdaptiveBackoff::new(1, Duration::from_secs(60));
+        for _ in 0..20 {
+            backoff.record_strain();
+        }
+        assert_eq!(backoff.spacing(), MAX_SPACING);
+    }
+}
@@ -0,0 +1,226 @@
+use super::{Client, RequestError};
+use futures_core::Stream;
+use serde::{Serialize, de::DeserializeOwned};
+use std::panic::resume_unwind;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::sync::mpsc;
+use tokio::task::spawn_blocking;
+
+/// The number of items buffered by a [`PaginationStream`] ahead of the
+/// consumer
+const STREAM_BUFFER: usize = 100;
+
+/// An asynchronous handle to a [`Client`] for use inside a [Tokio] runtime
+///
+/// Each request is run on Tokio's blocking thread pool by the wrapped
+/// `Client`, so requests made through an `AsyncClient` get the same retry,
+/// pacing, and rate-limit handling as requests made through the blocking API
+/// (and share that state with any clones of the `Client`), without tying up
+/// the runtime's worker threads.  The methods must be called from within a
+/// Tokio runtime.
+///
+/// This type is only available when the `tokio` feature is enabled.
+///
+/// [Tokio]: https://tokio.rs
+#[derive(Clone, Debug)]
+pub struct AsyncClient {
+    client: Client,
+}
+
+impl AsyncClient {
+    /// Create a new `AsyncClient` that makes requests with `client`
+    pub fn new(client: Client) -> AsyncClient {
+        AsyncClient { client }
+    }
+
+    /// Returns a reference to the underlying blocking [`Client`]
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Make a GET request to `path` and deserialize the response body as
+    /// JSON.  See [`Client::get()`].
+    pub async fn get<T>(&self, path: &str) -> Result<T, RequestError>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        let path = path.to_owned();
+        self.run(move |client| client.get(&path)).await
+    }
+
+    /// Make a POST request to `path` with `payload` as the JSON body and
+    /// deserialize the response body as JSON.  See [`Client::post()`].
+    pub async fn post<T, U>(&self, path: &str, payload: T) -> Result<U, RequestError>
+    where
+        T: Serialize + Send + 'static,
+        U: DeserializeOwned + Send + 'static,
+    {
+        let path = path.to_owned();
+        self.run(move |client| client.post(&path, &payload)).await
+    }
+
+    /// Make a PUT request to `path` with `payload` as the JSON body and
+    /// deserialize the response body as JSON.  See [`Client::put()`].
+    pub async fn put<T, U>(&self, path: &str, payload: T) -> Result<U, RequestError>
+    where
+        T: Serialize + Send + 'static,
+        U: DeserializeOwned + Send + 'static,
+    {
+        let path = path.to_owned();
+        self.run(move |client| client.put(&path, &payload)).await
+    }
+
+    /// Make a PATCH request to `path` with `payload` as the JSON body and
+    /// deserialize the response body as JSON.  See [`Client::patch()`].
+    pub async fn patch<T, U>(&self, path: &str, payload: T) -> Result<U, RequestError>
+    where
+        T: Serialize + Send + 'static,
+        U: DeserializeOwned + Send + 'static,
+    {
+        let path = path.to_owned();
+        self.run(move |client| client.patch(&path, &payload)).await
+    }
+
+    /// Make a DELETE request to `path`.  See [`Client::delete()`].
+    pub async fn delete(&self, path: &str) -> Result<(), RequestError> {
+        let path = path.to_owned();
+        self.run(move |client| client.delete(&path)).await
+    }
+
+    /// Returns a [`Stream`] that yields each item in each page of the
+    /// paginated results at `path`.  See [`Client::paginate()`].
+    ///
+    /// The pages are fetched in the background as the stream is consumed.
+    /// The stream ends after yielding an error; dropping the stream stops
+    /// further pages from being fetched.
+    pub fn paginate<T>(&self, path: &str) -> PaginationStream<T>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+        let client = self.client.clone();
+        let path = path.to_owned();
+        spawn_blocking(move || {
+            for item in client.paginate::<T>(&path) {
+                let failed = item.is_err();
+                if sender.blocking_send(item).is_err() || failed {
+                    break;
+                }
+            }
+        });
+        PaginationStream { receiver }
+    }
+
+    /// Run `func` with the client on the blocking thread pool
+    async fn run<F, T>(&self, func: F) -> Result<T, RequestError>
+    where
+        F: FnOnce(&Client) -> Result<T, RequestError> + Send + 'static,
+        T: Send + 'static,
+    {
+        let client = self.client.clone();
+        match spawn_blocking(move || func(&client)).await {
+            Ok(r) => r,
+            Err(e) => match e.try_into_panic() {
+                Ok(payload) => resume_unwind(payload),
+                Err(e) => panic!("blocking request task did not complete: {e}"),
+            },
+        }
+    }
+}
+
+impl From<Client> for AsyncClient {
+    fn from(client: Client) -> AsyncClient {
+        AsyncClient::new(client)
+    }
+}
+
+/// A [`Stream`] of the items in a paginated list of results, returned by
+/// [`AsyncClient::paginate()`]
+///
+/// This type is only available when the `tokio` feature is enabled.
+#[derive(Debug)]
+pub struct PaginationStream<T> {
+    receiver: mpsc::Receiver<Result<T, RequestError>>,
+}
+
+impl<T> Stream for PaginationStream<T> {
+    type Item = Result<T, RequestError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockResponse, MockServer};
+    use serde_json::{Value, json};
+    use std::future::poll_fn;
+    use tokio::runtime::{Builder, Runtime};
+
+    fn runtime() -> Runtime {
+        Builder::new_current_thread().build().unwrap()
+    }
+
+    async fn collect<T>(mut stream: PaginationStream<T>) -> Vec<Result<T, RequestError>> {
+        let mut items = Vec::new();
+        while let Some(item) = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await {
+            items.push(item);
+        }
+        items
+    }
+
+    #[test]
+    fn get_and_post() {
+        let server = MockServer::start(|req| match (req.method.as_str(), req.path.as_str()) {
+            ("GET", "/zen") => MockResponse::json(200, &json!({"zen": "Keep it simple"})),
+            ("POST", "/repos/octocat/hello-world/issues") => {
+                let body: Value = serde_json::from_slice(&req.body).unwrap();
+                MockResponse::json(201, &json!({"number": 1, "title": body["title"]}))
+            }
+            _ => MockResponse::new(404, "{}"),
+        });
+        let client = AsyncClient::new(server.client_builder().build().unwrap());
+        runtime().block_on(async {
+            let zen = client.get::<Value>("/zen").await.unwrap();
+            assert_eq!(zen, json!({"zen": "Keep it simple"}));
+            let issue = client
+                .post::<_, Value>("/repos/octocat/hello-world/issues", json!({"title": "Hi"}))
+                .await
+                .unwrap();
+            assert_eq!(issue, json!({"number": 1, "title": "Hi"}));
+            let r = client.get::<Value>("/nonexistent").await;
+            assert!(matches!(r, Err(RequestError::Status(_))));
+        });
+    }
+
+    #[test]
+    fn paginate() {
+        let server = MockServer::start(|req| {
+            if req.path.contains("page=2") {
+                MockResponse::json(200, &json!([3]))
+            } else {
+                let host = req.header("host").unwrap();
+                MockResponse::json(200, &json!([1, 2])).with_header(
+                    "Link",
+                    &format!("<http://{host}/items?page=2>; rel=\"next\""),
+                )
+            }
+        });
+        let client = AsyncClient::new(server.client_builder().build().unwrap());
+        let items = runtime().block_on(async { collect(client.paginate::<u32>("/items")).await });
+        let items = items.into_iter().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(items, [1, 2, 3]);
+    }
+
+    #[test]
+    fn paginate_stops_after_error() {
+        let server = MockServer::start(|_| MockResponse::new(404, "{}"));
+        let client = AsyncClient::new(server.client_builder().build().unwrap());
+        let items = runtime().block_on(async { collect(client.paginate::<u32>("/items")).await });
+        assert_eq!(items.len(), 1);
+        assert!(matches!(items[0], Err(RequestError::Status(_))));
+    }
+}
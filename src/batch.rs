@@ -0,0 +1,468 @@
+use super::{Client, Method, ProgressEvent, RequestError};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// A single request to be performed as part of a [`MutationQueue`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Operation {
+    /// The HTTP method of the request
+    pub method: Method,
+
+    /// The URL path (or complete URL) to which to send the request
+    pub path: String,
+
+    /// The JSON payload, if any, to send as the request body
+    pub payload: Option<Value>,
+}
+
+impl Operation {
+    /// Create a new `Operation` for a request with the given method, path,
+    /// and payload
+    pub fn new(method: Method, path: &str, payload: Option<Value>) -> Operation {
+        Operation {
+            method,
+            path: path.to_owned(),
+            payload,
+        }
+    }
+
+    /// Create a new `Operation` for a GET request
+    pub fn get(path: &str) -> Operation {
+        Operation::new(Method::Get, path, None)
+    }
+
+    /// Create a new `Operation` for a POST request
+    pub fn post(path: &str, payload: Value) -> Operation {
+        Operation::new(Method::Post, path, Some(payload))
+    }
+
+    /// Create a new `Operation` for a PUT request
+    pub fn put(path: &str, payload: Value) -> Operation {
+        Operation::new(Method::Put, path, Some(payload))
+    }
+
+    /// Create a new `Operation` for a PATCH request
+    pub fn patch(path: &str, payload: Value) -> Operation {
+        Operation::new(Method::Patch, path, Some(payload))
+    }
+
+    /// Create a new `Operation` for a DELETE request
+    pub fn delete(path: &str) -> Operation {
+        Operation::new(Method::Delete, path, None)
+    }
+}
+
+/// A queue of mutating requests (POST, PATCH, PUT, or DELETE) to be executed
+/// one at a time.
+///
+/// Operations are executed in the order they were enqueued, with the
+/// client's usual spacing between mutating requests and handling of
+/// secondary rate limits, plus any additional interval set with
+/// [`with_interval()`][MutationQueue::with_interval].  A failed operation
+/// does not stop the remaining operations from being executed.
+///
+/// A `MutationQueue` is created with [`Client::mutation_queue()`].
+#[derive(Clone, Debug)]
+pub struct MutationQueue<'a> {
+    client: &'a Client,
+    ops: VecDeque<Operation>,
+
+    /// The minimum amount of time between the starts of consecutive
+    /// operations
+    interval: Duration,
+}
+
+impl<'a> MutationQueue<'a> {
+    pub(super) fn new(client: &'a Client) -> Self {
+        MutationQueue {
+            client,
+            ops: VecDeque::new(),
+            interval: Duration::ZERO,
+        }
+    }
+
+    /// Space the operations so that each one starts at least `interval`
+    /// after the previous one started.  This is on top of the client's own
+    /// spacing between mutating requests, and so it only has an effect if it
+    /// is longer than that spacing (one second by default), e.g., for
+    /// slowing down bulk jobs that would otherwise trigger secondary rate
+    /// limits.
+    ///
+    /// The waits are subject to the same constraints as the client's other
+    /// sleeps: if the client is in no-sleep mode, its deadline would pass
+    /// during a wait, or it is shut down, the operation fails instead.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Add an operation to the end of the queue
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` without adding the operation if its method is not a
+    /// mutating method (i.e., if it is GET)
+    pub fn push(&mut self, op: Operation) -> Result<(), NotMutatingError> {
+        if op.method.is_mutating() {
+            self.ops.push_back(op);
+            Ok(())
+        } else {
+            Err(NotMutatingError(op))
+        }
+    }
+
+    /// Add each of the operations in `ops` to the end of the queue in order
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` upon encountering an operation whose method is not a
+    /// mutating method.  The operations before it are still added, while it
+    /// and the operations after it are not.
+    pub fn push_all<I>(&mut self, ops: I) -> Result<(), NotMutatingError>
+    where
+        I: IntoIterator<Item = Operation>,
+    {
+        ops.into_iter().try_for_each(|op| self.push(op))
+    }
+
+    /// Returns the number of operations in the queue
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Returns `true` if the queue is empty
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Execute all operations in the queue in order and return a report of
+    /// the results
+    pub fn run(mut self) -> MutationReport {
+        let ops = std::mem::take(&mut self.ops);
+        let total = ops.len();
+        let mut results = Vec::with_capacity(total);
+        let mut last_start = None;
+        for (i, operation) in ops.into_iter().enumerate() {
+            if let Err(e) = self.wait_for_interval(last_start, &operation) {
+                results.push(OperationResult {
+                    operation,
+                    result: Err(e),
+                });
+                continue;
+            }
+            log::debug!(
+                "Executing queued mutation {}/{total}: {} {}",
+                i + 1,
+                operation.method,
+                operation.path
+            );
+            last_start = Some(Instant::now());
+            let result = self.client.request_value(
+                operation.method,
+                &operation.path,
+                operation.payload.as_ref(),
+            );
+            results.push(OperationResult { operation, result });
+        }
+        MutationReport {
+            results,
+            skipped: Vec::new(),
+        }
+    }
+
+    /// Sleep until `interval` has passed since `last_start`, the time at
+    /// which the previous operation started, if any
+    fn wait_for_interval(
+        &self,
+        last_start: Option<Instant>,
+        operation: &Operation,
+    ) -> Result<(), RequestError> {
+        let Some(last_start) = last_start else {
+            return Ok(());
+        };
+        let delay = self.interval.saturating_sub(last_start.elapsed());
+        if delay.is_zero() {
+            return Ok(());
+        }
+        let method = operation.method;
+        let url = self.client.mkurl(&operation.path)?;
+        log::debug!("Sleeping for {delay:?} between queued mutations");
+        self.client.sleep_before(
+            method,
+            &url,
+            delay,
+            None,
+            ProgressEvent::MutationDelay {
+                method,
+                url: url.clone(),
+                delay,
+            },
+        )?;
+        self.client.update_usage(|u| u.mutation_wait += delay);
+        Ok(())
+    }
+}
+
+/// Error returned when adding an [`Operation`] with a non-mutating method to
+/// a [`MutationQueue`]
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("{} {} is not a mutating operation", .0.method, .0.path)]
+pub struct NotMutatingError(
+    /// The rejected operation
+    pub Operation,
+);
+
+/// The outcome of executing a single [`Operation`]
+#[derive(Debug)]
+pub struct OperationResult {
+    /// The operation that was executed
+    pub operation: Operation,
+
+    /// The result of the operation: either the decoded JSON response body (or
+    /// `None` if the response body was empty) or the error that occurred
+    pub result: Result<Option<Value>, RequestError>,
+}
+
+/// A report of the results of running a [`MutationQueue`] or
+/// [`Client::batch()`]
+///
+/// The `Display` implementation produces a one-line summary of the number of
+/// operations that succeeded & failed (and were skipped, if any were).
+#[derive(Debug)]
+pub struct MutationReport {
+    /// The results of each operation, in the order they were executed
+    pub results: Vec<OperationResult>,
+
+    /// The operations that were not executed because a batch was aborted
+    pub skipped: Vec<Operation>,
+}
+
+/// A report of the results of [`Client::batch()`]
+///
+/// This is the same type as [`MutationReport`]: a batch and a
+/// [`MutationQueue`] both produce one result per executed [`Operation`] plus
+/// the list of operations skipped after an abort, so the two share a single
+/// report type, and code summarizing or inspecting failures works for either.
+pub type BatchReport = MutationReport;
+
+impl MutationReport {
+    /// Returns the number of operations that succeeded
+    pub fn succeeded(&self) -> usize {
+        self.results.iter().filter(|r| r.result.is_ok()).count()
+    }
+
+    /// Returns the number of operations that failed
+    pub fn failed(&self) -> usize {
+        self.results.iter().filter(|r| r.result.is_err()).count()
+    }
+
+    /// Returns an iterator over the results of the operations that failed
+    pub fn failures(&self) -> impl Iterator<Item = &OperationResult> {
+        self.results.iter().filter(|r| r.result.is_err())
+    }
+
+    /// Returns `true` if every operation was executed and succeeded
+    pub fn is_success(&self) -> bool {
+        self.failed() == 0 && self.skipped.is_empty()
+    }
+
+    /// Returns `true` if execution was aborted before all operations were
+    /// executed
+    pub fn aborted(&self) -> bool {
+        !self.skipped.is_empty()
+    }
+}
+
+impl fmt::Display for MutationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} operations: {} succeeded, {} failed",
+            self.results.len() + self.skipped.len(),
+            self.succeeded(),
+            self.failed()
+        )?;
+        if !self.skipped.is_empty() {
+            write!(f, ", {} skipped", self.skipped.len())?;
+        }
+        Ok(())
+    }
+}
+
+impl Client {
+    /// Execute a series of heterogeneous requests in order, continuing past
+    /// failures, and return a report of which operations succeeded, which
+    /// failed, and which were skipped.
+    ///
+    /// Mutating requests are spaced out as usual.  If `max_failures` is
+    /// `Some(n)`, execution is aborted once `n` operations have failed, and
+    /// the remaining operations are reported as skipped; `Some(0)` is treated
+    /// the same as `Some(1)`, i.e., execution stops at the first failure.
+    pub fn batch<I>(&self, ops: I, max_failures: Option<usize>) -> BatchReport
+    where
+        I: IntoIterator<Item = Operation>,
+    {
+        let max_failures = max_failures.map(|n| n.max(1));
+        let mut results = Vec::new();
+        let mut failures = 0;
+        let mut ops = ops.into_iter();
+        for operation in ops.by_ref() {
+            log::debug!(
+                "Executing batch operation: {} {}",
+                operation.method,
+                operation.path
+            );
+            let result = self.request_value(
+                operation.method,
+                &operation.path,
+                operation.payload.as_ref(),
+            );
+            if result.is_err() {
+                failures += 1;
+            }
+            results.push(OperationResult { operation, result });
+            if max_failures.is_some_and(|n| failures >= n) {
+                log::debug!("{failures} batch operations failed; aborting batch");
+                break;
+            }
+        }
+        BatchReport {
+            results,
+            skipped: ops.collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockResponse, MockServer};
+    use serde_json::json;
+
+    #[test]
+    fn report() {
+        let report = MutationReport {
+            results: vec![
+                OperationResult {
+                    operation: Operation::patch(
+                        "/repos/octocat/hello-world/issues/1",
+                        json!({"state": "closed"}),
+                    ),
+                    result: Ok(Some(json!({"number": 1, "state": "closed"}))),
+                },
+                OperationResult {
+                    operation: Operation::delete("/repos/octocat/hello-world/labels/bug"),
+                    result: Ok(None),
+                },
+                OperationResult {
+                    operation: Operation::delete("https://[::1"),
+                    result: Err(RequestError::Path {
+                        source: url::ParseError::InvalidIpv6Address,
+                        path: "https://[::1".into(),
+                    }),
+                },
+            ],
+            skipped: Vec::new(),
+        };
+        assert_eq!(report.succeeded(), 2);
+        assert_eq!(report.failed(), 1);
+        assert_eq!(
+            report.failures().next().unwrap().operation.path,
+            "https://[::1"
+        );
+        assert_eq!(report.to_string(), "3 operations: 2 succeeded, 1 failed");
+        assert!(!report.is_success());
+        assert!(!report.aborted());
+    }
+
+    #[test]
+    fn queue_rejects_non_mutating() {
+        let client = Client::new("hunter2").unwrap();
+        let mut queue = client.mutation_queue();
+        let r = queue.push(Operation::get("/repos/octocat/hello-world"));
+        assert_eq!(
+            r,
+            Err(NotMutatingError(Operation::get(
+                "/repos/octocat/hello-world"
+            )))
+        );
+        assert_eq!(
+            r.unwrap_err().to_string(),
+            "GET /repos/octocat/hello-world is not a mutating operation"
+        );
+        let r = queue.push_all([
+            Operation::delete("/repos/octocat/hello-world/labels/bug"),
+            Operation::get("/repos/octocat/hello-world/labels"),
+            Operation::delete("/repos/octocat/hello-world/labels/wontfix"),
+        ]);
+        assert!(r.is_err());
+        assert_eq!(queue.len(), 1);
+    }
+
+    #[test]
+    fn queue_interval() {
+        let server = MockServer::start(|_| MockResponse::new(204, ""));
+        let client = server.client_builder().build().unwrap();
+        let mut queue = client
+            .mutation_queue()
+            .with_interval(Duration::from_millis(1500));
+        queue
+            .push_all([
+                Operation::delete("/repos/octocat/hello-world/labels/bug"),
+                Operation::delete("/repos/octocat/hello-world/labels/wontfix"),
+            ])
+            .unwrap();
+        let start = Instant::now();
+        let report = queue.run();
+        assert!(report.is_success());
+        assert!(start.elapsed() >= Duration::from_millis(1500));
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[test]
+    fn batch_aborts_after_max_failures() {
+        let client = Client::new("hunter2").unwrap();
+        let ops = vec![
+            Operation::delete("https://[::1"),
+            Operation::delete("https://[::2"),
+            Operation::delete("/repos/octocat/hello-world/labels/bug"),
+        ];
+        let report = client.batch(ops, Some(2));
+        assert_eq!(report.succeeded(), 0);
+        assert_eq!(report.failed(), 2);
+        assert_eq!(
+            report.skipped,
+            [Operation::delete("/repos/octocat/hello-world/labels/bug")]
+        );
+        assert!(report.aborted());
+        assert_eq!(
+            report.to_string(),
+            "3 operations: 0 succeeded, 2 failed, 1 skipped"
+        );
+    }
+
+    #[test]
+    fn batch_zero_max_failures() {
+        let server = MockServer::start(|req| {
+            if req.path == "/rate_limit" {
+                MockResponse::json(200, &json!({}))
+            } else {
+                MockResponse::json(404, &json!({"message": "Not Found"}))
+            }
+        });
+        let client = server.client_builder().build().unwrap();
+        let ops = vec![
+            Operation::get("/rate_limit"),
+            Operation::get("/rate_limit"),
+            Operation::get("/nope"),
+            Operation::get("/rate_limit"),
+        ];
+        let report = client.batch(ops, Some(0));
+        assert_eq!(report.succeeded(), 2);
+        assert_eq!(report.failed(), 1);
+        assert_eq!(report.skipped, [Operation::get("/rate_limit")]);
+    }
+}
@@ -0,0 +1,148 @@
+//! A minimal command-line client for the GitHub REST API, in the vein of
+//! `gh api`
+use anyhow::Context;
+use clap::Parser;
+use minigh::{ClientBuilder, Method, RequestError};
+use serde_json::{Map, Value};
+use std::process::ExitCode;
+
+/// Make an authenticated request to the GitHub REST API and print the
+/// response body as JSON.
+///
+/// The token and API URL are discovered in the same way as by the `gh` CLI.
+#[derive(Clone, Debug, Eq, Parser, PartialEq)]
+#[command(version)]
+struct Arguments {
+    /// Add a string parameter.  For GET requests, parameters are added to the
+    /// URL's query string; for other requests, they are sent as fields of a
+    /// JSON object in the request body.  If no parameters are given, requests
+    /// other than GET are sent without a body.
+    #[arg(short = 'f', long = "raw-field", value_name = "KEY=VALUE", value_parser = parse_param)]
+    raw_fields: Vec<(String, String)>,
+
+    /// Add a typed parameter: `true`, `false`, `null`, and integers are
+    /// converted to the corresponding JSON values; everything else is sent
+    /// as a string
+    #[arg(short = 'F', long = "field", value_name = "KEY=VALUE", value_parser = parse_param)]
+    fields: Vec<(String, String)>,
+
+    /// Fetch all pages of results and output them as a single JSON array
+    #[arg(long)]
+    paginate: bool,
+
+    /// The HTTP method of the request
+    method: Method,
+
+    /// The URL path to request (e.g., `/repos/octocat/hello-world`) or a
+    /// complete URL
+    path: String,
+}
+
+impl Arguments {
+    fn run(self) -> anyhow::Result<()> {
+        let client = ClientBuilder::new()
+            .with_discovered_auth()
+            .build()
+            .context("failed to construct client")?;
+        let mut params = Map::new();
+        for (key, value) in self.raw_fields {
+            params.insert(key, Value::String(value));
+        }
+        for (key, value) in self.fields {
+            params.insert(key, typed_value(value));
+        }
+        let (path, payload) = if self.method == Method::Get {
+            (with_query(&self.path, &params), None)
+        } else {
+            let payload = (!params.is_empty()).then_some(Value::Object(params));
+            (self.path, payload)
+        };
+        let output = if self.paginate {
+            anyhow::ensure!(
+                self.method == Method::Get,
+                "--paginate can only be used with GET requests"
+            );
+            Some(Value::Array(
+                client.paginate::<Value>(&path).collect::<Result<_, _>>()?,
+            ))
+        } else {
+            let mut req = client.req(self.method, &path);
+            if let Some(payload) = payload {
+                req = req.with_json(&payload);
+            }
+            let body = req
+                .send_raw()?
+                .into_body()
+                .read_to_vec()
+                .context("failed to read response body")?;
+            // Responses such as 204 No Content have empty bodies
+            if body.is_empty() {
+                None
+            } else {
+                Some(
+                    serde_json::from_slice::<Value>(&body)
+                        .context("failed to decode response body")?,
+                )
+            }
+        };
+        if let Some(output) = output {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&output).expect("serializing Value should not fail")
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Parse a `KEY=VALUE` command-line parameter
+fn parse_param(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_owned(), value.to_owned())),
+        _ => Err(String::from("expected KEY=VALUE")),
+    }
+}
+
+/// Convert the value of a `--field` parameter to a JSON value
+fn typed_value(s: String) -> Value {
+    match s.as_str() {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        "null" => Value::Null,
+        _ => s.parse::<i64>().map_or(Value::String(s), Value::from),
+    }
+}
+
+/// Append the given parameters to `path` as a query string
+fn with_query(path: &str, params: &Map<String, Value>) -> String {
+    if params.is_empty() {
+        return path.to_owned();
+    }
+    let mut query = url::form_urlencoded::Serializer::new(String::new());
+    for (key, value) in params {
+        match value {
+            Value::String(s) => query.append_pair(key, s),
+            other => query.append_pair(key, &other.to_string()),
+        };
+    }
+    let sep = if path.contains('?') { '&' } else { '?' };
+    format!("{path}{sep}{}", query.finish())
+}
+
+fn main() -> ExitCode {
+    match Arguments::parse().run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("minigh: {e:?}");
+            for src in e.chain() {
+                if let Some(err) = src.downcast_ref::<RequestError>() {
+                    if let Some(body) = err.body() {
+                        eprintln!("\n{body}");
+                    }
+                    return ExitCode::from(err.exit_code());
+                }
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use crate::util::lock;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A token bucket that allows bursts of up to `capacity` events and refills
+/// at a rate of `capacity` events per `window`, implemented as a generic cell
+/// rate algorithm
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct TokenBucket {
+    /// The maximum number of events that can occur in a burst
+    capacity: u32,
+
+    /// The period over which the bucket refills completely
+    window: Duration,
+
+    /// The amount of time it takes for one token to be refilled
+    interval: Duration,
+
+    /// The "theoretical arrival time": the time at which the bucket will be
+    /// full again if no more tokens are taken
+    tat: Instant,
+}
+
+impl TokenBucket {
+    /// Create a new full bucket
+    pub(crate) fn new(capacity: u32, window: Duration, now: Instant) -> TokenBucket {
+        let capacity = capacity.max(1);
+        TokenBucket {
+            capacity,
+            window,
+            interval: window / capacity,
+            tat: now,
+        }
+    }
+
+    /// Take a token from the bucket, returning how long the caller must wait
+    /// before the token becomes available
+    pub(crate) fn acquire(&mut self, now: Instant) -> Duration {
+        let start = self.tat.max(now);
+        let burst = self.interval * (self.capacity - 1);
+        let wait = start
+            .checked_sub(burst)
+            .map_or(Duration::ZERO, |t| t.saturating_duration_since(now));
+        self.tat = start + self.interval;
+        wait
+    }
+
+    /// Take a token from the bucket if one is available now, returning
+    /// whether a token was taken
+    pub(crate) fn try_acquire(&mut self, now: Instant) -> bool {
+        let start = self.tat.max(now);
+        let burst = self.interval * (self.capacity - 1);
+        if start.checked_sub(burst).is_some_and(|t| t > now) {
+            false
+        } else {
+            self.tat = start + self.interval;
+            true
+        }
+    }
+
+    /// Update the bucket with rate-limit information reported by the server:
+    /// the capacity is set to `limit`, and if fewer than `remaining` tokens
+    /// are left in the bucket, the bucket is drained accordingly
+    pub(crate) fn observe(&mut self, limit: u64, remaining: u64, now: Instant) {
+        let limit = u32::try_from(limit).unwrap_or(u32::MAX).max(1);
+        if limit != self.capacity {
+            self.capacity = limit;
+            self.interval = self.window / limit;
+        }
+        let remaining = u32::try_from(remaining)
+            .unwrap_or(u32::MAX)
+            .min(self.capacity);
+        let tat = now + self.interval * (self.capacity - remaining);
+        if tat > self.tat {
+            self.tat = tat;
+        }
+    }
+}
+
+/// A client-wide limit on the number of retries made within a sliding time
+/// window, shared by all of a client's handles
+#[derive(Clone, Debug)]
+pub(crate) struct RetryBudget(Arc<Mutex<TokenBucket>>);
+
+impl RetryBudget {
+    pub(crate) fn new(max_retries: u32, window: Duration) -> RetryBudget {
+        RetryBudget(Arc::new(Mutex::new(TokenBucket::new(
+            max_retries,
+            window,
+            Instant::now(),
+        ))))
+    }
+
+    /// Spend one retry from the budget, returning `false` if the budget is
+    /// exhausted
+    pub(crate) fn take(&self) -> bool {
+        lock(&self.0).try_acquire(Instant::now())
+    }
+}
+
+impl PartialEq for RetryBudget {
+    fn eq(&self, other: &RetryBudget) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for RetryBudget {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn burst_then_pace() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(3, Duration::from_secs(30), now);
+        for _ in 0..3 {
+            assert_eq!(bucket.acquire(now), Duration::ZERO);
+        }
+        assert_eq!(bucket.acquire(now), Duration::from_secs(10));
+        assert_eq!(bucket.acquire(now), Duration::from_secs(20));
+        let later = now + Duration::from_secs(60);
+        assert_eq!(bucket.acquire(later), Duration::ZERO);
+    }
+
+    #[test]
+    fn refills_gradually() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(2, Duration::from_secs(20), now);
+        assert_eq!(bucket.acquire(now), Duration::ZERO);
+        assert_eq!(bucket.acquire(now), Duration::ZERO);
+        let later = now + Duration::from_secs(4);
+        assert_eq!(bucket.acquire(later), Duration::from_secs(6));
+    }
+
+    #[test]
+    fn try_acquire_does_not_wait() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(2, Duration::from_secs(20), now);
+        assert!(bucket.try_acquire(now));
+        assert!(bucket.try_acquire(now));
+        assert!(!bucket.try_acquire(now));
+        assert!(!bucket.try_acquire(now + Duration::from_secs(9)));
+        assert!(bucket.try_acquire(now + Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn observe_drains() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(30, Duration::from_secs(60), now);
+        bucket.observe(10, 0, now);
+        assert_eq!(bucket.acquire(now), Duration::from_secs(6));
+    }
+
+    #[test]
+    fn observe_full() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(30, Duration::from_secs(60), now);
+        bucket.observe(30, 30, now);
+        assert_eq!(bucket.acquire(now), Duration::ZERO);
+    }
+}
@@ -0,0 +1,355 @@
+use super::{Client, Method, ProgressEvent, RateLimit, RequestError, RetryReason};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+use thiserror::Error;
+use ureq::http::status::StatusCode;
+
+/// The rate-limit resource used by most REST API endpoints
+pub(crate) const CORE_RESOURCE: &str = "core";
+
+/// A reservation of a number of requests against the current rate-limit
+/// window, as returned by [`Client::reserve_budget()`] and
+/// [`Client::wait_for_budget()`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Budget {
+    /// The number of requests that were reserved
+    pub requested: u64,
+
+    /// The rate-limit status at the time of the reservation, or `None` if the
+    /// server does not enforce rate limits
+    pub rate_limit: Option<RateLimit>,
+}
+
+impl Budget {
+    /// Returns the number of requests that can still be made in the current
+    /// rate-limit window after the reserved requests are made, or `None` if
+    /// the server does not enforce rate limits
+    pub fn spare(&self) -> Option<u64> {
+        self.rate_limit
+            .as_ref()
+            .map(|rl| rl.remaining.saturating_sub(self.requested))
+    }
+}
+
+/// Error returned when a rate-limit budget cannot be reserved
+#[derive(Debug, Error)]
+pub enum BudgetError {
+    /// The remaining quota in the current rate-limit window is less than the
+    /// requested number of requests
+    #[error(
+        "requested budget of {requested} requests exceeds remaining quota of {} requests",
+        rate_limit.remaining
+    )]
+    Insufficient {
+        /// The requested number of requests
+        requested: u64,
+
+        /// The current rate-limit status
+        rate_limit: RateLimit,
+    },
+
+    /// The requested number of requests is greater than the total number of
+    /// requests allowed in a rate-limit window, and so the budget can never be
+    /// satisfied
+    #[error(
+        "requested budget of {requested} requests exceeds rate limit of {limit} requests per window"
+    )]
+    ExceedsLimit {
+        /// The requested number of requests
+        requested: u64,
+
+        /// The maximum number of requests allowed per rate-limit window
+        limit: u64,
+    },
+
+    /// Fetching the current rate-limit status failed
+    #[error("failed to fetch rate-limit status")]
+    Request(#[from] RequestError),
+}
+
+/// The response body of `GET /rate_limit`
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct RateLimitResponse {
+    resources: HashMap<String, RateLimit>,
+}
+
+impl Client {
+    /// Fetch the current rate-limit status for all rate-limit resources from
+    /// the `/rate_limit` endpoint.  (Requests to this endpoint do not count
+    /// against the primary rate limit.)
+    ///
+    /// Returns `Ok(None)` if the server responds with 404, which is the case
+    /// for GitHub Enterprise Server instances with rate limiting disabled.
+    ///
+    /// See [`request()`][Client::request] for information on lower-level
+    /// behavior.
+    pub fn rate_limit(&self) -> Result<Option<HashMap<String, RateLimit>>, RequestError> {
+        match self.get::<RateLimitResponse>("/rate_limit") {
+            Ok(r) => {
+                let mut resources = r.resources;
+                for (name, rl) in &mut resources {
+                    rl.resource.get_or_insert_with(|| name.clone());
+                }
+                Ok(Some(resources))
+            }
+            Err(RequestError::Status(e)) if e.status == StatusCode::NOT_FOUND => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Check that at least `n` requests can be made against the `core` rate
+    /// limit before the current rate-limit window resets, failing immediately
+    /// if not.
+    ///
+    /// The remaining quota is taken from the rate-limit headers of the most
+    /// recent response received by the client, if there is one for the
+    /// current window; otherwise, it is fetched from the `/rate_limit`
+    /// endpoint.
+    ///
+    /// Note that this does not actually prevent requests from being made;
+    /// it is merely a way for a job to check whether it can finish before
+    /// starting.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the remaining quota is less than `n` or if fetching
+    /// the rate-limit status fails
+    pub fn reserve_budget(&self, n: u64) -> Result<Budget, BudgetError> {
+        let rate_limit = self.current_rate_limit(CORE_RESOURCE)?;
+        check_budget(n, rate_limit)
+    }
+
+    /// Wait until at least `n` requests can be made against the `core` rate
+    /// limit before the current rate-limit window resets.  If the remaining
+    /// quota in the current window is less than `n`, this method sleeps until
+    /// the window resets and then checks again.
+    ///
+    /// The remaining quota is determined in the same way as for
+    /// [`reserve_budget()`][Client::reserve_budget].  The wait is subject to
+    /// the same constraints as the client's other sleeps: it fails instead of
+    /// sleeping in no-sleep mode or if the client's deadline would pass
+    /// first, and it is cut short if the client is shut down.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if `n` is greater than the total number of requests
+    /// allowed per rate-limit window, if fetching the rate-limit status
+    /// fails, or if waiting for the window to reset is not allowed or is
+    /// interrupted by a shutdown
+    pub fn wait_for_budget(&self, n: u64) -> Result<Budget, BudgetError> {
+        loop {
+            let rate_limit = self.current_rate_limit(CORE_RESOURCE)?;
+            match check_budget(n, rate_limit) {
+                Err(BudgetError::Insufficient { rate_limit, .. }) => {
+                    let reset = rate_limit.reset_time();
+                    let delay = reset.duration_since(SystemTime::now()).unwrap_or_default()
+                        + Duration::from_secs(1);
+                    let url = self.mkurl("/rate_limit")?;
+                    log::debug!(
+                        "Remaining quota of {} requests is less than requested budget of {n}; sleeping for {delay:?} until rate limit resets",
+                        rate_limit.remaining
+                    );
+                    self.sleep_before(
+                        Method::Get,
+                        &url,
+                        delay,
+                        Some(RetryReason::PrimaryRateLimit { reset: Some(reset) }),
+                        ProgressEvent::RateLimitWait {
+                            method: Method::Get,
+                            url: url.clone(),
+                            until: SystemTime::now() + delay,
+                        },
+                    )?;
+                    self.update_usage(|u| u.rate_limit_wait += delay);
+                    self.forget_rate_limit(CORE_RESOURCE);
+                }
+                r => return r,
+            }
+        }
+    }
+
+    /// Return the rate-limit status for the given resource, using the most
+    /// recently received rate-limit headers if they are for the current
+    /// window and querying `/rate_limit` otherwise
+    fn current_rate_limit(&self, resource: &str) -> Result<Option<RateLimit>, RequestError> {
+        if let Some(rl) = self.last_rate_limit(resource)
+            && rl.reset_time() > SystemTime::now()
+        {
+            return Ok(Some(rl));
+        }
+        Ok(self
+            .rate_limit()?
+            .and_then(|mut resources| resources.remove(resource)))
+    }
+}
+
+fn check_budget(n: u64, rate_limit: Option<RateLimit>) -> Result<Budget, BudgetError> {
+    match rate_limit {
+        Some(rl) if n > rl.limit => Err(BudgetError::ExceedsLimit {
+            requested: n,
+            limit: rl.limit,
+        }),
+        Some(rl) if n > rl.remaining => Err(BudgetError::Insufficient {
+            requested: n,
+            rate_limit: rl,
+        }),
+        rate_limit => Ok(Budget {
+            requested: n,
+            rate_limit,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockResponse, MockServer};
+    use serde_json::json;
+    use std::thread;
+
+    fn rate_limit(limit: u64, remaining: u64) -> RateLimit {
+        RateLimit {
+            limit,
+            remaining,
+            used: limit - remaining,
+            reset: 1750000000,
+            resource: Some("core".into()),
+        }
+    }
+
+    #[test]
+    fn check_budget_ok() {
+        let budget = check_budget(100, Some(rate_limit(5000, 4000))).unwrap();
+        assert_eq!(budget.requested, 100);
+        assert_eq!(budget.spare(), Some(3900));
+    }
+
+    #[test]
+    fn check_budget_insufficient() {
+        let r = check_budget(100, Some(rate_limit(5000, 42)));
+        assert!(matches!(
+            r,
+            Err(BudgetError::Insufficient { requested: 100, .. })
+        ));
+    }
+
+    #[test]
+    fn check_budget_exceeds_limit() {
+        let r = check_budget(6000, Some(rate_limit(5000, 5000)));
+        assert!(matches!(
+            r,
+            Err(BudgetError::ExceedsLimit {
+                requested: 6000,
+                limit: 5000
+            })
+        ));
+    }
+
+    #[test]
+    fn check_budget_unlimited() {
+        let budget = check_budget(1_000_000, None).unwrap();
+        assert_eq!(budget.spare(), None);
+    }
+
+    #[test]
+    fn deser_rate_limit_response() {
+        let src = r#"{
+            "resources": {
+                "core": {
+                    "limit": 5000,
+                    "used": 1,
+                    "remaining": 4999,
+                    "reset": 1691591363
+                },
+                "search": {
+                    "limit": 30,
+                    "used": 12,
+                    "remaining": 18,
+                    "reset": 1691591091,
+                    "resource": "search"
+                }
+            },
+            "rate": {
+                "limit": 5000,
+                "used": 1,
+                "remaining": 4999,
+                "reset": 1372700873
+            }
+        }"#;
+        let r = serde_json::from_str::<RateLimitResponse>(src).unwrap();
+        assert_eq!(
+            r.resources["core"],
+            RateLimit {
+                limit: 5000,
+                remaining: 4999,
+                used: 1,
+                reset: 1691591363,
+                resource: None,
+            }
+        );
+        assert_eq!(r.resources["search"].resource.as_deref(), Some("search"));
+    }
+
+    fn exhausted_server() -> MockServer {
+        MockServer::start(|_| {
+            let reset = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_secs()
+                + 3600;
+            MockResponse::json(
+                200,
+                &json!({
+                    "resources": {
+                        "core": {"limit": 5000, "used": 5000, "remaining": 0, "reset": reset}
+                    }
+                }),
+            )
+        })
+    }
+
+    #[test]
+    fn wait_for_budget_no_sleep() {
+        let server = exhausted_server();
+        let client = server.client_builder().with_no_sleep(true).build().unwrap();
+        let r = client.wait_for_budget(10);
+        assert!(matches!(
+            r,
+            Err(BudgetError::Request(RequestError::WouldSleep {
+                reason: Some(ref reason),
+                ..
+            })) if matches!(**reason, RetryReason::PrimaryRateLimit { reset: Some(_) })
+        ));
+    }
+
+    #[test]
+    fn wait_for_budget_deadline() {
+        let server = exhausted_server();
+        let client = server
+            .client_builder()
+            .with_total_deadline(Duration::from_secs(60))
+            .build()
+            .unwrap();
+        let r = client.wait_for_budget(10);
+        assert!(matches!(
+            r,
+            Err(BudgetError::Request(RequestError::DeadlineExceeded { .. }))
+        ));
+    }
+
+    #[test]
+    fn wait_for_budget_shutdown() {
+        let server = exhausted_server();
+        let client = server.client_builder().build().unwrap();
+        thread::scope(|s| {
+            let waiter = s.spawn(|| client.wait_for_budget(10));
+            thread::sleep(Duration::from_millis(200));
+            client.shutdown(Duration::ZERO);
+            assert!(matches!(
+                waiter.join().unwrap(),
+                Err(BudgetError::Request(RequestError::ShutDown { .. }))
+            ));
+        });
+    }
+}
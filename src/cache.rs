@@ -0,0 +1,146 @@
+use super::{Client, Method, RequestError};
+use crate::util::lock;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use ureq::http::{
+    header::{ETAG, HeaderMap, HeaderValue, IF_NONE_MATCH},
+    response::Response,
+    status::StatusCode,
+};
+use url::Url;
+
+/// A cache of responses to GET requests, keyed by URL, used for making
+/// conditional requests with `If-None-Match`
+#[derive(Debug, Default)]
+pub(crate) struct EtagCache(Mutex<HashMap<Url, CachedResponse>>);
+
+/// A response stored in an [`EtagCache`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct CachedResponse {
+    /// The value of the response's `ETag` header
+    etag: HeaderValue,
+
+    /// The response's status code
+    status: StatusCode,
+
+    /// The response's headers
+    headers: HeaderMap,
+
+    /// The response body
+    body: Vec<u8>,
+}
+
+impl CachedResponse {
+    /// Construct a new response from the cached data
+    fn to_response(&self) -> Response<Vec<u8>> {
+        let mut resp = Response::new(self.body.clone());
+        *resp.status_mut() = self.status;
+        *resp.headers_mut() = self.headers.clone();
+        resp
+    }
+
+    /// Construct a new response from the cached data, updated with the
+    /// rate-limit headers from the given 304 response
+    fn refresh(&self, not_modified: &Response<Vec<u8>>) -> Response<Vec<u8>> {
+        let mut resp = self.to_response();
+        for (name, value) in not_modified.headers() {
+            if name.as_str().starts_with("x-ratelimit-") {
+                resp.headers_mut().insert(name, value.clone());
+            }
+        }
+        resp
+    }
+}
+
+impl Client {
+    /// Perform a GET request for a page of paginated results and read the
+    /// response body into memory.  If the client's `ETag` cache is enabled,
+    /// the request is made conditional on the page having changed since it
+    /// was last fetched, and if the server responds with 304, the cached
+    /// response is returned.
+    pub(crate) fn get_page(
+        &self,
+        url: Url,
+        headers: &HeaderMap,
+    ) -> Result<Response<Vec<u8>>, RequestError> {
+        let Some(EtagCache(cache)) = self.etag_cache.as_deref() else {
+            return self.request_buffered::<()>(Method::Get, url, None, headers);
+        };
+        let mut headers = headers.clone();
+        if let Some(cached) = lock(cache).get(&url) {
+            headers.insert(IF_NONE_MATCH, cached.etag.clone());
+        }
+        let resp = self.request_buffered::<()>(Method::Get, url.clone(), None, &headers)?;
+        if resp.status() == StatusCode::NOT_MODIFIED
+            && let Some(resp) = lock(cache).get(&url).map(|cached| cached.refresh(&resp))
+        {
+            log::debug!("Page at {url} not modified; using cached response");
+            return Ok(resp);
+        }
+        if resp.status().is_success()
+            && let Some(etag) = resp.headers().get(ETAG).cloned()
+        {
+            let (parts, body) = resp.into_parts();
+            let cached = CachedResponse {
+                etag,
+                status: parts.status,
+                headers: parts.headers,
+                body,
+            };
+            let resp = cached.to_response();
+            lock(cache).insert(url, cached);
+            Ok(resp)
+        } else {
+            Ok(resp)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ureq::http::header::LINK;
+
+    #[test]
+    fn cached_response_roundtrip() {
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, HeaderValue::from_static(r#"W/"abc123""#));
+        headers.insert(
+            LINK,
+            HeaderValue::from_static(r#"<https://api.github.com/foo?page=2>; rel="next""#),
+        );
+        let cached = CachedResponse {
+            etag: HeaderValue::from_static(r#"W/"abc123""#),
+            status: StatusCode::OK,
+            headers: headers.clone(),
+            body: b"[1, 2, 3]".to_vec(),
+        };
+        let resp = cached.to_response();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers(), &headers);
+        assert_eq!(resp.body(), b"[1, 2, 3]");
+    }
+
+    #[test]
+    fn cached_response_refresh() {
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, HeaderValue::from_static(r#""abc123""#));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static("4999"));
+        let cached = CachedResponse {
+            etag: HeaderValue::from_static(r#""abc123""#),
+            status: StatusCode::OK,
+            headers,
+            body: b"[1, 2, 3]".to_vec(),
+        };
+        let mut not_modified = Response::new(Vec::new());
+        *not_modified.status_mut() = StatusCode::NOT_MODIFIED;
+        not_modified
+            .headers_mut()
+            .insert("x-ratelimit-remaining", HeaderValue::from_static("4990"));
+        let resp = cached.refresh(&not_modified);
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers()[ETAG], r#""abc123""#);
+        assert_eq!(resp.headers()["x-ratelimit-remaining"], "4990");
+        assert_eq!(resp.body(), b"[1, 2, 3]");
+    }
+}
@@ -0,0 +1,97 @@
+use crate::util::lock;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A circuit breaker that trips after a number of consecutive request
+/// failures and then causes further requests to fail fast until a cooldown
+/// period has passed
+#[derive(Debug)]
+pub(crate) struct CircuitBreaker {
+    /// The number of consecutive failures after which the breaker trips
+    threshold: u32,
+
+    /// How long the breaker stays open after tripping
+    cooldown: Duration,
+
+    /// The breaker's mutable state
+    state: Mutex<BreakerState>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+struct BreakerState {
+    /// The number of consecutive failed requests observed so far
+    failures: u32,
+
+    /// If the breaker is open, the time at which it will close again
+    open_until: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(threshold: u32, cooldown: Duration) -> CircuitBreaker {
+        CircuitBreaker {
+            threshold,
+            cooldown,
+            state: Mutex::new(BreakerState::default()),
+        }
+    }
+
+    /// If the breaker is currently open, return the time at which it will
+    /// close
+    pub(crate) fn open_until(&self) -> Option<Instant> {
+        lock(&self.state).open_until.filter(|&t| t > Instant::now())
+    }
+
+    /// Record the outcome of a request attempt.  `failed` should be `true` if
+    /// the attempt failed due to a transport error or a 5xx response.
+    pub(crate) fn record(&self, failed: bool) {
+        let mut state = lock(&self.state);
+        if failed {
+            state.failures = state.failures.saturating_add(1);
+            let failures = state.failures;
+            if failures >= self.threshold {
+                log::debug!(
+                    "{failures} consecutive request failures; opening circuit breaker for {:?}",
+                    self.cooldown
+                );
+                state.open_until = Some(Instant::now() + self.cooldown);
+            }
+        } else {
+            *state = BreakerState::default();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trips_after_threshold() {
+        let breaker = CircuitBreaker::new(3, Duration::from_secs(60));
+        breaker.record(true);
+        breaker.record(true);
+        assert!(breaker.open_until().is_none());
+        breaker.record(true);
+        assert!(breaker.open_until().is_some());
+    }
+
+    #[test]
+    fn success_resets() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+        breaker.record(true);
+        breaker.record(false);
+        breaker.record(true);
+        assert!(breaker.open_until().is_none());
+        breaker.record(true);
+        assert!(breaker.open_until().is_some());
+        breaker.record(false);
+        assert!(breaker.open_until().is_none());
+    }
+
+    #[test]
+    fn closes_after_cooldown() {
+        let breaker = CircuitBreaker::new(1, Duration::ZERO);
+        breaker.record(true);
+        assert!(breaker.open_until().is_none());
+    }
+}
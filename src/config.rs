@@ -0,0 +1,361 @@
+use super::{ClientBuilder, PacingProfile, PausePolicy, RateLimitMode};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::path::PathBuf;
+use std::time::Duration;
+use url::Url;
+
+/// Serializable configuration for a [`Client`][crate::Client], covering all
+/// of the settings of a [`ClientBuilder`] other than the token, proxy, client
+/// certificate, progress hook, and disabling of TLS verification.
+///
+/// This allows programs with configuration files to persist and round-trip
+/// client settings.  Durations are (de)serialized as (possibly fractional)
+/// numbers of seconds, and all fields are optional when deserializing,
+/// defaulting to the same values as [`ClientBuilder::new()`].
+///
+/// A `ClientConfig` can be obtained from a `ClientBuilder` with
+/// [`ClientBuilder::config()`] and turned back into a builder with
+/// [`ClientBuilder::from_config()`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
+pub struct ClientConfig {
+    /// The base GitHub API URL
+    pub api_url: Url,
+
+    /// The value of the `X-GitHub-Api-Version` header
+    pub api_version: String,
+
+    /// The value of the `Accept` header
+    pub accept: String,
+
+    /// The value of the `Accept-Language` header, if any
+    pub accept_language: Option<String>,
+
+    /// The value of the `User-Agent` header
+    pub user_agent: String,
+
+    /// The circuit breaker settings, if a circuit breaker is to be used
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+
+    /// The adaptive backoff settings, if adaptive backoff is to be used
+    pub adaptive_backoff: Option<AdaptiveBackoffConfig>,
+
+    /// The client-wide retry budget settings, if a retry budget is to be used
+    pub retry_budget: Option<RetryBudgetConfig>,
+
+    /// Whether to enable the `ETag` cache
+    pub etag_cache: bool,
+
+    /// Whether to enable the cookie jar
+    pub cookie_jar: bool,
+
+    /// The maximum amount of time to sleep in response to a rate-limit error
+    #[serde(with = "opt_secs")]
+    pub max_rate_limit_wait: Option<Duration>,
+
+    /// How long to wait before retrying after a legacy "abuse detection
+    /// mechanism" error
+    #[serde(with = "secs")]
+    pub abuse_cooldown: Duration,
+
+    /// The client-wide time budget
+    #[serde(with = "opt_secs")]
+    pub total_deadline: Option<Duration>,
+
+    /// The maximum amount of time to wait for more data while reading a
+    /// response body
+    #[serde(with = "opt_secs")]
+    pub read_idle_timeout: Option<Duration>,
+
+    /// What to do with requests issued while the client is paused
+    pub pause_policy: PausePolicy,
+
+    /// How far in advance of the token's expiration to log a warning
+    #[serde(with = "opt_secs")]
+    pub token_expiry_warning: Option<Duration>,
+
+    /// The pacing profile to use, or `None` to detect it from the environment
+    pub pacing_profile: Option<PacingProfile>,
+
+    /// Whether to enable schema drift detection
+    pub drift_detection: bool,
+
+    /// The directory in which to write copies of responses, if any
+    pub response_dump_dir: Option<PathBuf>,
+
+    /// The directory in which to write replay bundles for failed requests,
+    /// if any
+    pub replay_dir: Option<PathBuf>,
+
+    /// Base API URLs to fail over to when the base API URL cannot be reached
+    pub fallback_api_urls: Vec<Url>,
+
+    /// The name of the header in which the token is sent
+    pub auth_header: String,
+
+    /// The authentication scheme with which the token is sent
+    pub auth_scheme: String,
+
+    /// The maximum number of requests that may be in progress at once
+    pub max_concurrent_requests: Option<usize>,
+
+    /// Whether to assume that the server enforces rate limits
+    pub rate_limit_mode: RateLimitMode,
+
+    /// Whether to log request & response headers at the `TRACE` level
+    pub header_logging: bool,
+
+    /// Whether to fail requests instead of sleeping before sending or
+    /// retrying them
+    pub no_sleep: bool,
+
+    /// The names of the response headers to retain
+    pub retained_headers: Vec<String>,
+
+    /// The maximum number of bytes of a status error's response body to show
+    /// when displaying the error with `{}`
+    pub error_body_display: Option<usize>,
+}
+
+impl Default for ClientConfig {
+    fn default() -> ClientConfig {
+        ClientBuilder::new().config()
+    }
+}
+
+/// Serializable circuit breaker settings; see
+/// [`ClientBuilder::with_circuit_breaker()`]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CircuitBreakerConfig {
+    /// The number of consecutive failures after which the breaker trips
+    pub threshold: u32,
+
+    /// How long the breaker stays open after tripping
+    #[serde(with = "secs")]
+    pub cooldown: Duration,
+}
+
+/// Serializable adaptive backoff settings; see
+/// [`ClientBuilder::with_adaptive_backoff()`]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AdaptiveBackoffConfig {
+    /// The number of strained responses within `window` that increases the
+    /// spacing between requests
+    pub threshold: u32,
+
+    /// The period over which strained responses are counted and after which
+    /// the spacing decays
+    #[serde(with = "secs")]
+    pub window: Duration,
+}
+
+/// Serializable retry budget settings; see
+/// [`ClientBuilder::with_retry_budget()`]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RetryBudgetConfig {
+    /// The maximum number of retries per window
+    pub max_retries: u32,
+
+    /// The period over which the budget refills completely
+    #[serde(with = "secs")]
+    pub window: Duration,
+}
+
+impl ClientBuilder {
+    /// Create a new `ClientBuilder` with the settings in `config`.  No token
+    /// is set.
+    pub fn from_config(config: &ClientConfig) -> ClientBuilder {
+        ClientBuilder {
+            user_agent: Cow::from(config.user_agent.clone()),
+            api_url: config.api_url.clone(),
+            api_version: Cow::from(config.api_version.clone()),
+            accept: Cow::from(config.accept.clone()),
+            accept_language: config.accept_language.clone(),
+            circuit_breaker: config.circuit_breaker.map(|cb| (cb.threshold, cb.cooldown)),
+            adaptive_backoff: config.adaptive_backoff.map(|ab| (ab.threshold, ab.window)),
+            retry_budget: config.retry_budget.map(|rb| (rb.max_retries, rb.window)),
+            etag_cache: config.etag_cache,
+            cookie_jar: config.cookie_jar,
+            max_rate_limit_wait: config.max_rate_limit_wait,
+            abuse_cooldown: config.abuse_cooldown,
+            total_deadline: config.total_deadline,
+            read_idle_timeout: config.read_idle_timeout,
+            pause_policy: config.pause_policy,
+            token_expiry_warning: config.token_expiry_warning,
+            pacing_profile: config.pacing_profile,
+            drift_detection: config.drift_detection,
+            response_dump_dir: config.response_dump_dir.clone(),
+            replay_dir: config.replay_dir.clone(),
+            fallback_api_urls: config.fallback_api_urls.clone(),
+            auth_header: Cow::from(config.auth_header.clone()),
+            auth_scheme: Cow::from(config.auth_scheme.clone()),
+            max_concurrent_requests: config.max_concurrent_requests,
+            rate_limit_mode: config.rate_limit_mode,
+            header_logging: config.header_logging,
+            no_sleep: config.no_sleep,
+            retained_headers: config.retained_headers.clone(),
+            error_body_display: config.error_body_display,
+            ..ClientBuilder::new()
+        }
+    }
+
+    /// Return the builder's settings, other than the token, proxy, client
+    /// certificate, progress hook, and disabling of TLS verification, as a
+    /// [`ClientConfig`]
+    pub fn config(&self) -> ClientConfig {
+        ClientConfig {
+            api_url: self.api_url.clone(),
+            api_version: self.api_version.clone().into_owned(),
+            accept: self.accept.clone().into_owned(),
+            accept_language: self.accept_language.clone(),
+            user_agent: self.user_agent.clone().into_owned(),
+            circuit_breaker: self.circuit_breaker.map(|(threshold, cooldown)| {
+                CircuitBreakerConfig {
+                    threshold,
+                    cooldown,
+                }
+            }),
+            adaptive_backoff: self
+                .adaptive_backoff
+                .map(|(threshold, window)| AdaptiveBackoffConfig { threshold, window }),
+            retry_budget: self
+                .retry_budget
+                .map(|(max_retries, window)| RetryBudgetConfig {
+                    max_retries,
+                    window,
+                }),
+            etag_cache: self.etag_cache,
+            cookie_jar: self.cookie_jar,
+            max_rate_limit_wait: self.max_rate_limit_wait,
+            abuse_cooldown: self.abuse_cooldown,
+            total_deadline: self.total_deadline,
+            read_idle_timeout: self.read_idle_timeout,
+            pause_policy: self.pause_policy,
+            token_expiry_warning: self.token_expiry_warning,
+            pacing_profile: self.pacing_profile,
+            drift_detection: self.drift_detection,
+            response_dump_dir: self.response_dump_dir.clone(),
+            replay_dir: self.replay_dir.clone(),
+            fallback_api_urls: self.fallback_api_urls.clone(),
+            auth_header: self.auth_header.clone().into_owned(),
+            auth_scheme: self.auth_scheme.clone().into_owned(),
+            max_concurrent_requests: self.max_concurrent_requests,
+            rate_limit_mode: self.rate_limit_mode,
+            header_logging: self.header_logging,
+            no_sleep: self.no_sleep,
+            retained_headers: self.retained_headers.clone(),
+            error_body_display: self.error_body_display,
+        }
+    }
+}
+
+/// (De)serialize a `Duration` as a number of seconds
+mod secs {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+    use std::time::Duration;
+
+    pub(super) fn serialize<S: Serializer>(d: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(d.as_secs_f64())
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Duration, D::Error> {
+        Duration::try_from_secs_f64(f64::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+/// (De)serialize an `Option<Duration>` as an optional number of seconds
+pub(crate) mod opt_secs {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+    use std::time::Duration;
+
+    pub(crate) fn serialize<S: Serializer>(
+        d: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match d {
+            Some(d) => serializer.serialize_some(&d.as_secs_f64()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Option::<f64>::deserialize(deserializer)?
+            .map(|secs| Duration::try_from_secs_f64(secs).map_err(D::Error::custom))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use serde_json::json;
+
+    #[test]
+    fn roundtrip() {
+        let builder = ClientBuilder::new()
+            .with_api_url(Url::parse("https://github.example.com/api/v3").unwrap())
+            .with_circuit_breaker(5, Duration::from_secs(30))
+            .with_adaptive_backoff(10, Duration::from_secs(20))
+            .with_retry_budget(20, Duration::from_secs(60))
+            .with_max_rate_limit_wait(Duration::from_millis(1500))
+            .with_abuse_cooldown(Duration::from_secs(300))
+            .with_read_idle_timeout(Duration::from_secs(45))
+            .with_pause_policy(PausePolicy::Fail)
+            .with_pacing_profile(PacingProfile::Actions);
+        let config = builder.config();
+        let value = serde_json::to_value(&config).unwrap();
+        assert_eq!(
+            value["circuit_breaker"],
+            json!({"threshold": 5, "cooldown": 30.0})
+        );
+        assert_eq!(
+            value["adaptive_backoff"],
+            json!({"threshold": 10, "window": 20.0})
+        );
+        assert_eq!(
+            value["retry_budget"],
+            json!({"max_retries": 20, "window": 60.0})
+        );
+        assert_eq!(value["max_rate_limit_wait"], json!(1.5));
+        assert_eq!(value["abuse_cooldown"], json!(300.0));
+        assert_eq!(value["total_deadline"], json!(null));
+        assert_eq!(value["read_idle_timeout"], json!(45.0));
+        assert_eq!(value["pause_policy"], json!("fail"));
+        assert_eq!(value["pacing_profile"], json!("actions"));
+        let config2 = serde_json::from_value::<ClientConfig>(value).unwrap();
+        assert_eq!(config2, config);
+        assert_eq!(ClientBuilder::from_config(&config2), builder);
+    }
+
+    #[test]
+    fn partial() {
+        let src = indoc! {r#"
+        {
+            "api_url": "https://github.example.com/api/v3",
+            "etag_cache": true,
+            "total_deadline": 600
+        }
+        "#};
+        let config = serde_json::from_str::<ClientConfig>(src).unwrap();
+        assert_eq!(
+            ClientBuilder::from_config(&config),
+            ClientBuilder::new()
+                .with_api_url(Url::parse("https://github.example.com/api/v3").unwrap())
+                .with_etag_cache(true)
+                .with_total_deadline(Duration::from_secs(600))
+        );
+    }
+
+    #[test]
+    fn token_not_serialized() {
+        let config = ClientBuilder::new().with_token("hunter2").config();
+        let s = serde_json::to_string(&config).unwrap();
+        assert!(!s.contains("hunter2"));
+    }
+}
@@ -0,0 +1,262 @@
+use super::{Client, PathTemplateError, RequestError};
+use crate::template::{encode_segment, is_valid_segment};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// The alphabet used for standard base64 encoding
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Minimal details about a file in a repository, as returned by the contents
+/// API
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct FileMetadata {
+    /// The file's name
+    pub name: String,
+
+    /// The file's path relative to the root of the repository
+    pub path: String,
+
+    /// The blob SHA of the file, which must be passed to
+    /// [`Client::put_file()`] in order to update the file later
+    pub sha: String,
+
+    /// The size of the file in bytes
+    pub size: u64,
+
+    /// The URL of the file on the GitHub website
+    pub html_url: Option<Url>,
+}
+
+/// The result of creating or updating a file with [`Client::put_file()`]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(from = "RawFileCommit")]
+pub struct FileCommit {
+    /// The new file's metadata
+    pub content: FileMetadata,
+
+    /// The SHA of the commit that created or updated the file
+    pub commit_sha: String,
+}
+
+/// The part of the response to `PUT /repos/{owner}/{repo}/contents/{path}`
+/// that we care about
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct RawFileCommit {
+    content: FileMetadata,
+    commit: RawCommit,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct RawCommit {
+    sha: String,
+}
+
+impl From<RawFileCommit> for FileCommit {
+    fn from(raw: RawFileCommit) -> FileCommit {
+        FileCommit {
+            content: raw.content,
+            commit_sha: raw.commit.sha,
+        }
+    }
+}
+
+/// The payload for `PUT /repos/{owner}/{repo}/contents/{path}`
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+struct PutFilePayload<'a> {
+    message: &'a str,
+    content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    branch: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sha: Option<&'a str>,
+}
+
+impl Client {
+    /// Create or update the file at `path` in the given repository so that
+    /// it contains `content`, committing the change with the given commit
+    /// message.
+    ///
+    /// `path` is relative to the root of the repository, with components
+    /// separated by `/` (e.g., `"docs/README.md"`).  Each component is
+    /// percent-encoded, and a path that is empty or that contains an empty,
+    /// `.`, or `..` component is rejected with a
+    /// [`RequestError::PathTemplate`] error, as it would otherwise refer to
+    /// something other than a file in the repository.
+    ///
+    /// `content` is base64-encoded as required by the API.  If `branch` is
+    /// `None`, the change is committed to the repository's default branch.
+    ///
+    /// To create a new file, pass `None` for `sha`.  To update an existing
+    /// file, pass its current blob SHA (e.g., from the
+    /// [`FileMetadata::sha`] field of a previous call); the server responds
+    /// with 409 or 422 if the SHA is missing or out of date.
+    ///
+    /// See [`request()`][Client::request] for information on lower-level
+    /// behavior.
+    #[allow(clippy::too_many_arguments)]
+    pub fn put_file(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        content: &[u8],
+        message: &str,
+        branch: Option<&str>,
+        sha: Option<&str>,
+    ) -> Result<FileCommit, RequestError> {
+        let payload = PutFilePayload {
+            message,
+            content: base64_encode(content),
+            branch,
+            sha,
+        };
+        self.put(&contents_path(owner, repo, path)?, &payload)
+    }
+}
+
+/// The path template for the contents endpoint, used when reporting errors
+const CONTENTS_TEMPLATE: &str = "/repos/{owner}/{repo}/contents/{path}";
+
+/// Construct the API path for the contents endpoint for the given file,
+/// percent-encoding each component of `path` and rejecting components that
+/// would change the structure of the path
+fn contents_path(owner: &str, repo: &str, path: &str) -> Result<String, RequestError> {
+    let mut s = String::from("/repos");
+    let mut push = |name: &str, segment: &str| {
+        if !is_valid_segment(segment) {
+            return Err(RequestError::PathTemplate {
+                template: CONTENTS_TEMPLATE.to_owned(),
+                source: PathTemplateError::InvalidSegment(name.to_owned()),
+            });
+        }
+        s.push('/');
+        encode_segment(&mut s, segment);
+        Ok(())
+    };
+    push("owner", owner)?;
+    push("repo", repo)?;
+    push("contents", "contents")?;
+    for segment in path.split('/') {
+        push("path", segment)?;
+    }
+    Ok(s)
+}
+
+/// Encode `data` as standard base64 with padding
+fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or_default(),
+            chunk.get(2).copied().unwrap_or_default(),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (n >> (18 - 6 * i)) & 0x3F;
+                out.push(char::from(BASE64_ALPHABET[index as usize]));
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(b"", "")]
+    #[case(b"f", "Zg==")]
+    #[case(b"fo", "Zm8=")]
+    #[case(b"foo", "Zm9v")]
+    #[case(b"foob", "Zm9vYg==")]
+    #[case(b"fooba", "Zm9vYmE=")]
+    #[case(b"foobar", "Zm9vYmFy")]
+    #[case(b"\xFF\xFE\x00", "//4A")]
+    fn test_base64_encode(#[case] data: &[u8], #[case] encoded: &str) {
+        assert_eq!(base64_encode(data), encoded);
+    }
+
+    #[test]
+    fn test_contents_path() {
+        assert_eq!(
+            contents_path("octocat", "hello-world", "docs/read me.md").unwrap(),
+            "/repos/octocat/hello-world/contents/docs/read%20me.md"
+        );
+        assert_eq!(
+            contents_path("octocat", "hello-world", ".github/a..b").unwrap(),
+            "/repos/octocat/hello-world/contents/.github/a..b"
+        );
+    }
+
+    #[rstest]
+    #[case("octocat", "hello-world", "../../../user/keys", "path")]
+    #[case("octocat", "hello-world", "docs/./README.md", "path")]
+    #[case("octocat", "hello-world", "docs//README.md", "path")]
+    #[case("octocat", "hello-world", "/docs/README.md", "path")]
+    #[case("octocat", "hello-world", "docs/", "path")]
+    #[case("octocat", "hello-world", "", "path")]
+    #[case("..", "hello-world", "README.md", "owner")]
+    #[case("octocat", ".", "README.md", "repo")]
+    fn contents_path_rejects_invalid_segments(
+        #[case] owner: &str,
+        #[case] repo: &str,
+        #[case] path: &str,
+        #[case] name: &str,
+    ) {
+        let r = contents_path(owner, repo, path);
+        assert!(matches!(
+            r,
+            Err(RequestError::PathTemplate {
+                source: PathTemplateError::InvalidSegment(ref n),
+                ..
+            }) if n == name
+        ));
+    }
+
+    #[test]
+    fn payload_omits_unset_fields() {
+        let payload = PutFilePayload {
+            message: "Add file",
+            content: base64_encode(b"hi"),
+            branch: None,
+            sha: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&payload).unwrap(),
+            serde_json::json!({"message": "Add file", "content": "aGk="})
+        );
+    }
+
+    #[test]
+    fn deserialize_file_commit() {
+        let src = indoc! {r#"
+        {
+            "content": {
+                "name": "hello.txt",
+                "path": "notes/hello.txt",
+                "sha": "95b966ae1c166bd92f8ae7d1c313e738c731dfc3",
+                "size": 9,
+                "url": "https://api.github.com/repos/octocat/Hello-World/contents/notes/hello.txt",
+                "html_url": "https://github.com/octocat/Hello-World/blob/master/notes/hello.txt",
+                "type": "file"
+            },
+            "commit": {
+                "sha": "7638417db6d59f3c431d3e1f261cc637155684cd",
+                "message": "my commit message"
+            }
+        }
+        "#};
+        let fc = serde_json::from_str::<FileCommit>(src).unwrap();
+        assert_eq!(fc.content.path, "notes/hello.txt");
+        assert_eq!(fc.content.size, 9);
+        assert_eq!(fc.commit_sha, "7638417db6d59f3c431d3e1f261cc637155684cd");
+    }
+}
@@ -0,0 +1,163 @@
+use super::Client;
+use crate::util::lock;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use ureq::http::header::{HeaderMap, HeaderValue, SET_COOKIE};
+
+/// A store of cookies set by servers, keyed by host, used for echoing
+/// session & affinity cookies back to the hosts that set them
+///
+/// Only the name & value of each cookie and its `Max-Age` attribute are
+/// honored; other attributes, including `Domain` and `Path`, are ignored, so
+/// a cookie is sent with every subsequent request to the exact host that set
+/// it.
+#[derive(Debug, Default)]
+pub(crate) struct CookieJar(Mutex<HashMap<String, Vec<StoredCookie>>>);
+
+/// A cookie stored in a [`CookieJar`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct StoredCookie {
+    name: String,
+    value: String,
+
+    /// When the cookie expires, if it has a `Max-Age`
+    expires: Option<Instant>,
+}
+
+impl CookieJar {
+    /// Store the cookies set by the `Set-Cookie` headers in `headers` of a
+    /// response from `host`
+    pub(crate) fn store(&self, host: &str, headers: &HeaderMap) {
+        let mut cookies = Vec::new();
+        for value in headers.get_all(SET_COOKIE) {
+            match value.to_str().ok().and_then(parse_set_cookie) {
+                Some(c) => cookies.push(c),
+                None => log::debug!("Ignoring malformed Set-Cookie header from {host}"),
+            }
+        }
+        if cookies.is_empty() {
+            return;
+        }
+        let now = Instant::now();
+        let mut jar = lock(&self.0);
+        let stored = jar.entry(host.to_owned()).or_default();
+        for c in cookies {
+            stored.retain(|s| s.name != c.name);
+            if c.expires.is_none_or(|t| t > now) {
+                log::debug!("Storing cookie {:?} for {host}", c.name);
+                stored.push(c);
+            } else {
+                log::debug!("Removing cookie {:?} for {host}", c.name);
+            }
+        }
+    }
+
+    /// Returns the value of the `Cookie` header to send with a request to
+    /// `host`, if any unexpired cookies are stored for it
+    pub(crate) fn header_value(&self, host: &str) -> Option<HeaderValue> {
+        let now = Instant::now();
+        let mut jar = lock(&self.0);
+        let stored = jar.get_mut(host)?;
+        stored.retain(|c| c.expires.is_none_or(|t| t > now));
+        if stored.is_empty() {
+            return None;
+        }
+        let value = stored
+            .iter()
+            .map(|c| format!("{}={}", c.name, c.value))
+            .collect::<Vec<_>>()
+            .join("; ");
+        HeaderValue::from_str(&value).ok()
+    }
+
+    /// Remove all stored cookies
+    pub(crate) fn clear(&self) {
+        lock(&self.0).clear();
+    }
+}
+
+impl Client {
+    /// Discard all cookies stored in the client's cookie jar, so that the
+    /// next request to each host is sent without cookies.  This has no effect
+    /// if the cookie jar was not enabled with
+    /// [`ClientBuilder::with_cookie_jar()`][crate::ClientBuilder::with_cookie_jar].
+    pub fn clear_cookies(&self) {
+        if let Some(jar) = self.cookies.as_deref() {
+            log::debug!("Clearing cookie jar");
+            jar.clear();
+        }
+    }
+}
+
+/// Parse the value of a `Set-Cookie` header
+fn parse_set_cookie(s: &str) -> Option<StoredCookie> {
+    let mut parts = s.split(';');
+    let (name, value) = parts.next()?.split_once('=')?;
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+    let value = value.trim().trim_matches('"');
+    let mut expires = None;
+    for attr in parts {
+        if let Some((key, val)) = attr.split_once('=')
+            && key.trim().eq_ignore_ascii_case("max-age")
+            && let Ok(secs) = val.trim().parse::<i64>()
+        {
+            let now = Instant::now();
+            expires = Some(match u64::try_from(secs) {
+                Ok(secs) if secs > 0 => now + Duration::from_secs(secs),
+                _ => now,
+            });
+        }
+    }
+    Some(StoredCookie {
+        name: name.to_owned(),
+        value: value.to_owned(),
+        expires,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set_cookies(values: &[&'static str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for v in values {
+            headers.append(SET_COOKIE, HeaderValue::from_static(v));
+        }
+        headers
+    }
+
+    #[test]
+    fn per_host() {
+        let jar = CookieJar::default();
+        jar.store(
+            "ghe.example.com",
+            &set_cookies(&[
+                "_gh_sess=abc123; Path=/; HttpOnly; Secure",
+                "affinity=\"node-7\"; Max-Age=3600",
+                "=bad",
+            ]),
+        );
+        assert_eq!(
+            jar.header_value("ghe.example.com").unwrap(),
+            "_gh_sess=abc123; affinity=node-7"
+        );
+        assert_eq!(jar.header_value("api.github.com"), None);
+
+        jar.store(
+            "ghe.example.com",
+            &set_cookies(&["_gh_sess=def456", "affinity=gone; Max-Age=0"]),
+        );
+        assert_eq!(
+            jar.header_value("ghe.example.com").unwrap(),
+            "_gh_sess=def456"
+        );
+
+        jar.clear();
+        assert_eq!(jar.header_value("ghe.example.com"), None);
+    }
+}
@@ -0,0 +1,278 @@
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Number of seconds in a day
+const DAY_SECS: i64 = 86400;
+
+/// A calendar date in the proleptic Gregorian calendar, stored as a number of
+/// days since the Unix epoch
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub(crate) struct Date(i64);
+
+impl Date {
+    /// Construct a `Date` from a year, month, and day.  Returns `None` if the
+    /// month or day is out of range.
+    pub(crate) fn from_ymd(year: i64, month: u32, day: u32) -> Option<Date> {
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+            return None;
+        }
+        Some(Date(days_from_civil(year, month, day)))
+    }
+
+    /// Return the current date in UTC
+    pub(crate) fn today() -> Date {
+        Date::from_system_time(SystemTime::now())
+    }
+
+    /// Return the UTC date of the given time
+    pub(crate) fn from_system_time(t: SystemTime) -> Date {
+        Date(unix_seconds(t).div_euclid(DAY_SECS))
+    }
+
+    /// Return the year, month, and day of the date
+    pub(crate) fn ymd(self) -> (i64, u32, u32) {
+        civil_from_days(self.0)
+    }
+
+    /// Return the number of days from `self` to `other`
+    pub(crate) fn days_until(self, other: Date) -> i64 {
+        other.0 - self.0
+    }
+
+    /// Return the date `n` days after `self`
+    pub(crate) fn add_days(self, n: i64) -> Date {
+        Date(self.0 + n)
+    }
+}
+
+impl fmt::Display for Date {
+    /// Format the date as `YYYY-MM-DD`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (y, m, d) = self.ymd();
+        write!(f, "{y:04}-{m:02}-{d:02}")
+    }
+}
+
+/// Return the number of seconds since the Unix epoch for the given time,
+/// rounding down to the nearest second
+pub(crate) fn unix_seconds(t: SystemTime) -> i64 {
+    match t.duration_since(UNIX_EPOCH) {
+        Ok(d) => i64::try_from(d.as_secs()).unwrap_or(i64::MAX),
+        Err(e) => {
+            let d = e.duration();
+            let secs = i64::try_from(d.as_secs()).unwrap_or(i64::MAX);
+            if d.subsec_nanos() > 0 {
+                -secs - 1
+            } else {
+                -secs
+            }
+        }
+    }
+}
+
+/// Parse a timestamp of the form `YYYY-MM-DD HH:MM:SS ZONE`, where `ZONE` is
+/// either `UTC` or a numeric offset of the form `+HHMM` or `-HHMM`, as used
+/// by the `GitHub-Authentication-Token-Expiration` header
+pub(crate) fn parse_timestamp(s: &str) -> Option<SystemTime> {
+    let mut parts = s.split_whitespace();
+    let (Some(date), Some(time), Some(zone), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return None;
+    };
+    let mut ymd = date.split('-');
+    let (Some(year), Some(month), Some(day), None) =
+        (ymd.next(), ymd.next(), ymd.next(), ymd.next())
+    else {
+        return None;
+    };
+    let date = Date::from_ymd(year.parse().ok()?, month.parse().ok()?, day.parse().ok()?)?;
+    let mut hms = time.split(':');
+    let (Some(hour), Some(minute), Some(second), None) =
+        (hms.next(), hms.next(), hms.next(), hms.next())
+    else {
+        return None;
+    };
+    let hour = hour.parse::<i64>().ok().filter(|h| (0..24).contains(h))?;
+    let minute = minute.parse::<i64>().ok().filter(|m| (0..60).contains(m))?;
+    let second = second
+        .parse::<i64>()
+        .ok()
+        .filter(|s| (0..=60).contains(s))?;
+    let offset = if zone == "UTC" {
+        0
+    } else {
+        let (sign, hhmm) = match zone.split_at_checked(1)? {
+            ("+", hhmm) => (1, hhmm),
+            ("-", hhmm) => (-1, hhmm),
+            _ => return None,
+        };
+        if hhmm.len() != 4 || !hhmm.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let (hh, mm) = hhmm.split_at(2);
+        sign * (hh.parse::<i64>().ok()? * 3600 + mm.parse::<i64>().ok()? * 60)
+    };
+    let secs = date.0 * DAY_SECS + hour * 3600 + minute * 60 + second - offset;
+    match u64::try_from(secs) {
+        Ok(secs) => UNIX_EPOCH.checked_add(Duration::from_secs(secs)),
+        Err(_) => UNIX_EPOCH.checked_sub(Duration::from_secs(secs.unsigned_abs())),
+    }
+}
+
+/// Format the given time in UTC as a compact timestamp of the form
+/// `YYYYMMDDTHHMMSS.mmmZ`, suitable for use in filenames
+pub(crate) fn compact_timestamp(t: SystemTime) -> String {
+    let secs = unix_seconds(t);
+    let (y, m, d) = Date(secs.div_euclid(DAY_SECS)).ymd();
+    let tod = secs.rem_euclid(DAY_SECS);
+    let millis = t
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_millis());
+    format!(
+        "{y:04}{m:02}{d:02}T{:02}{:02}{:02}.{millis:03}Z",
+        tod / 3600,
+        tod % 3600 / 60,
+        tod % 60
+    )
+}
+
+/// Format the given time in UTC as an ISO 8601 timestamp of the form
+/// `YYYY-MM-DDTHH:MM:SSZ`, rounding down to the nearest second
+pub(crate) fn iso8601_timestamp(t: SystemTime) -> String {
+    let secs = unix_seconds(t);
+    let (y, m, d) = Date(secs.div_euclid(DAY_SECS)).ymd();
+    let tod = secs.rem_euclid(DAY_SECS);
+    format!(
+        "{y:04}-{m:02}-{d:02}T{:02}:{:02}:{:02}Z",
+        tod / 3600,
+        tod % 3600 / 60,
+        tod % 60
+    )
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Return the number of days since the Unix epoch for the given date.
+///
+/// Algorithm taken from
+/// <https://howardhinnant.github.io/date_algorithms.html#days_from_civil>.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let m = i64::from(month);
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Return the year, month, and day for the given number of days since the
+/// Unix epoch.
+///
+/// Algorithm taken from
+/// <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let (Ok(day), Ok(month)) = (
+        u32::try_from(doy - (153 * mp + 2) / 5 + 1),
+        u32::try_from(if mp < 10 { mp + 3 } else { mp - 9 }),
+    ) else {
+        unreachable!("day and month should be small positive integers");
+    };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::time::Duration;
+
+    #[rstest]
+    #[case("2023-02-28 20:56:43 UTC", Some(1677617803))]
+    #[case("2024-04-30 00:00:00 -0700", Some(1714460400))]
+    #[case("2024-04-30 12:30:00 +0530", Some(1714460400))]
+    #[case("1969-12-31 23:59:59 UTC", Some(-1))]
+    #[case("2023-02-29 00:00:00 UTC", None)]
+    #[case("2023-02-28 24:00:00 UTC", None)]
+    #[case("2023-02-28 20:56:43", None)]
+    #[case("2023-02-28 20:56:43 PST", None)]
+    #[case("2023-02-28 20:56:43 +07", None)]
+    #[case("2023-02-28T20:56:43Z", None)]
+    fn test_parse_timestamp(#[case] s: &str, #[case] unix: Option<i64>) {
+        assert_eq!(parse_timestamp(s).map(unix_seconds), unix);
+    }
+
+    #[test]
+    fn test_compact_timestamp() {
+        let t = UNIX_EPOCH + Duration::from_millis(1_792_054_383_042);
+        assert_eq!(compact_timestamp(t), "20261015T085303.042Z");
+    }
+
+    #[test]
+    fn test_iso8601_timestamp() {
+        let t = UNIX_EPOCH + Duration::from_millis(1_792_054_383_942);
+        assert_eq!(iso8601_timestamp(t), "2026-10-15T08:53:03Z");
+        let t = UNIX_EPOCH - Duration::from_millis(500);
+        assert_eq!(iso8601_timestamp(t), "1969-12-31T23:59:59Z");
+    }
+
+    #[rstest]
+    #[case(1970, 1, 1, 0)]
+    #[case(1969, 12, 31, -1)]
+    #[case(2000, 2, 29, 11016)]
+    #[case(2000, 3, 1, 11017)]
+    #[case(2008, 4, 10, 13979)]
+    #[case(2026, 10, 15, 20741)]
+    fn date_roundtrip(#[case] y: i64, #[case] m: u32, #[case] d: u32, #[case] days: i64) {
+        let date = Date::from_ymd(y, m, d).unwrap();
+        assert_eq!(date, Date(days));
+        assert_eq!(date.ymd(), (y, m, d));
+    }
+
+    #[rstest]
+    #[case(2023, 2, 29)]
+    #[case(2024, 13, 1)]
+    #[case(2024, 4, 31)]
+    #[case(2024, 1, 0)]
+    fn invalid_date(#[case] y: i64, #[case] m: u32, #[case] d: u32) {
+        assert_eq!(Date::from_ymd(y, m, d), None);
+    }
+
+    #[test]
+    fn display_date() {
+        let date = Date::from_ymd(2008, 4, 1).unwrap();
+        assert_eq!(date.to_string(), "2008-04-01");
+    }
+
+    #[test]
+    fn date_from_system_time() {
+        let t = UNIX_EPOCH + Duration::from_secs(1_750_000_000);
+        assert_eq!(Date::from_system_time(t).to_string(), "2025-06-15");
+    }
+
+    #[test]
+    fn unix_seconds_negative() {
+        let t = UNIX_EPOCH - Duration::from_millis(500);
+        assert_eq!(unix_seconds(t), -1);
+        assert_eq!(Date::from_system_time(t).to_string(), "1969-12-31");
+    }
+}
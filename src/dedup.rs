@@ -0,0 +1,136 @@
+use super::{PaginationIter, RequestError};
+use serde::de::DeserializeOwned;
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::Hash;
+
+/// An iterator that yields the items of a [`PaginationIter`] while dropping
+/// items whose keys have already been seen.
+///
+/// If the list being paginated changes during a crawl (e.g., because new
+/// issues are created), items can shift from one page to the next and be
+/// yielded twice.  `DedupByKey` remembers the key of every item yielded so
+/// far and skips any later item with the same key.  (Items that shift
+/// backwards onto an already-fetched page will still be missed.)
+///
+/// Errors are yielded as-is.
+///
+/// `DedupByKey` is returned from the [`PaginationIter::dedup_by_key()`]
+/// method.
+pub struct DedupByKey<'a, T, K, F> {
+    inner: PaginationIter<'a, T>,
+    key: F,
+    seen: HashSet<K>,
+    duplicates: u64,
+}
+
+impl<'a, T, K, F> DedupByKey<'a, T, K, F> {
+    /// Return a reference to the inner [`PaginationIter`]
+    pub fn get_ref(&self) -> &PaginationIter<'a, T> {
+        &self.inner
+    }
+
+    /// Returns the number of duplicate items that have been dropped so far
+    pub fn duplicates(&self) -> u64 {
+        self.duplicates
+    }
+}
+
+impl<T: fmt::Debug, K: fmt::Debug, F> fmt::Debug for DedupByKey<'_, T, K, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DedupByKey")
+            .field("inner", &self.inner)
+            .field("seen", &self.seen)
+            .field("duplicates", &self.duplicates)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, K, F> Iterator for DedupByKey<'_, T, K, F>
+where
+    T: DeserializeOwned,
+    K: Eq + Hash,
+    F: FnMut(&T) -> K,
+{
+    type Item = Result<T, RequestError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let item = match self.inner.next()? {
+                Ok(item) => item,
+                Err(e) => return Some(Err(e)),
+            };
+            if self.seen.insert((self.key)(&item)) {
+                return Some(Ok(item));
+            }
+            self.duplicates += 1;
+            log::debug!("Dropping duplicate item from paginated results");
+        }
+    }
+}
+
+impl<T, K, F> std::iter::FusedIterator for DedupByKey<'_, T, K, F>
+where
+    T: DeserializeOwned,
+    K: Eq + Hash,
+    F: FnMut(&T) -> K,
+{
+}
+
+impl<'a, T> PaginationIter<'a, T> {
+    /// Convert the iterator into one that drops items whose keys, as returned
+    /// by `key`, have already been seen during the crawl, so that items that
+    /// shift between pages while they are being fetched are not yielded
+    /// twice.  See [`DedupByKey`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use minigh::Client;
+    /// # use serde::Deserialize;
+    /// #[derive(Deserialize)]
+    /// struct Issue {
+    ///     id: u64,
+    ///     title: String,
+    /// }
+    ///
+    /// # let client = Client::new("token").unwrap();
+    /// for issue in client
+    ///     .paginate::<Issue>("/repos/octocat/hello-world/issues")
+    ///     .dedup_by_key(|issue| issue.id)
+    /// {
+    ///     println!("{}", issue?.title);
+    /// }
+    /// # Ok::<(), minigh::RequestError>(())
+    /// ```
+    pub fn dedup_by_key<K, F>(self, key: F) -> DedupByKey<'a, T, K, F>
+    where
+        K: Eq + Hash,
+        F: FnMut(&T) -> K,
+    {
+        DedupByKey {
+            inner: self,
+            key,
+            seen: HashSet::new(),
+            duplicates: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Client;
+
+    #[test]
+    fn dedup() {
+        let client = Client::new("hunter2").unwrap();
+        let iter = PaginationIter::from_items(&client, vec![1u64, 2, 3, 3, 4, 2, 5]);
+        let mut dedup = iter.dedup_by_key(|&n| n);
+        assert_eq!(
+            dedup.by_ref().collect::<Result<Vec<_>, _>>().unwrap(),
+            [1, 2, 3, 4, 5]
+        );
+        assert_eq!(dedup.duplicates(), 2);
+    }
+}
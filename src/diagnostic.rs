@@ -0,0 +1,256 @@
+use super::{RequestError, StatusError};
+use crate::datetime::{Date, unix_seconds};
+use miette::{Diagnostic, LabeledSpan, SourceCode};
+use std::fmt;
+use std::time::{Instant, SystemTime};
+use ureq::http::status::StatusCode;
+
+impl Diagnostic for StatusError {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(format!(
+            "minigh::status::{}",
+            self.status.as_u16()
+        )))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        let help = match self.status {
+            StatusCode::UNAUTHORIZED => "the token is missing, invalid, expired, or revoked",
+            StatusCode::FORBIDDEN if self.body().is_some_and(|s| s.contains("rate limit")) => {
+                "a rate limit was exceeded; wait for it to reset before retrying"
+            }
+            StatusCode::FORBIDDEN if self.is_abuse_detection() => {
+                "GitHub's abuse detection mechanism was triggered; wait a few minutes and reduce the request rate before retrying"
+            }
+            StatusCode::FORBIDDEN => {
+                "the token may lack the scopes or permissions required for this request"
+            }
+            StatusCode::NOT_FOUND => {
+                "the resource does not exist, or the token cannot access it (GitHub reports private resources as not found)"
+            }
+            StatusCode::BAD_REQUEST
+                if self
+                    .body()
+                    .is_some_and(|s| s.to_ascii_lowercase().contains("api version")) =>
+            {
+                "the server may not support the configured X-GitHub-Api-Version; check with `Client::check_api_version()` and set a supported version with `ClientBuilder::with_api_version()`"
+            }
+            StatusCode::CONFLICT => "the request conflicts with the current state of the resource",
+            StatusCode::UNPROCESSABLE_ENTITY => {
+                "the server rejected the request payload; see the response body for details"
+            }
+            s if s.is_server_error() => "GitHub is having problems; try again later",
+            _ => return None,
+        };
+        Some(Box::new(help))
+    }
+
+    fn url<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        let body = serde_json::from_str::<serde_json::Value>(self.body()?).ok()?;
+        let url = body.get("documentation_url")?.as_str()?.to_owned();
+        Some(Box::new(url))
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        let body: &dyn SourceCode = self.body.as_ref()?;
+        Some(body)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let body = self.body()?;
+        let label = match message_span(body) {
+            Some((offset, len)) => LabeledSpan::new(Some("error message".into()), offset, len),
+            None => LabeledSpan::new(Some("response body".into()), 0, body.len()),
+        };
+        Some(Box::new(std::iter::once(label)))
+    }
+}
+
+impl Diagnostic for RequestError {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        match self {
+            RequestError::Status(e) => e.code(),
+            _ => Some(Box::new(format!("minigh::{}", self.kind()))),
+        }
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        let help = match self {
+            RequestError::Status(e) => return e.help(),
+            RequestError::EmptyRange { .. } => {
+                "the range passed to `Client::get_range()` must contain at least one byte"
+                    .to_owned()
+            }
+            RequestError::Send { .. } => {
+                "check the network connection and any proxy settings".to_owned()
+            }
+            RequestError::CircuitOpen { until, .. } => format!(
+                "too many recent requests failed; requests will be attempted again {}",
+                in_time(*until)
+            ),
+            RequestError::RateLimitWait { retry_at, .. } => format!(
+                "the rate limit resets at {}",
+                format_time(*retry_at)
+            ),
+            RequestError::WouldSleep { retry_at, .. } => {
+                format!("the request can be retried at {}", format_time(*retry_at))
+            }
+            RequestError::Paused { .. } => {
+                "resume the client with `Client::resume()` before making requests".to_owned()
+            }
+            RequestError::ShutDown { .. } => {
+                "the client was shut down with `Client::shutdown()`; create a new client to make more requests".to_owned()
+            }
+            RequestError::DeadlineExceeded { .. } => {
+                "the deadline set with `ClientBuilder::with_total_deadline()` is too short for this workload".to_owned()
+            }
+            RequestError::Deserialize { .. } | RequestError::MissingField { .. } => {
+                "the response did not have the expected structure".to_owned()
+            }
+            #[cfg(feature = "validation")]
+            RequestError::ResponseValidation { .. } => {
+                "the response did not match the validator registered with `ClientBuilder::with_response_validator()`; the API may have changed".to_owned()
+            }
+            RequestError::Path { .. }
+            | RequestError::PathTemplate { .. }
+            | RequestError::Query { .. } => return None,
+        };
+        Some(Box::new(help))
+    }
+
+    fn url<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        match self {
+            RequestError::Status(e) => e.url(),
+            _ => None,
+        }
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        match self {
+            RequestError::Status(e) => e.source_code(),
+            _ => None,
+        }
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        match self {
+            RequestError::Status(e) => e.labels(),
+            _ => None,
+        }
+    }
+}
+
+/// If `body` is a (pretty-printed) JSON object with a `"message"` field whose
+/// value is a string, return the byte offset & length of the string literal
+fn message_span(body: &str) -> Option<(usize, usize)> {
+    let key = body.find("\"message\"")?;
+    let after_key = key + "\"message\"".len();
+    let colon = after_key + body[after_key..].find(':')?;
+    let start = colon + 1 + body[(colon + 1)..].find('"')?;
+    let mut escaped = false;
+    for (i, c) in body[(start + 1)..].char_indices() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => return Some((start, i + 2)),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+/// Format a time as `YYYY-MM-DD HH:MM:SS UTC`
+fn format_time(t: SystemTime) -> String {
+    let secs = unix_seconds(t);
+    let tod = secs.rem_euclid(86400);
+    format!(
+        "{} {:02}:{:02}:{:02} UTC",
+        Date::from_system_time(t),
+        tod / 3600,
+        tod % 3600 / 60,
+        tod % 60
+    )
+}
+
+/// Describe how far in the future `t` is
+fn in_time(t: Instant) -> String {
+    let secs = t.saturating_duration_since(Instant::now()).as_secs();
+    if secs == 0 {
+        "now".to_owned()
+    } else {
+        format!("in {secs}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Method;
+    use std::time::{Duration, UNIX_EPOCH};
+    use url::Url;
+
+    fn status_error(status: StatusCode, body: &str) -> StatusError {
+        StatusError {
+            method: Method::Get,
+            url: Url::parse("https://api.github.com/repos/octocat/nope").unwrap(),
+            status,
+            body: Some(body.to_owned()),
+            request_id: None,
+            redirects: Vec::new(),
+            request: None,
+            display_body_limit: None,
+        }
+    }
+
+    #[test]
+    fn status_diagnostic() {
+        let body = "{\n  \"message\": \"Not \\\"Found\\\"\",\n  \"documentation_url\": \"https://docs.github.com/rest\"\n}";
+        let e = status_error(StatusCode::NOT_FOUND, body);
+        assert_eq!(e.code().unwrap().to_string(), "minigh::status::404");
+        assert!(e.help().is_some());
+        assert_eq!(e.url().unwrap().to_string(), "https://docs.github.com/rest");
+        let labels = e.labels().unwrap().collect::<Vec<_>>();
+        assert_eq!(labels.len(), 1);
+        let span = labels[0].inner();
+        assert_eq!(
+            &body[span.offset()..(span.offset() + span.len())],
+            "\"Not \\\"Found\\\"\""
+        );
+    }
+
+    #[test]
+    fn status_diagnostic_plain_body() {
+        let e = status_error(StatusCode::IM_A_TEAPOT, "short and stout");
+        assert!(e.help().is_none());
+        assert!(e.url().is_none());
+        let labels = e.labels().unwrap().collect::<Vec<_>>();
+        assert_eq!(labels[0].inner().len(), "short and stout".len());
+    }
+
+    #[test]
+    fn api_version_help() {
+        let e = status_error(
+            StatusCode::BAD_REQUEST,
+            r#"{"message": "API version 2026-03-10 is not supported."}"#,
+        );
+        assert!(e.help().unwrap().to_string().contains("check_api_version"));
+        let e = status_error(
+            StatusCode::BAD_REQUEST,
+            r#"{"message": "Problems parsing JSON"}"#,
+        );
+        assert!(e.help().is_none());
+    }
+
+    #[test]
+    fn rate_limit_wait_help() {
+        let e = RequestError::RateLimitWait {
+            method: Method::Get,
+            url: Url::parse("https://api.github.com/zen").unwrap(),
+            retry_at: UNIX_EPOCH + Duration::from_secs(1750000000),
+        };
+        assert_eq!(
+            e.help().unwrap().to_string(),
+            "the rate limit resets at 2025-06-15 15:06:40 UTC"
+        );
+        assert_eq!(e.code().unwrap().to_string(), "minigh::rate_limit_wait");
+    }
+}
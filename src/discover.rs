@@ -0,0 +1,263 @@
+use super::ClientBuilder;
+use crate::util::getenv;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use url::Url;
+
+/// The default GitHub host
+const GITHUB_HOST: &str = "github.com";
+
+impl ClientBuilder {
+    /// Configure the builder's token and base API URL in the same way as the
+    /// [`gh`](https://cli.github.com) CLI.
+    ///
+    /// The host to use is taken from the `GH_HOST` environment variable or,
+    /// if that is not set, from the hosts that `gh` has been logged into (as
+    /// recorded in `hosts.yml` in `gh`'s configuration directory): if `gh` is
+    /// logged into exactly one host, that host is used; otherwise,
+    /// `github.com` is used if it is among them, or else the first host
+    /// listed.  If no hosts are configured, `github.com` is used.
+    ///
+    /// The base API URL is then set to `https://api.github.com` for
+    /// `github.com`, `https://api.{host}` for GHE.com hosts, and
+    /// `https://{host}/api/v3` for GitHub Enterprise Server hosts.
+    ///
+    /// The token for the host is taken from the first of the following that
+    /// is set:
+    ///
+    /// - the `GH_TOKEN` or `GITHUB_TOKEN` environment variable (for
+    ///   `github.com` and GHE.com hosts) or the `GH_ENTERPRISE_TOKEN` or
+    ///   `GITHUB_ENTERPRISE_TOKEN` environment variable (for other hosts)
+    ///
+    /// - the host's `oauth_token` in `hosts.yml`
+    ///
+    /// - the output of `gh auth token --hostname {host}`, for tokens stored
+    ///   in the system keyring
+    ///
+    /// If no token can be found, the builder's token is left unchanged.
+    pub fn with_discovered_auth(mut self) -> Self {
+        let hosts = gh_config_dir()
+            .and_then(|dir| std::fs::read_to_string(dir.join("hosts.yml")).ok())
+            .map(|src| parse_hosts(&src))
+            .unwrap_or_default();
+        let host = getenv("GH_HOST").unwrap_or_else(|| default_host(&hosts).to_owned());
+        log::debug!("Discovering authentication for host {host}");
+        if let Some(api_url) = api_url_for_host(&host) {
+            self = self.with_api_url(api_url);
+        }
+        let token = if is_github_com(&host) {
+            getenv("GH_TOKEN").or_else(|| getenv("GITHUB_TOKEN"))
+        } else {
+            getenv("GH_ENTERPRISE_TOKEN").or_else(|| getenv("GITHUB_ENTERPRISE_TOKEN"))
+        }
+        .or_else(|| {
+            hosts
+                .iter()
+                .find(|h| h.name == host)
+                .and_then(|h| h.oauth_token.clone())
+        })
+        .or_else(|| gh_auth_token(&host));
+        if let Some(token) = token {
+            self = self.with_token(&token);
+        } else {
+            log::debug!("No token found for host {host}");
+        }
+        self
+    }
+}
+
+/// A host entry in `gh`'s `hosts.yml`
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct GhHost {
+    /// The hostname
+    name: String,
+
+    /// The host's `oauth_token` setting, if any
+    oauth_token: Option<String>,
+}
+
+/// Return the path to `gh`'s configuration directory
+fn gh_config_dir() -> Option<PathBuf> {
+    if let Some(dir) = getenv("GH_CONFIG_DIR") {
+        Some(PathBuf::from(dir))
+    } else if let Some(dir) = getenv("XDG_CONFIG_HOME") {
+        Some(PathBuf::from(dir).join("gh"))
+    } else if cfg!(windows)
+        && let Some(dir) = getenv("AppData")
+    {
+        Some(PathBuf::from(dir).join("GitHub CLI"))
+    } else {
+        getenv("HOME").map(|home| PathBuf::from(home).join(".config").join("gh"))
+    }
+}
+
+/// Parse the top-level host entries and their `oauth_token` settings from
+/// the contents of `gh`'s `hosts.yml`.  Only the simple block-style YAML that
+/// `gh` writes is supported.
+fn parse_hosts(src: &str) -> Vec<GhHost> {
+    let mut hosts: Vec<GhHost> = Vec::new();
+    let mut child_indent = None;
+    for line in src.lines() {
+        let content = line.trim_start();
+        if content.is_empty() || content.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - content.len();
+        let Some((key, value)) = content.split_once(':') else {
+            continue;
+        };
+        let value = unquote(value.trim());
+        if indent == 0 {
+            hosts.push(GhHost {
+                name: unquote(key.trim()).to_owned(),
+                oauth_token: None,
+            });
+            child_indent = None;
+        } else if let Some(host) = hosts.last_mut() {
+            let child_indent = *child_indent.get_or_insert(indent);
+            if indent == child_indent && key.trim() == "oauth_token" && !value.is_empty() {
+                host.oauth_token = Some(value.to_owned());
+            }
+        }
+    }
+    hosts
+}
+
+/// Strip matching single or double quotes from around a YAML scalar
+fn unquote(s: &str) -> &str {
+    for q in ['"', '\''] {
+        if let Some(inner) = s.strip_prefix(q).and_then(|t| t.strip_suffix(q)) {
+            return inner;
+        }
+    }
+    s
+}
+
+/// Determine the host to use from the hosts `gh` is logged into
+fn default_host(hosts: &[GhHost]) -> &str {
+    match hosts {
+        [] => GITHUB_HOST,
+        [h] => &h.name,
+        _ if hosts.iter().any(|h| h.name == GITHUB_HOST) => GITHUB_HOST,
+        [h, ..] => &h.name,
+    }
+}
+
+/// Returns `true` if `host` is `github.com` or a GHE.com host, which use the
+/// same token environment variables as `github.com`
+fn is_github_com(host: &str) -> bool {
+    host == GITHUB_HOST || host.ends_with(".ghe.com")
+}
+
+/// Return the base API URL for the given host, or `None` if the host does not
+/// form a valid URL
+fn api_url_for_host(host: &str) -> Option<Url> {
+    let url = if host == GITHUB_HOST {
+        String::from("https://api.github.com")
+    } else if host.ends_with(".ghe.com") {
+        format!("https://api.{host}")
+    } else {
+        format!("https://{host}/api/v3")
+    };
+    Url::parse(&url).ok()
+}
+
+/// Return the base API URL for the GitHub instance whose web URL is `server`
+/// (as given by `GITHUB_SERVER_URL`).  GitHub Enterprise Server instances
+/// keep the scheme, port, and path of `server`.
+pub(crate) fn api_url_for_server(server: &Url) -> Result<Url, url::ParseError> {
+    if let Some(host) = server.host_str()
+        && is_github_com(host)
+        && let Some(url) = api_url_for_host(host)
+    {
+        return Ok(url);
+    }
+    Url::parse(&format!("{}/api/v3", server.as_str().trim_end_matches('/')))
+}
+
+/// Retrieve the token for `host` by running `gh auth token`
+fn gh_auth_token(host: &str) -> Option<String> {
+    let output = Command::new("gh")
+        .args(["auth", "token", "--hostname", host])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let token = String::from_utf8(output.stdout).ok()?;
+    let token = token.trim();
+    (!token.is_empty()).then(|| token.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use rstest::rstest;
+
+    #[test]
+    fn test_parse_hosts() {
+        let src = indoc! {"
+        github.com:
+            users:
+                octocat:
+                    oauth_token: gho_nested
+            git_protocol: https
+            user: octocat
+        ghe.corp.example:
+            oauth_token: \"gho_enterprise\"
+            git_protocol: ssh
+            user: mona
+        "};
+        assert_eq!(
+            parse_hosts(src),
+            [
+                GhHost {
+                    name: "github.com".into(),
+                    oauth_token: None,
+                },
+                GhHost {
+                    name: "ghe.corp.example".into(),
+                    oauth_token: Some("gho_enterprise".into()),
+                },
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case(&[], "github.com")]
+    #[case(&["ghe.corp.example"], "ghe.corp.example")]
+    #[case(&["ghe.corp.example", "github.com"], "github.com")]
+    #[case(&["ghe.corp.example", "ghe.other.example"], "ghe.corp.example")]
+    fn test_default_host(#[case] names: &[&str], #[case] host: &str) {
+        let hosts = names
+            .iter()
+            .map(|&name| GhHost {
+                name: name.into(),
+                oauth_token: None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(default_host(&hosts), host);
+    }
+
+    #[rstest]
+    #[case("github.com", "https://api.github.com/")]
+    #[case("octocorp.ghe.com", "https://api.octocorp.ghe.com/")]
+    #[case("ghe.corp.example", "https://ghe.corp.example/api/v3")]
+    fn test_api_url_for_host(#[case] host: &str, #[case] url: &str) {
+        assert_eq!(api_url_for_host(host).unwrap().as_str(), url);
+    }
+
+    #[rstest]
+    #[case("https://github.com", "https://api.github.com/")]
+    #[case("https://github.com/", "https://api.github.com/")]
+    #[case("https://octocorp.ghe.com", "https://api.octocorp.ghe.com/")]
+    #[case("https://ghe.corp.example", "https://ghe.corp.example/api/v3")]
+    #[case("http://ghe.corp.example:8080/", "http://ghe.corp.example:8080/api/v3")]
+    fn test_api_url_for_server(#[case] server: &str, #[case] url: &str) {
+        let server = Url::parse(server).unwrap();
+        assert_eq!(api_url_for_server(&server).unwrap().as_str(), url);
+    }
+}
@@ -0,0 +1,344 @@
+use super::Method;
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, Deserializer, IntoDeserializer, MapAccess, SeqAccess,
+    Visitor,
+};
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use url::Url;
+
+/// Discrepancies between a JSON value and the type it was deserialized into,
+/// as detected when [`ClientBuilder::with_drift_detection()`] is enabled
+///
+/// [`ClientBuilder::with_drift_detection()`]: crate::ClientBuilder::with_drift_detection
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct DriftReport {
+    /// JSON pointers to fields in the value that were ignored by the target
+    /// type
+    unknown: BTreeSet<String>,
+
+    /// JSON pointers to fields that the target type expected but that were
+    /// absent from the value
+    missing: BTreeSet<String>,
+}
+
+impl DriftReport {
+    /// Log a warning about any discrepancies found in the response to a
+    /// request for `url` when deserializing into a value of type `T`
+    pub(crate) fn log<T>(&self, method: Method, url: &Url) {
+        let type_name = std::any::type_name::<T>();
+        if !self.unknown.is_empty() {
+            log::warn!(
+                "Response to {method} request to {url} contained fields not used by {type_name}: {}",
+                join(&self.unknown)
+            );
+        }
+        if !self.missing.is_empty() {
+            log::warn!(
+                "Response to {method} request to {url} lacked fields expected by {type_name}: {}",
+                join(&self.missing)
+            );
+        }
+    }
+}
+
+/// Deserialize `value` as `T`, recording in `report` any fields of `value`
+/// that `T` ignored and any fields that `T` expected but were absent.
+///
+/// If deserialization fails, `value` is deserialized again without
+/// tracking, so that enabling drift detection never changes whether
+/// deserialization succeeds.
+pub(crate) fn from_value<T: DeserializeOwned>(
+    value: Value,
+    report: &mut DriftReport,
+) -> Result<T, serde_json::Error> {
+    let mut tracked = DriftReport::default();
+    match T::deserialize(Tracked {
+        value: value.clone(),
+        path: String::new(),
+        report: &mut tracked,
+    }) {
+        Ok(val) => {
+            report.unknown.extend(tracked.unknown);
+            report.missing.extend(tracked.missing);
+            Ok(val)
+        }
+        Err(_) => serde_json::from_value(value),
+    }
+}
+
+/// A deserializer for a JSON value at a given path that records ignored and
+/// missing fields
+#[derive(Debug)]
+struct Tracked<'a> {
+    value: Value,
+    path: String,
+    report: &'a mut DriftReport,
+}
+
+impl<'de> Deserializer<'de> for Tracked<'_> {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            Value::Array(items) => visitor.visit_seq(TrackedSeq {
+                items: items.into_iter().enumerate(),
+                path: self.path,
+                report: self.report,
+            }),
+            Value::Object(map) => visitor.visit_map(TrackedMap {
+                entries: map.into_iter(),
+                pending: None,
+                path: self.path,
+                report: self.report,
+            }),
+            other => other.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.value.is_null() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        if let Value::Object(ref map) = self.value {
+            record_missing(map, fields, &self.path, self.report);
+        }
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.value.deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if !self.path.is_empty() {
+            self.report.unknown.insert(self.path);
+        }
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map identifier
+    }
+}
+
+/// Record the names in `fields` that are not keys of `map` as missing
+fn record_missing(map: &Map<String, Value>, fields: &[&str], path: &str, report: &mut DriftReport) {
+    for &f in fields {
+        if !map.contains_key(f) {
+            report.missing.insert(child_path(path, f));
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TrackedSeq<'a> {
+    items: std::iter::Enumerate<std::vec::IntoIter<Value>>,
+    path: String,
+    report: &'a mut DriftReport,
+}
+
+impl<'de> SeqAccess<'de> for TrackedSeq<'_> {
+    type Error = serde_json::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        match self.items.next() {
+            Some((i, value)) => seed
+                .deserialize(Tracked {
+                    value,
+                    path: child_path(&self.path, &i.to_string()),
+                    report: &mut *self.report,
+                })
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+#[derive(Debug)]
+struct TrackedMap<'a> {
+    entries: serde_json::map::IntoIter,
+    pending: Option<(String, Value)>,
+    path: String,
+    report: &'a mut DriftReport,
+}
+
+impl<'de> MapAccess<'de> for TrackedMap<'_> {
+    type Error = serde_json::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        match self.entries.next() {
+            Some((key, value)) => {
+                self.pending = Some((child_path(&self.path, &key), value));
+                seed.deserialize(IntoDeserializer::<'de, serde_json::Error>::into_deserializer(key))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let Some((path, value)) = self.pending.take() else {
+            return Err(de::Error::custom("map value requested before key"));
+        };
+        seed.deserialize(Tracked {
+            value,
+            path,
+            report: &mut *self.report,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+/// Append `key` to the JSON pointer `path`, escaping as per RFC 6901
+fn child_path(path: &str, key: &str) -> String {
+    format!("{path}/{}", key.replace('~', "~0").replace('/', "~1"))
+}
+
+/// Join a set of JSON pointers into a comma-separated list
+fn join(paths: &BTreeSet<String>) -> String {
+    paths
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, Eq, PartialEq)]
+    struct Repo {
+        name: String,
+        owner: Owner,
+        description: Option<String>,
+        topics: Vec<Topic>,
+    }
+
+    #[derive(Debug, Deserialize, Eq, PartialEq)]
+    struct Owner {
+        login: String,
+    }
+
+    #[derive(Debug, Deserialize, Eq, PartialEq)]
+    struct Topic {
+        name: String,
+    }
+
+    #[test]
+    fn detect_drift() {
+        let value = json!({
+            "name": "hello-world",
+            "owner": {"login": "octocat", "id": 1},
+            "topics": [{"name": "rust"}, {"name": "cli", "a/b": 2}],
+            "private": false,
+        });
+        let mut report = DriftReport::default();
+        let repo = from_value::<Repo>(value, &mut report).unwrap();
+        assert_eq!(
+            repo,
+            Repo {
+                name: "hello-world".into(),
+                owner: Owner {
+                    login: "octocat".into()
+                },
+                description: None,
+                topics: vec![
+                    Topic {
+                        name: "rust".into()
+                    },
+                    Topic { name: "cli".into() }
+                ],
+            }
+        );
+        assert_eq!(
+            report
+                .unknown
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>(),
+            ["/owner/id", "/private", "/topics/1/a~1b"]
+        );
+        assert_eq!(
+            report
+                .missing
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>(),
+            ["/description"]
+        );
+    }
+
+    #[test]
+    fn no_drift() {
+        let value = json!({"login": "octocat"});
+        let mut report = DriftReport::default();
+        let owner = from_value::<Owner>(value, &mut report).unwrap();
+        assert_eq!(
+            owner,
+            Owner {
+                login: "octocat".into()
+            }
+        );
+        assert_eq!(report, DriftReport::default());
+    }
+
+    #[test]
+    fn untracked_fallback() {
+        let value = json!({"1": "one", "2": "two"});
+        let mut report = DriftReport::default();
+        let map =
+            from_value::<std::collections::BTreeMap<u32, String>>(value, &mut report).unwrap();
+        assert_eq!(map[&2], "two");
+    }
+
+    #[test]
+    fn deserialize_error() {
+        let value = json!({"login": 42});
+        let mut report = DriftReport::default();
+        assert!(from_value::<Owner>(value, &mut report).is_err());
+    }
+}
@@ -0,0 +1,164 @@
+use super::Method;
+use crate::datetime::compact_timestamp;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+use ureq::{
+    Body,
+    http::{
+        Response,
+        header::{AUTHORIZATION, COOKIE, HeaderMap, HeaderName, PROXY_AUTHORIZATION, SET_COOKIE},
+        response::Parts,
+    },
+};
+use url::Url;
+
+/// Headers whose values are replaced with a placeholder in dumped responses
+const REDACTED_HEADERS: [HeaderName; 4] = [AUTHORIZATION, PROXY_AUTHORIZATION, COOKIE, SET_COOKIE];
+
+/// Writes a copy of each response received by a client to a file in a
+/// directory
+#[derive(Debug)]
+pub(crate) struct ResponseDumper {
+    /// The directory in which to write the files
+    dir: PathBuf,
+
+    /// The number of responses dumped so far, used to keep filenames unique
+    counter: AtomicU64,
+}
+
+impl ResponseDumper {
+    pub(crate) fn new(dir: PathBuf) -> ResponseDumper {
+        ResponseDumper {
+            dir,
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    /// Read the body of `resp`, write the response to a new file in the dump
+    /// directory, and return an equivalent response with the body buffered in
+    /// memory.
+    ///
+    /// Failure to write the file is logged but otherwise ignored.
+    pub(crate) fn dump(
+        &self,
+        method: Method,
+        url: &Url,
+        resp: Response<Body>,
+    ) -> Result<Response<Body>, ureq::Error> {
+        let (parts, mut body) = resp.into_parts();
+        let mut builder = Body::builder();
+        if let Some(mime) = body.mime_type() {
+            builder = builder.mime_type(mime);
+        }
+        if let Some(charset) = body.charset() {
+            builder = builder.charset(charset);
+        }
+        let data = body.read_to_vec()?;
+        let n = self.counter.fetch_add(1, Ordering::Relaxed) + 1;
+        let path = self.dir.join(format!(
+            "{}-{n:06}-{method}.http",
+            compact_timestamp(SystemTime::now())
+        ));
+        match write_dump(&path, method, url, &parts, &data) {
+            Ok(()) => log::debug!("Dumped response to {}", path.display()),
+            Err(e) => log::debug!("Failed to dump response to {}: {e}", path.display()),
+        }
+        Ok(Response::from_parts(parts, builder.data(data)))
+    }
+}
+
+/// Write the request line and the status, headers, and body of a response
+/// to a file at `path`, creating its parent directory if necessary
+fn write_dump(
+    path: &Path,
+    method: Method,
+    url: &Url,
+    parts: &Parts,
+    body: &[u8],
+) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut content = format_head(method, url, parts).into_bytes();
+    content.extend_from_slice(body);
+    std::fs::write(path, content)
+}
+
+/// Format the request line, response status line, and response headers,
+/// with sensitive header values redacted
+fn format_head(method: Method, url: &Url, parts: &Parts) -> String {
+    let mut head = format!("{method} {url}\n{:?} {}\n", parts.version, parts.status);
+    write_headers(&mut head, &parts.headers, &AUTHORIZATION);
+    head.push('\n');
+    head
+}
+
+/// Append a `name: value` line to `out` for each header in `headers`, with
+/// the values of headers that may contain credentials (including
+/// `auth_header`, the header in which the client sends its token) redacted
+pub(crate) fn write_headers(out: &mut String, headers: &HeaderMap, auth_header: &HeaderName) {
+    for (name, value) in headers {
+        if is_redacted(name, auth_header) {
+            let _ = writeln!(out, "{name}: <redacted>");
+        } else {
+            let _ = writeln!(out, "{name}: {}", String::from_utf8_lossy(value.as_bytes()));
+        }
+    }
+}
+
+/// Returns `true` if the value of the header `name` may contain credentials
+/// and should be redacted.  `auth_header` is the header in which the client
+/// sends its token.
+pub(crate) fn is_redacted(name: &HeaderName, auth_header: &HeaderName) -> bool {
+    name == auth_header || REDACTED_HEADERS.contains(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ureq::http::{HeaderValue, StatusCode};
+
+    #[test]
+    fn redacts_headers() {
+        let mut resp = Response::new(());
+        *resp.status_mut() = StatusCode::NOT_FOUND;
+        resp.headers_mut()
+            .insert("x-github-request-id", HeaderValue::from_static("ABCD:1234"));
+        resp.headers_mut()
+            .insert(SET_COOKIE, HeaderValue::from_static("session=hunter2"));
+        let (parts, ()) = resp.into_parts();
+        let url = Url::parse("https://api.github.com/repos/octocat/nope").unwrap();
+        assert_eq!(
+            format_head(Method::Get, &url, &parts),
+            "GET https://api.github.com/repos/octocat/nope\n\
+             HTTP/1.1 404 Not Found\n\
+             x-github-request-id: ABCD:1234\n\
+             set-cookie: <redacted>\n\n"
+        );
+    }
+
+    #[test]
+    fn redacts_custom_auth_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-forwarded-authorization",
+            HeaderValue::from_static("Bearer hunter2"),
+        );
+        headers.insert(
+            "x-github-api-version",
+            HeaderValue::from_static("2026-03-10"),
+        );
+        let mut s = String::new();
+        write_headers(
+            &mut s,
+            &headers,
+            &HeaderName::from_static("x-forwarded-authorization"),
+        );
+        assert_eq!(
+            s,
+            "x-forwarded-authorization: <redacted>\nx-github-api-version: 2026-03-10\n"
+        );
+    }
+}
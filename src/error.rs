@@ -0,0 +1,990 @@
+//! Error types returned by `minigh`
+
+use crate::util::{is_abuse_detection, is_connect_error};
+use crate::{
+    DISPLAY_BODY_LIMIT, Method, PathTemplateError, QueryError, RequestSnapshot, RetryReason,
+};
+use indenter::indented;
+use std::fmt::{self, Write};
+use std::time::{Duration, Instant, SystemTime};
+use thiserror::Error;
+use ureq::http::{header::HeaderName, status::StatusCode};
+use url::Url;
+
+/// A stable classification of a [`RequestError`], as returned by
+/// [`RequestError::kind()`], with one kind per `RequestError` variant
+///
+/// New kinds may be added in future versions alongside new `RequestError`
+/// variants, so matches on `ErrorKind` must include a wildcard arm.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// A [`RequestError::Path`] error
+    Path,
+
+    /// A [`RequestError::PathTemplate`] error
+    PathTemplate,
+
+    /// A [`RequestError::Query`] error
+    Query,
+
+    /// A [`RequestError::EmptyRange`] error
+    EmptyRange,
+
+    /// A [`RequestError::Send`] error
+    Send,
+
+    /// A [`RequestError::Status`] error
+    Status,
+
+    /// A [`RequestError::CircuitOpen`] error
+    CircuitOpen,
+
+    /// A [`RequestError::RateLimitWait`] error
+    RateLimitWait,
+
+    /// A [`RequestError::WouldSleep`] error
+    WouldSleep,
+
+    /// A [`RequestError::Paused`] error
+    Paused,
+
+    /// A [`RequestError::ShutDown`] error
+    ShutDown,
+
+    /// A [`RequestError::DeadlineExceeded`] error
+    DeadlineExceeded,
+
+    /// A [`RequestError::Deserialize`] error
+    Deserialize,
+
+    /// A [`RequestError::MissingField`] error
+    MissingField,
+
+    /// A `RequestError::ResponseValidation` error (only produced when the
+    /// `validation` feature is enabled)
+    ResponseValidation,
+}
+
+impl ErrorKind {
+    /// Returns a short `snake_case` name for the kind of error (e.g.,
+    /// `"status"` or `"rate_limit_wait"`), as used in the `"kind"` field of
+    /// [`RequestError::to_json()`]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::Path => "path",
+            ErrorKind::PathTemplate => "path_template",
+            ErrorKind::Query => "query",
+            ErrorKind::EmptyRange => "empty_range",
+            ErrorKind::Send => "send",
+            ErrorKind::Status => "status",
+            ErrorKind::CircuitOpen => "circuit_open",
+            ErrorKind::RateLimitWait => "rate_limit_wait",
+            ErrorKind::WouldSleep => "would_sleep",
+            ErrorKind::Paused => "paused",
+            ErrorKind::ShutDown => "shut_down",
+            ErrorKind::DeadlineExceeded => "deadline_exceeded",
+            ErrorKind::Deserialize => "deserialize",
+            ErrorKind::MissingField => "missing_field",
+            ErrorKind::ResponseValidation => "response_validation",
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error returned by [`Method`]'s `FromStr` implementation
+#[derive(Clone, Copy, Debug, Eq, Error, Hash, PartialEq)]
+#[error("invalid method name")]
+pub struct ParseMethodError;
+
+/// Error returned when trying to convert an [`ureq::http::Method`] that does
+/// not exist in [`Method`] to the latter type
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("method {0} is not supported by ghreq")]
+pub struct MethodConvertError(
+    /// The input [`ureq::http::Method`] that could not be converted
+    pub ureq::http::Method,
+);
+
+/// Error returned when constructing a `Client` fails
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum BuildClientError {
+    /// A value for a header could not be converted to a
+    /// [`HeaderValue`][ureq::http::HeaderValue]
+    #[error("value supplied for header {header} is invalid")]
+    InvalidHeaderValue {
+        /// The name of the header
+        header: HeaderName,
+        /// The conversion error
+        source: ureq::http::header::InvalidHeaderValue,
+    },
+
+    /// A name supplied for the credential header or the retained headers is
+    /// not a valid header name
+    #[error("invalid header name {name:?}")]
+    InvalidHeaderName {
+        /// The invalid name
+        name: String,
+        /// The conversion error
+        source: ureq::http::header::InvalidHeaderName,
+    },
+
+    /// The proxy could not be configured with the credentials supplied to
+    /// [`ClientBuilder::with_proxy_credentials()`][crate::ClientBuilder::with_proxy_credentials]
+    #[error("invalid proxy credentials")]
+    InvalidProxy {
+        /// The inner [`ureq::Error`]
+        source: ureq::Error,
+    },
+
+    /// The client certificate or private key could not be parsed
+    #[error("invalid client certificate or key")]
+    InvalidClientCert {
+        /// The parse error
+        source: ureq::Error,
+    },
+}
+
+/// Error returned by
+/// [`ClientBuilder::from_env()`][crate::ClientBuilder::from_env] when an
+/// environment variable has an invalid value
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("environment variable {var} has invalid URL value {value:?}")]
+pub struct FromEnvError {
+    /// The name of the environment variable
+    pub var: &'static str,
+
+    /// The value of the environment variable
+    pub value: String,
+
+    /// The inner [`url::ParseError`]
+    pub source: url::ParseError,
+}
+
+/// Error returned when an HTTP request fails
+///
+/// New variants may be added in future versions, so matches on
+/// `RequestError` must include a wildcard arm.  Code that only needs to
+/// distinguish between broad categories of errors can match on
+/// [`RequestError::kind()`] instead.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum RequestError {
+    /// Failed to construct a valid URL from a given path
+    #[error("failed to construct a GitHub API URL from path {path:?}")]
+    Path {
+        /// The inner [`url::ParseError`]
+        source: url::ParseError,
+
+        /// The supplied `path` value
+        path: String,
+    },
+
+    /// Failed to fill in the placeholders in a URL path template
+    #[error("failed to expand path template {template:?}")]
+    PathTemplate {
+        /// The inner [`PathTemplateError`]
+        source: PathTemplateError,
+
+        /// The supplied template
+        template: String,
+    },
+
+    /// Failed to append query parameters to a path
+    #[error("failed to add query parameters to path {path:?}")]
+    Query {
+        /// The inner [`QueryError`]
+        source: QueryError,
+
+        /// The supplied `path` value
+        path: String,
+    },
+
+    /// The byte range passed to
+    /// [`Client::get_range()`][crate::Client::get_range] was empty
+    #[error("empty byte range requested for {method} request to {url}")]
+    EmptyRange {
+        /// The HTTP method of the request
+        method: Method,
+
+        /// The URL to which the request would have been sent
+        url: Url,
+    },
+
+    /// Failed to perform the HTTP request
+    #[error("failed to make {method} request to {url}")]
+    Send {
+        /// The HTTP method of the attempted request
+        method: Method,
+
+        /// The URL to which the request was sent
+        url: Url,
+
+        /// The inner [`ureq::Error`]
+        source: Box<ureq::Error>,
+
+        /// A redacted snapshot of the request, if it was attempted
+        request: Option<Box<RequestSnapshot>>,
+    },
+
+    /// The server returned a 4xx or 5xx status code
+    #[error(transparent)]
+    Status(Box<StatusError>),
+
+    /// The request was not attempted because the client's circuit breaker is
+    /// open
+    #[error("not making {method} request to {url} as circuit breaker is open")]
+    CircuitOpen {
+        /// The HTTP method of the attempted request
+        method: Method,
+
+        /// The URL to which the request would have been sent
+        url: Url,
+
+        /// The time at which the circuit breaker will close
+        until: Instant,
+    },
+
+    /// The server responded with a rate-limit error and asked the client to
+    /// wait longer than the maximum set with
+    /// [`ClientBuilder::with_max_rate_limit_wait()`][crate::ClientBuilder::with_max_rate_limit_wait]
+    /// before retrying
+    #[error(
+        "rate limit exceeded for {method} request to {url}; server requested too long a wait before retrying"
+    )]
+    RateLimitWait {
+        /// The HTTP method of the attempted request
+        method: Method,
+
+        /// The URL to which the request was sent
+        url: Url,
+
+        /// The time at which the request can be retried
+        retry_at: SystemTime,
+    },
+
+    /// The client is in no-sleep mode (see
+    /// [`ClientBuilder::with_no_sleep()`][crate::ClientBuilder::with_no_sleep])
+    /// and would have had to sleep before sending or retrying the request
+    #[error("{method} request to {url} would require waiting before it can be sent")]
+    WouldSleep {
+        /// The HTTP method of the attempted request
+        method: Method,
+
+        /// The URL to which the request would have been sent
+        url: Url,
+
+        /// The time at which the request can be tried again
+        retry_at: SystemTime,
+
+        /// If the client would have retried the request after a failure, the
+        /// reason for the retry; `None` if the client would have waited
+        /// before sending the request in the first place
+        reason: Option<Box<RetryReason>>,
+    },
+
+    /// The request was not attempted because the client is paused and its
+    /// [`PausePolicy`][crate::PausePolicy] is
+    /// [`PausePolicy::Fail`][crate::PausePolicy::Fail]
+    #[error("not making {method} request to {url} as client is paused")]
+    Paused {
+        /// The HTTP method of the attempted request
+        method: Method,
+
+        /// The URL to which the request would have been sent
+        url: Url,
+    },
+
+    /// The request was not attempted because
+    /// [`Client::shutdown()`][crate::Client::shutdown] was called
+    #[error("not making {method} request to {url} as client is shut down")]
+    ShutDown {
+        /// The HTTP method of the attempted request
+        method: Method,
+
+        /// The URL to which the request would have been sent
+        url: Url,
+    },
+
+    /// The client's deadline, set with
+    /// [`ClientBuilder::with_total_deadline()`][crate::ClientBuilder::with_total_deadline],
+    /// passed or would have passed before the request could complete
+    #[error("client deadline exceeded before {method} request to {url} could complete")]
+    DeadlineExceeded {
+        /// The HTTP method of the attempted request
+        method: Method,
+
+        /// The URL to which the request was sent
+        url: Url,
+
+        /// The client's deadline
+        deadline: Instant,
+    },
+
+    /// Failed to deserialize the response body as JSON
+    #[error("failed to deserialize response body from {method} request to {url}")]
+    Deserialize {
+        /// The HTTP method of the attempted request
+        method: Method,
+
+        /// The URL to which the request was sent
+        url: Url,
+
+        /// The inner [`ureq::Error`]
+        source: Box<ureq::Error>,
+    },
+
+    /// The response body had no value at the JSON pointer passed to
+    /// [`Client::get_field()`][crate::Client::get_field]
+    #[error("response body from {method} request to {url} has no value at {pointer:?}")]
+    MissingField {
+        /// The HTTP method of the request
+        method: Method,
+
+        /// The URL to which the request was sent
+        url: Url,
+
+        /// The JSON pointer
+        pointer: Box<str>,
+    },
+
+    /// The response body failed validation by a function registered with
+    /// [`ClientBuilder::with_response_validator()`][crate::ClientBuilder::with_response_validator]
+    ///
+    /// This variant is only available when the `validation` feature is
+    /// enabled.
+    #[cfg(feature = "validation")]
+    #[error("response body from {method} request to {url} failed validation: {}", errors.join("; "))]
+    ResponseValidation {
+        /// The HTTP method of the request
+        method: Method,
+
+        /// The URL to which the request was sent
+        url: Url,
+
+        /// Descriptions of the problems found by the validators
+        errors: Vec<String>,
+    },
+}
+
+impl RequestError {
+    /// If the request failed due to a 4xx or 5xx response, and a nonempty
+    /// response body was read, return the body.  If the response's headers
+    /// indicated the body was JSON, the body is pretty-printed.
+    ///
+    /// The body is also printed when displaying a `RequestError` with `{:#}`.
+    pub fn body(&self) -> Option<&str> {
+        if let RequestError::Status(stat) = self {
+            stat.body()
+        } else {
+            None
+        }
+    }
+
+    /// Returns a redacted snapshot of the request (method, final URL,
+    /// selected headers, and payload size) if the request was sent or
+    /// attempted.  A snapshot is available for [`RequestError::Send`] and
+    /// [`RequestError::Status`] errors returned by the request methods.
+    pub fn request(&self) -> Option<&RequestSnapshot> {
+        match self {
+            RequestError::Send { request, .. } => request.as_deref(),
+            RequestError::Status(e) => e.request(),
+            _ => None,
+        }
+    }
+
+    /// If the request failed because the server asked the client to wait too
+    /// long before retrying or because the client is in no-sleep mode, return
+    /// the time at which the request can be retried
+    pub fn retry_at(&self) -> Option<SystemTime> {
+        if let RequestError::RateLimitWait { retry_at, .. }
+        | RequestError::WouldSleep { retry_at, .. } = self
+        {
+            Some(*retry_at)
+        } else {
+            None
+        }
+    }
+
+    /// Like [`retry_at()`][RequestError::retry_at], but returns a
+    /// [`time::OffsetDateTime`] in UTC
+    ///
+    /// This method is only available when the `time` feature is enabled.
+    #[cfg(feature = "time")]
+    pub fn retry_at_datetime(&self) -> Option<time::OffsetDateTime> {
+        self.retry_at().map(time::OffsetDateTime::from)
+    }
+
+    /// Returns the kind of error
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            RequestError::Path { .. } => ErrorKind::Path,
+            RequestError::PathTemplate { .. } => ErrorKind::PathTemplate,
+            RequestError::Query { .. } => ErrorKind::Query,
+            RequestError::EmptyRange { .. } => ErrorKind::EmptyRange,
+            RequestError::Send { .. } => ErrorKind::Send,
+            RequestError::Status(_) => ErrorKind::Status,
+            RequestError::CircuitOpen { .. } => ErrorKind::CircuitOpen,
+            RequestError::RateLimitWait { .. } => ErrorKind::RateLimitWait,
+            RequestError::WouldSleep { .. } => ErrorKind::WouldSleep,
+            RequestError::Paused { .. } => ErrorKind::Paused,
+            RequestError::ShutDown { .. } => ErrorKind::ShutDown,
+            RequestError::DeadlineExceeded { .. } => ErrorKind::DeadlineExceeded,
+            RequestError::Deserialize { .. } => ErrorKind::Deserialize,
+            RequestError::MissingField { .. } => ErrorKind::MissingField,
+            #[cfg(feature = "validation")]
+            RequestError::ResponseValidation { .. } => ErrorKind::ResponseValidation,
+        }
+    }
+
+    /// Returns a machine-readable description of the error as a JSON object
+    /// with the following fields, for use by CI wrappers and bots that need
+    /// to report failures without parsing the error's `Display` output:
+    ///
+    /// - `"kind"` — a short `snake_case` name for the kind of error, e.g.,
+    ///   `"status"`, `"send"`, or `"rate_limit_wait"`
+    ///
+    /// - `"method"` — the HTTP method of the request, or `null` if the error
+    ///   occurred before a URL was constructed
+    ///
+    /// - `"url"` — the URL of the request, or `null` if the error occurred
+    ///   before a URL was constructed
+    ///
+    /// - `"status"` — the response's status code, for status errors
+    ///
+    /// - `"message"` — the error message, including those of its sources
+    ///
+    /// - `"api_message"` — the `"message"` field of a JSON error response
+    ///   body, if any
+    ///
+    /// - `"request_id"` — the value of the response's `X-GitHub-Request-Id`
+    ///   header, for status errors
+    ///
+    /// - `"retry_after"` — the number of seconds after which the request may
+    ///   be attempted again, for errors caused by rate limits, no-sleep mode,
+    ///   or an open circuit breaker
+    ///
+    /// Fields that do not apply are `null`.
+    pub fn to_json(&self) -> serde_json::Value {
+        let (method, url) = match self {
+            RequestError::Path { .. }
+            | RequestError::PathTemplate { .. }
+            | RequestError::Query { .. } => (None, None),
+            RequestError::Status(e) => (Some(e.method), Some(&e.url)),
+            RequestError::EmptyRange { method, url }
+            | RequestError::Send { method, url, .. }
+            | RequestError::CircuitOpen { method, url, .. }
+            | RequestError::RateLimitWait { method, url, .. }
+            | RequestError::WouldSleep { method, url, .. }
+            | RequestError::Paused { method, url }
+            | RequestError::ShutDown { method, url }
+            | RequestError::DeadlineExceeded { method, url, .. }
+            | RequestError::Deserialize { method, url, .. }
+            | RequestError::MissingField { method, url, .. } => (Some(*method), Some(url)),
+            #[cfg(feature = "validation")]
+            RequestError::ResponseValidation { method, url, .. } => (Some(*method), Some(url)),
+        };
+        let mut message = self.to_string();
+        let mut source = std::error::Error::source(self);
+        while let Some(e) = source {
+            let _ = write!(message, ": {e}");
+            source = e.source();
+        }
+        let (status, api_message, request_id) = match self {
+            RequestError::Status(e) => (
+                Some(e.status.as_u16()),
+                e.body()
+                    .and_then(|b| serde_json::from_str::<serde_json::Value>(b).ok())
+                    .and_then(|v| v.get("message")?.as_str().map(ToOwned::to_owned)),
+                e.request_id.as_deref(),
+            ),
+            _ => (None, None, None),
+        };
+        let retry_after = match self {
+            RequestError::CircuitOpen { until, .. } => {
+                Some(until.saturating_duration_since(Instant::now()))
+            }
+            _ => self.retry_at().map(|t| {
+                t.duration_since(SystemTime::now())
+                    .unwrap_or(Duration::ZERO)
+            }),
+        }
+        .map(|d| d.as_secs_f64().ceil());
+        serde_json::json!({
+            "kind": self.kind().as_str(),
+            "method": method.as_ref().map(Method::as_str),
+            "url": url.map(Url::as_str),
+            "status": status,
+            "message": message,
+            "api_message": api_message,
+            "request_id": request_id,
+            "retry_after": retry_after,
+        })
+    }
+
+    /// Returns the [`std::io::ErrorKind`] that best describes the error, as
+    /// used when converting it to a [`std::io::Error`]:
+    ///
+    /// - For transport errors caused by an I/O error, the kind of the I/O
+    ///   error.  Timeouts are reported as `TimedOut`, and failures to resolve
+    ///   or connect to the server as `ConnectionRefused`.
+    ///
+    /// - For status errors, `NotFound` for 404 and 410, `PermissionDenied` for
+    ///   401 and 403, `InvalidInput` for other 4xx statuses, and `Other` for
+    ///   5xx statuses
+    ///
+    /// - `InvalidInput` for invalid paths, path templates, and byte ranges
+    ///
+    /// - `InvalidData` for undecodable or invalid response bodies
+    ///
+    /// - `WouldBlock` for errors caused by no-sleep mode or pausing
+    ///
+    /// - `TimedOut` for rate-limit waits that were too long and exceeded
+    ///   deadlines
+    ///
+    /// - `NotConnected` for an open circuit breaker or a shut-down client
+    pub fn io_error_kind(&self) -> std::io::ErrorKind {
+        use std::io::ErrorKind;
+        match self {
+            RequestError::Path { .. }
+            | RequestError::PathTemplate { .. }
+            | RequestError::Query { .. }
+            | RequestError::EmptyRange { .. } => ErrorKind::InvalidInput,
+            RequestError::Send { source, .. } => match &**source {
+                ureq::Error::Io(e) => e.kind(),
+                ureq::Error::Timeout(_) => ErrorKind::TimedOut,
+                e if is_connect_error(e) => ErrorKind::ConnectionRefused,
+                _ => ErrorKind::Other,
+            },
+            RequestError::Status(e) => match e.status {
+                StatusCode::NOT_FOUND | StatusCode::GONE => ErrorKind::NotFound,
+                StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ErrorKind::PermissionDenied,
+                s if s.is_client_error() => ErrorKind::InvalidInput,
+                _ => ErrorKind::Other,
+            },
+            RequestError::WouldSleep { .. } | RequestError::Paused { .. } => ErrorKind::WouldBlock,
+            RequestError::RateLimitWait { .. } | RequestError::DeadlineExceeded { .. } => {
+                ErrorKind::TimedOut
+            }
+            RequestError::CircuitOpen { .. } | RequestError::ShutDown { .. } => {
+                ErrorKind::NotConnected
+            }
+            RequestError::Deserialize { source, .. } => match &**source {
+                ureq::Error::Io(e) => e.kind(),
+                ureq::Error::Timeout(_) => ErrorKind::TimedOut,
+                _ => ErrorKind::InvalidData,
+            },
+            RequestError::MissingField { .. } => ErrorKind::InvalidData,
+            #[cfg(feature = "validation")]
+            RequestError::ResponseValidation { .. } => ErrorKind::InvalidData,
+        }
+    }
+
+    /// Returns a process exit status describing the error, so that command-line
+    /// programs built on this library can report failures in a consistent,
+    /// scriptable way:
+    ///
+    /// | Code | Meaning |
+    /// | ---- | ------- |
+    /// | 2 | Invalid path, path template, query parameters, or byte range |
+    /// | 3 | Other 4xx status (e.g., 409 or 422) |
+    /// | 4 | Authentication or authorization failure (401, or 403 other than a rate limit) |
+    /// | 5 | Resource not found (404 or 410) |
+    /// | 6 | Rate limit exceeded (a rate-limit or abuse-detection status, a rate-limit wait that was too long, or no-sleep mode when retrying after a rate limit) |
+    /// | 7 | Server error (5xx status) or an open circuit breaker |
+    /// | 8 | Transport error (e.g., connection failure or timeout) |
+    /// | 9 | Response body could not be decoded or failed validation |
+    /// | 10 | Request stopped by client policy (no-sleep mode, pausing, shutdown, or the total deadline) |
+    ///
+    /// Code 1 is left for failures outside of requests.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            RequestError::Path { .. }
+            | RequestError::PathTemplate { .. }
+            | RequestError::Query { .. }
+            | RequestError::EmptyRange { .. } => 2,
+            RequestError::Status(e) => match e.status {
+                StatusCode::TOO_MANY_REQUESTS => 6,
+                StatusCode::FORBIDDEN if e.body().is_some_and(|s| s.contains("rate limit")) => 6,
+                StatusCode::FORBIDDEN if e.is_abuse_detection() => 6,
+                StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => 4,
+                StatusCode::NOT_FOUND | StatusCode::GONE => 5,
+                s if s.is_server_error() => 7,
+                _ => 3,
+            },
+            RequestError::RateLimitWait { .. } => 6,
+            RequestError::WouldSleep { reason, .. }
+                if reason.as_deref().is_some_and(|r| r.is_rate_limit()) =>
+            {
+                6
+            }
+            RequestError::CircuitOpen { .. } => 7,
+            RequestError::Send { .. } => 8,
+            RequestError::Deserialize { .. } | RequestError::MissingField { .. } => 9,
+            #[cfg(feature = "validation")]
+            RequestError::ResponseValidation { .. } => 9,
+            RequestError::WouldSleep { .. }
+            | RequestError::Paused { .. }
+            | RequestError::ShutDown { .. }
+            | RequestError::DeadlineExceeded { .. } => 10,
+        }
+    }
+}
+
+/// Converts a [`RequestError`] into an I/O error with the kind returned by
+/// [`RequestError::io_error_kind()`], wrapping the original error, for use in
+/// code that must return [`std::io::Result`], such as custom
+/// [`Read`][std::io::Read] implementations
+impl From<RequestError> for std::io::Error {
+    fn from(e: RequestError) -> std::io::Error {
+        std::io::Error::new(e.io_error_kind(), e)
+    }
+}
+
+/// Error returned when the server replies with a 4xx or 5xx status code
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StatusError {
+    /// The HTTP method of the attempted request
+    pub method: Method,
+
+    /// The URL to which the request was sent
+    pub url: Url,
+
+    /// The response's status code
+    pub status: StatusCode,
+
+    /// The response body, if read successfully and nonempty.  If the
+    /// response's headers indicated the body was JSON and the body is at most
+    /// 64 KiB, it is pretty-printed.  Invalid UTF-8 sequences are replaced
+    /// with U+FFFD.
+    pub body: Option<String>,
+
+    /// The value of the response's `X-GitHub-Request-Id` header, if any
+    pub request_id: Option<String>,
+
+    /// The URLs that responded with redirects before the error response was
+    /// received, in the order in which they were requested; empty if no
+    /// redirects were followed.  (The `url` field is the URL of the original
+    /// request.)
+    pub redirects: Vec<Url>,
+
+    /// A redacted snapshot of the request, if available
+    pub request: Option<Box<RequestSnapshot>>,
+
+    /// The maximum number of bytes of the body to show when displaying the
+    /// error with `{}`.  If `None`, the body is only shown with `{:#}`, in
+    /// which case it is truncated to 4 KiB.  Set from
+    /// [`ClientBuilder::with_error_body_display()`][crate::ClientBuilder::with_error_body_display].
+    pub display_body_limit: Option<usize>,
+}
+
+impl StatusError {
+    /// If a nonempty response body was read, return the body.  If the
+    /// response's headers indicated the body was JSON and the body is at most
+    /// 64 KiB, the body is pretty-printed.
+    ///
+    /// The body is also printed when displaying a `StatusError` with `{:#}`,
+    /// truncated to the first 4 KiB; this method always returns the full
+    /// body.
+    pub fn body(&self) -> Option<&str> {
+        self.body.as_deref()
+    }
+
+    /// Returns a redacted snapshot of the request, if available
+    pub fn request(&self) -> Option<&RequestSnapshot> {
+        self.request.as_deref()
+    }
+
+    /// Returns `true` if the error is the legacy "abuse detection mechanism"
+    /// 403 error returned by older GitHub Enterprise Server versions, which,
+    /// unlike an exhausted rate limit, carries no rate-limit headers
+    pub fn is_abuse_detection(&self) -> bool {
+        self.status == StatusCode::FORBIDDEN && self.body().is_some_and(is_abuse_detection)
+    }
+
+    /// Returns a value that displays the error followed by up to `max_len`
+    /// bytes of the response body, regardless of whether `{}` or `{:#}` is
+    /// used
+    pub fn display_with_body(&self, max_len: usize) -> StatusErrorWithBody<'_> {
+        StatusErrorWithBody {
+            error: self,
+            max_len,
+        }
+    }
+
+    /// Write the error message followed by up to `max_len` bytes of the body
+    /// (if `max_len` is not `None`)
+    fn fmt_with_body(&self, f: &mut fmt::Formatter<'_>, max_len: Option<usize>) -> fmt::Result {
+        write!(
+            f,
+            "{} request to {} returned {}",
+            self.method, self.url, self.status
+        )?;
+        if let Some(max_len) = max_len
+            && let Some(text) = self.body()
+        {
+            let mut end = text.len().min(max_len);
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+            let mut out = indented(f).with_str("    ");
+            write!(out, "\n\n{}", &text[..end])?;
+            if end < text.len() {
+                write!(out, "\n... [{} more bytes truncated]", text.len() - end)?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for StatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let max_len = if f.alternate() {
+            Some(DISPLAY_BODY_LIMIT)
+        } else {
+            self.display_body_limit
+        };
+        self.fmt_with_body(f, max_len)
+    }
+}
+
+impl std::error::Error for StatusError {}
+
+/// A [`StatusError`] displayed together with a truncated copy of its response
+/// body, as returned by [`StatusError::display_with_body()`]
+#[derive(Clone, Copy, Debug)]
+pub struct StatusErrorWithBody<'a> {
+    error: &'a StatusError,
+    max_len: usize,
+}
+
+impl fmt::Display for StatusErrorWithBody<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt_with_body(f, Some(self.max_len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ResponseMeta;
+    use crate::util::{REQUEST_ID_HEADER, RedirectChain, Retrier};
+    use ureq::{
+        Body,
+        http::{Response, header::HeaderValue},
+    };
+
+    #[test]
+    fn status_error_to_json() {
+        let url = Url::parse("https://api.github.com/repos/octocat/nope").unwrap();
+        let mut resp = Response::new(
+            Body::builder().data(
+                br#"{"message": "Not Found", "documentation_url": "https://docs.github.com/rest"}"#
+                    .to_vec(),
+            ),
+        );
+        *resp.status_mut() = StatusCode::NOT_FOUND;
+        resp.headers_mut().insert(
+            ureq::http::header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        );
+        resp.headers_mut().insert(
+            REQUEST_ID_HEADER,
+            HeaderValue::from_static("CAFE:1234:5678AB:9ABCDE:65A1B2C3"),
+        );
+        let Err(e) = Retrier::new(Method::Get, url).finalize(Ok(resp)) else {
+            panic!("Retrier did not return error");
+        };
+        assert_eq!(
+            e.to_json(),
+            serde_json::json!({
+                "kind": "status",
+                "method": "GET",
+                "url": "https://api.github.com/repos/octocat/nope",
+                "status": 404,
+                "message": "GET request to https://api.github.com/repos/octocat/nope returned 404 Not Found",
+                "api_message": "Not Found",
+                "request_id": "CAFE:1234:5678AB:9ABCDE:65A1B2C3",
+                "retry_after": null,
+            })
+        );
+    }
+
+    #[test]
+    fn io_error_conversion() {
+        let url = Url::parse("https://api.github.com/repos/octocat/nope").unwrap();
+        let mut resp = Response::new(Body::builder().data(b"{}".to_vec()));
+        *resp.status_mut() = StatusCode::NOT_FOUND;
+        let Err(e) = Retrier::new(Method::Get, url.clone()).finalize(Ok(resp)) else {
+            panic!("Retrier did not return error");
+        };
+        let e = std::io::Error::from(e);
+        assert_eq!(e.kind(), std::io::ErrorKind::NotFound);
+        assert!(e.get_ref().unwrap().is::<RequestError>());
+        let e = RequestError::Send {
+            method: Method::Get,
+            url,
+            source: Box::new(ureq::Error::Io(std::io::ErrorKind::ConnectionReset.into())),
+            request: None,
+        };
+        assert_eq!(e.io_error_kind(), std::io::ErrorKind::ConnectionReset);
+    }
+
+    #[test]
+    fn exit_codes() {
+        let url = Url::parse("https://api.github.com/repos/octocat/hello-world").unwrap();
+        for (status, body, code) in [
+            (StatusCode::UNAUTHORIZED, "Bad credentials", 4),
+            (StatusCode::FORBIDDEN, "Resource not accessible", 4),
+            (StatusCode::FORBIDDEN, "API rate limit exceeded", 6),
+            (
+                StatusCode::FORBIDDEN,
+                "You have triggered an abuse detection mechanism.",
+                6,
+            ),
+            (StatusCode::TOO_MANY_REQUESTS, "", 6),
+            (StatusCode::NOT_FOUND, "Not Found", 5),
+            (StatusCode::UNPROCESSABLE_ENTITY, "Validation Failed", 3),
+            (StatusCode::BAD_GATEWAY, "", 7),
+        ] {
+            let mut resp = Response::new(Body::builder().data(body.as_bytes().to_vec()));
+            *resp.status_mut() = status;
+            let Err(e) = Retrier::new(Method::Get, url.clone()).finalize(Ok(resp)) else {
+                panic!("Retrier did not return error");
+            };
+            assert_eq!(e.exit_code(), code, "wrong exit code for {status}");
+        }
+        let e = RequestError::Send {
+            method: Method::Get,
+            url: url.clone(),
+            source: Box::new(ureq::Error::ConnectionFailed),
+            request: None,
+        };
+        assert_eq!(e.exit_code(), 8);
+        let e = RequestError::WouldSleep {
+            method: Method::Get,
+            url: url.clone(),
+            retry_at: SystemTime::now(),
+            reason: Some(Box::new(RetryReason::SecondaryRateLimit)),
+        };
+        assert_eq!(e.exit_code(), 6);
+        let e = RequestError::WouldSleep {
+            method: Method::Get,
+            url,
+            retry_at: SystemTime::now(),
+            reason: None,
+        };
+        assert_eq!(e.exit_code(), 10);
+    }
+
+    #[test]
+    fn rate_limit_wait_to_json() {
+        let e = RequestError::RateLimitWait {
+            method: Method::Post,
+            url: Url::parse("https://api.github.com/markdown").unwrap(),
+            retry_at: SystemTime::now() + Duration::from_secs(90),
+        };
+        let value = e.to_json();
+        assert_eq!(value["kind"], "rate_limit_wait");
+        assert_eq!(value["status"], serde_json::Value::Null);
+        let retry_after = value["retry_after"].as_f64().unwrap();
+        assert!((89.0..=90.0).contains(&retry_after));
+    }
+
+    #[test]
+    fn status_error_display_truncates() {
+        let url = Url::parse("https://api.github.com/repos/octocat/hello-world").unwrap();
+        let body = format!(
+            "{{\"message\": \"Validation Failed\", \"content\": \"{}\"}}",
+            "é".repeat(40000)
+        );
+        let mut resp = Response::new(Body::builder().data(body.clone().into_bytes()));
+        *resp.status_mut() = StatusCode::UNPROCESSABLE_ENTITY;
+        resp.headers_mut().insert(
+            ureq::http::header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json; charset=utf-8"),
+        );
+        let Err(RequestError::Status(e)) = Retrier::new(Method::Put, url).finalize(Ok(resp)) else {
+            panic!("Retrier did not return status error");
+        };
+        assert_eq!(e.body(), Some(body.as_str()));
+        let shown = format!("{e:#}");
+        assert!(shown.len() < 5000);
+        assert!(shown.contains(" more bytes truncated]"));
+    }
+
+    #[test]
+    fn status_error_display_body_limit() {
+        let url = Url::parse("https://api.github.com/repos/octocat/hello-world").unwrap();
+        let mut resp = Response::new(Body::builder().data(b"Repository was archived".to_vec()));
+        *resp.status_mut() = StatusCode::FORBIDDEN;
+        let Err(RequestError::Status(e)) = Retrier::new(Method::Patch, url)
+            .with_display_body_limit(Some(10))
+            .finalize(Ok(resp))
+        else {
+            panic!("Retrier did not return status error");
+        };
+        assert_eq!(
+            e.to_string(),
+            "PATCH request to https://api.github.com/repos/octocat/hello-world returned 403 Forbidden\n\n    Repository\n    ... [13 more bytes truncated]\n"
+        );
+        assert!(format!("{e:#}").contains("Repository was archived"));
+        let e = StatusError {
+            display_body_limit: None,
+            ..*e
+        };
+        assert!(!e.to_string().contains("Repository"));
+        assert!(
+            e.display_with_body(100)
+                .to_string()
+                .contains("Repository was archived")
+        );
+    }
+
+    #[test]
+    fn status_error_redirects() {
+        let url = Url::parse("https://ghe.example.com/api/v3/user").unwrap();
+        let hop = Url::parse("https://sso.example.com/login").unwrap();
+        let mut resp = Response::new(Body::builder().data(b"<html>".to_vec()));
+        *resp.status_mut() = StatusCode::NOT_FOUND;
+        resp.extensions_mut()
+            .insert(RedirectChain(vec![url.clone(), hop.clone()]));
+        let meta = ResponseMeta::new(url.clone(), &resp);
+        assert_eq!(meta.redirects, [url.clone(), hop.clone()]);
+        let Err(RequestError::Status(e)) =
+            Retrier::new(Method::Get, url.clone()).finalize(Ok(resp))
+        else {
+            panic!("Retrier did not return status error");
+        };
+        assert_eq!(e.redirects, [url, hop]);
+    }
+
+    #[test]
+    fn status_error_body_lossy() {
+        let url = Url::parse("https://api.github.com/zen").unwrap();
+        let mut resp = Response::new(Body::builder().data(b"bad \xFF byte".to_vec()));
+        *resp.status_mut() = StatusCode::BAD_REQUEST;
+        let Err(RequestError::Status(e)) = Retrier::new(Method::Get, url).finalize(Ok(resp)) else {
+            panic!("Retrier did not return status error");
+        };
+        assert_eq!(e.body(), Some("bad \u{FFFD} byte"));
+    }
+
+    #[test]
+    fn kinds() {
+        let e = RequestError::Paused {
+            method: Method::Get,
+            url: Url::parse("https://api.github.com/user").unwrap(),
+        };
+        assert_eq!(e.kind(), ErrorKind::Paused);
+        assert_eq!(e.kind().to_string(), "paused");
+        assert_eq!(e.to_json()["kind"], "paused");
+    }
+}
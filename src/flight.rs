@@ -0,0 +1,149 @@
+use super::RequestError;
+use crate::util::lock;
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use url::Url;
+
+/// A registry of in-flight GET requests, used to coalesce concurrent requests
+/// for the same URL into a single network request
+#[derive(Debug, Default)]
+pub(crate) struct Singleflight {
+    calls: Mutex<HashMap<Url, Arc<Call>>>,
+}
+
+/// A single in-flight request
+#[derive(Debug, Default)]
+struct Call {
+    /// `None` while the request is in flight.  Once it has completed, this is
+    /// `Some(Some(body))` if it succeeded or `Some(None)` if it failed.
+    outcome: Mutex<Option<Option<Arc<[u8]>>>>,
+
+    /// Notified when the request completes
+    done: Condvar,
+}
+
+impl Singleflight {
+    /// Fetch the response body for `url` by calling `fetch`, unless another
+    /// thread is already fetching the same URL, in which case wait for that
+    /// thread's request to complete and return its result instead.
+    ///
+    /// As errors cannot be shared between callers, if the other thread's
+    /// request fails, `fetch` is called to make a request of our own.
+    pub(crate) fn run<F>(&self, url: &Url, fetch: F) -> Result<Arc<[u8]>, RequestError>
+    where
+        F: FnOnce() -> Result<Vec<u8>, RequestError>,
+    {
+        let (call, leader) = {
+            let mut calls = lock(&self.calls);
+            if let Some(call) = calls.get(url) {
+                (Arc::clone(call), false)
+            } else {
+                let call = Arc::new(Call::default());
+                calls.insert(url.clone(), Arc::clone(&call));
+                (call, true)
+            }
+        };
+        if leader {
+            let mut completion = Completion {
+                flights: self,
+                url,
+                call: &call,
+                body: None,
+            };
+            let r = fetch().map(Arc::<[u8]>::from);
+            if let Ok(body) = &r {
+                completion.body = Some(Arc::clone(body));
+            }
+            drop(completion);
+            r
+        } else {
+            log::debug!("Waiting on in-flight GET request to {url}");
+            let mut outcome = lock(&call.outcome);
+            while outcome.is_none() {
+                outcome = call
+                    .done
+                    .wait(outcome)
+                    .unwrap_or_else(PoisonError::into_inner);
+            }
+            if let Some(Some(body)) = outcome.as_ref() {
+                Ok(Arc::clone(body))
+            } else {
+                drop(outcome);
+                log::debug!("In-flight GET request to {url} failed; making own request");
+                fetch().map(Arc::<[u8]>::from)
+            }
+        }
+    }
+}
+
+/// Guard that publishes the outcome of a leader's request and deregisters it
+/// when dropped, so that waiting threads are woken even if the leader panics
+#[derive(Debug)]
+struct Completion<'a> {
+    flights: &'a Singleflight,
+    url: &'a Url,
+    call: &'a Call,
+    body: Option<Arc<[u8]>>,
+}
+
+impl Drop for Completion<'_> {
+    fn drop(&mut self) {
+        lock(&self.flights.calls).remove(self.url);
+        *lock(&self.call.outcome) = Some(self.body.take());
+        self.call.done.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Barrier;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn coalesce_concurrent() {
+        let flights = Singleflight::default();
+        let url = Url::parse("https://api.github.com/rate_limit").unwrap();
+        let fetches = AtomicUsize::new(0);
+        let barrier = Barrier::new(4);
+        let bodies = thread::scope(|s| {
+            let handles = std::iter::repeat_with(|| {
+                s.spawn(|| {
+                    barrier.wait();
+                    flights.run(&url, || {
+                        fetches.fetch_add(1, Ordering::SeqCst);
+                        thread::sleep(Duration::from_millis(200));
+                        Ok(b"{}".to_vec())
+                    })
+                })
+            })
+            .take(4)
+            .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|h| h.join().unwrap().unwrap())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        assert!(bodies.iter().all(|b| &**b == b"{}"));
+        assert!(lock(&flights.calls).is_empty());
+    }
+
+    #[test]
+    fn sequential_not_coalesced() {
+        let flights = Singleflight::default();
+        let url = Url::parse("https://api.github.com/rate_limit").unwrap();
+        let mut fetches = 0;
+        for _ in 0..2 {
+            flights
+                .run(&url, || {
+                    fetches += 1;
+                    Ok(Vec::new())
+                })
+                .unwrap();
+        }
+        assert_eq!(fetches, 2);
+    }
+}
@@ -0,0 +1,232 @@
+use super::{Client, PaginationIter, RequestError};
+use serde::de::DeserializeOwned;
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A comparison function for the items of a sorted merge
+type CompareFn<'a, T> = Box<dyn FnMut(&T, &T) -> Ordering + 'a>;
+
+/// An iterator that runs several [`PaginationIter`]s and merges their items
+/// into a single stream, e.g., for fetching the issues of several
+/// repositories at once.
+///
+/// By default, items are taken from the inner iterators in round-robin
+/// order, one at a time.  Calling
+/// [`ordered_by_key()`][MergedPagination::ordered_by_key] instead makes the
+/// iterator perform a sorted merge.
+///
+/// Pages are only fetched from an inner iterator when its next item is
+/// needed, and all requests are made through the inner iterators' clients, so
+/// they are subject to the same pacing and rate-limit handling as any other
+/// requests.
+///
+/// If an inner iterator yields an error, the error is yielded as-is, and
+/// merging continues with the remaining items of the other iterators.
+///
+/// `MergedPagination` is returned from the [`Client::paginate_merged()`]
+/// method and can also be constructed from arbitrary `PaginationIter`s with
+/// [`MergedPagination::new()`].
+pub struct MergedPagination<'a, T> {
+    sources: Vec<PaginationIter<'a, T>>,
+
+    /// For a sorted merge, the next item from each source, if it has been
+    /// fetched
+    heads: Vec<Option<T>>,
+
+    /// Which sources have been exhausted
+    finished: Vec<bool>,
+
+    /// For a round-robin merge, the index of the next source to take an item
+    /// from
+    next_source: usize,
+
+    /// For a sorted merge, the comparison function for items
+    compare: Option<CompareFn<'a, T>>,
+}
+
+impl<'a, T> MergedPagination<'a, T> {
+    /// Create a new `MergedPagination` that merges the items of the given
+    /// iterators in round-robin order
+    pub fn new<I>(sources: I) -> Self
+    where
+        I: IntoIterator<Item = PaginationIter<'a, T>>,
+    {
+        let sources = sources.into_iter().collect::<Vec<_>>();
+        let heads = sources.iter().map(|_| None).collect();
+        let finished = vec![false; sources.len()];
+        MergedPagination {
+            sources,
+            heads,
+            finished,
+            next_source: 0,
+            compare: None,
+        }
+    }
+
+    /// Merge the items in ascending order of the keys returned by `key`.
+    /// Items with equal keys are yielded in the order of their iterators.
+    /// (Use [`std::cmp::Reverse`] to merge in descending order.)
+    ///
+    /// The merged output is only sorted if each inner iterator yields its
+    /// items in ascending order of `key`, e.g., when each iterator fetches
+    /// issues sorted by creation date.
+    pub fn ordered_by_key<K, F>(mut self, mut key: F) -> Self
+    where
+        K: Ord,
+        F: FnMut(&T) -> K + 'a,
+    {
+        self.compare = Some(Box::new(move |a, b| key(a).cmp(&key(b))));
+        self
+    }
+
+    /// Return a reference to the inner [`PaginationIter`]s
+    pub fn get_ref(&self) -> &[PaginationIter<'a, T>] {
+        &self.sources
+    }
+}
+
+impl<T: DeserializeOwned> MergedPagination<'_, T> {
+    /// Yield the next item in round-robin order
+    fn next_round_robin(&mut self) -> Option<Result<T, RequestError>> {
+        let n = self.sources.len();
+        for offset in 0..n {
+            let i = (self.next_source + offset) % n;
+            if self.finished[i] {
+                continue;
+            }
+            match self.sources[i].next() {
+                Some(item) => {
+                    self.next_source = (i + 1) % n;
+                    return Some(item);
+                }
+                None => self.finished[i] = true,
+            }
+        }
+        None
+    }
+
+    /// Yield the next item in sorted order
+    fn next_sorted(&mut self) -> Option<Result<T, RequestError>> {
+        for (i, source) in self.sources.iter_mut().enumerate() {
+            if self.heads[i].is_none() && !self.finished[i] {
+                match source.next() {
+                    Some(Ok(value)) => self.heads[i] = Some(value),
+                    Some(Err(e)) => return Some(Err(e)),
+                    None => self.finished[i] = true,
+                }
+            }
+        }
+        let compare = self.compare.as_mut()?;
+        let mut least: Option<(usize, &T)> = None;
+        for (i, head) in self.heads.iter().enumerate() {
+            if let Some(value) = head
+                && least.is_none_or(|(_, m)| compare(value, m) == Ordering::Less)
+            {
+                least = Some((i, value));
+            }
+        }
+        let (i, _) = least?;
+        self.heads[i].take().map(Ok)
+    }
+}
+
+impl<T> fmt::Debug for MergedPagination<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MergedPagination")
+            .field("sources", &self.sources)
+            .field("heads", &self.heads)
+            .field("finished", &self.finished)
+            .field("next_source", &self.next_source)
+            .field("ordered", &self.compare.is_some())
+            .finish()
+    }
+}
+
+impl<T> Iterator for MergedPagination<'_, T>
+where
+    T: DeserializeOwned,
+{
+    type Item = Result<T, RequestError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.compare.is_some() {
+            self.next_sorted()
+        } else {
+            self.next_round_robin()
+        }
+    }
+}
+
+impl<T> std::iter::FusedIterator for MergedPagination<'_, T> where T: DeserializeOwned {}
+
+impl Client {
+    /// Returns a [`MergedPagination`] iterator that paginates over each of
+    /// `paths` as with [`paginate()`][Client::paginate] and merges the
+    /// results into a single stream of items in round-robin order.  Use
+    /// [`MergedPagination::ordered_by_key()`] to merge the items in sorted
+    /// order instead.
+    pub fn paginate_merged<T, I, S>(&self, paths: I) -> MergedPagination<'_, T>
+    where
+        T: DeserializeOwned,
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        MergedPagination::new(paths.into_iter().map(|p| self.paginate(p.as_ref())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Reverse;
+
+    fn sources(client: &Client) -> Vec<PaginationIter<'_, u32>> {
+        vec![
+            PaginationIter::from_items(client, vec![1, 4, 9]),
+            PaginationIter::from_items(client, vec![]),
+            PaginationIter::from_items(client, vec![2, 3, 10, 11]),
+        ]
+    }
+
+    #[test]
+    fn round_robin() {
+        let client = Client::builder().build().unwrap();
+        let items = MergedPagination::new(sources(&client))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(items, [1, 2, 4, 3, 9, 10, 11]);
+    }
+
+    #[test]
+    fn ordered() {
+        let client = Client::builder().build().unwrap();
+        let items = MergedPagination::new(sources(&client))
+            .ordered_by_key(|&n| n)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(items, [1, 2, 3, 4, 9, 10, 11]);
+    }
+
+    #[test]
+    fn ordered_descending_ties() {
+        let client = Client::builder().build().unwrap();
+        let items = MergedPagination::new([
+            PaginationIter::from_items(&client, vec![(5, 'a'), (3, 'a')]),
+            PaginationIter::from_items(&client, vec![(5, 'b'), (4, 'b')]),
+        ])
+        .ordered_by_key(|&(n, _)| Reverse(n))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+        assert_eq!(items, [(5, 'a'), (5, 'b'), (4, 'b'), (3, 'a')]);
+    }
+
+    #[test]
+    fn empty() {
+        let client = Client::builder().build().unwrap();
+        let mut iter = client.paginate_merged::<u32, _, &str>([]);
+        assert!(iter.next().is_none());
+    }
+}
@@ -0,0 +1,349 @@
+use super::ServerWarning;
+use super::util::{
+    RATELIMIT_REMAINING_HEADER, RATELIMIT_RESET_HEADER, REQUEST_ID_HEADER, get_link, get_links,
+    get_redirects,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use ureq::http::{
+    header::{CONTENT_LANGUAGE, HeaderMap, HeaderName},
+    response::Response,
+    status::StatusCode,
+};
+use url::Url;
+
+/// The name of the `X-RateLimit-Limit` header
+const RATELIMIT_LIMIT_HEADER: HeaderName = HeaderName::from_static("x-ratelimit-limit");
+
+/// The name of the `X-RateLimit-Used` header
+const RATELIMIT_USED_HEADER: HeaderName = HeaderName::from_static("x-ratelimit-used");
+
+/// The name of the `X-RateLimit-Resource` header
+const RATELIMIT_RESOURCE_HEADER: HeaderName = HeaderName::from_static("x-ratelimit-resource");
+
+/// Metadata about a response received from the server
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ResponseMeta {
+    /// The URL to which the corresponding request was sent
+    pub url: Url,
+
+    /// The response's status code
+    pub status: StatusCode,
+
+    /// The response's headers
+    pub headers: HeaderMap,
+
+    /// The rate-limit information reported in the response's headers, if
+    /// any
+    pub rate_limit: Option<RateLimit>,
+
+    /// The URLs that responded with redirects before this response was
+    /// received, in the order in which they were requested; empty if no
+    /// redirects were followed.  (The `url` field is the URL of the final
+    /// request.)
+    pub redirects: Vec<Url>,
+}
+
+impl ResponseMeta {
+    pub(crate) fn new<B>(url: Url, r: &Response<B>) -> ResponseMeta {
+        let headers = r.headers().clone();
+        let rate_limit = RateLimit::from_headers(&headers);
+        ResponseMeta {
+            url,
+            status: r.status(),
+            headers,
+            rate_limit,
+            redirects: get_redirects(r.extensions()),
+        }
+    }
+
+    /// Returns the URL with the given relation type (e.g., `"next"` or
+    /// `"last"`), if any, in the response's `Link` header
+    pub fn link(&self, rel: &str) -> Option<Url> {
+        get_link(&self.headers, rel)
+    }
+
+    /// Returns the warnings, if any, in the response's `Warning` headers
+    pub fn warnings(&self) -> Vec<ServerWarning> {
+        ServerWarning::from_headers(&self.headers)
+    }
+
+    /// Returns the value of the response's `Content-Language` header, if any,
+    /// i.e., the language that the server chose for messages in the response
+    /// body in response to the `Accept-Language` header set with
+    /// [`ClientBuilder::with_accept_language()`][crate::ClientBuilder::with_accept_language]
+    pub fn content_language(&self) -> Option<&str> {
+        self.headers.get(CONTENT_LANGUAGE)?.to_str().ok()
+    }
+}
+
+/// Extension methods for extracting GitHub-specific information from the
+/// headers of a response, for use with responses returned by
+/// [`Client::request()`][super::Client::request] and similar low-level
+/// methods
+pub trait ResponseExt {
+    /// Returns the URL with the given relation type (e.g., `"next"` or
+    /// `"last"`), if any, in the response's `Link` header
+    fn link(&self, rel: &str) -> Option<Url>;
+
+    /// Returns the `rel="next"` URL, if any, in the response's `Link` header
+    fn next_link(&self) -> Option<Url> {
+        self.link("next")
+    }
+
+    /// Returns a map from relation types to URLs for all of the links in the
+    /// response's `Link` header
+    fn link_relations(&self) -> HashMap<String, Url>;
+
+    /// Returns the rate-limit information reported in the response's
+    /// `X-RateLimit-*` headers, if any
+    fn rate_limit(&self) -> Option<RateLimit>;
+
+    /// Returns the value of the response's `X-GitHub-Request-Id` header, if
+    /// any, which can be given to GitHub Support when reporting a problem
+    /// with a request
+    fn request_id(&self) -> Option<&str>;
+
+    /// Returns the warnings, if any, in the response's `Warning` headers,
+    /// such as notices that the request used a deprecated feature
+    fn warnings(&self) -> Vec<ServerWarning>;
+
+    /// Returns the value of the response's `Content-Language` header, if any
+    fn content_language(&self) -> Option<&str>;
+}
+
+impl<B> ResponseExt for Response<B> {
+    fn link(&self, rel: &str) -> Option<Url> {
+        get_link(self.headers(), rel)
+    }
+
+    fn link_relations(&self) -> HashMap<String, Url> {
+        get_links(self.headers())
+    }
+
+    fn rate_limit(&self) -> Option<RateLimit> {
+        RateLimit::from_headers(self.headers())
+    }
+
+    fn request_id(&self) -> Option<&str> {
+        self.headers().get(REQUEST_ID_HEADER)?.to_str().ok()
+    }
+
+    fn warnings(&self) -> Vec<ServerWarning> {
+        ServerWarning::from_headers(self.headers())
+    }
+
+    fn content_language(&self) -> Option<&str> {
+        self.headers().get(CONTENT_LANGUAGE)?.to_str().ok()
+    }
+}
+
+/// Rate-limit information as reported by the `X-RateLimit-*` headers of a
+/// response or by the `/rate_limit` endpoint
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct RateLimit {
+    /// The maximum number of requests that can be made per hour
+    pub limit: u64,
+
+    /// The number of requests remaining in the current rate-limit window
+    pub remaining: u64,
+
+    /// The number of requests made in the current rate-limit window
+    pub used: u64,
+
+    /// The time at which the current rate-limit window resets, in seconds
+    /// since the Unix epoch
+    pub reset: u64,
+
+    /// The rate-limit resource (e.g., `"core"` or `"search"`) that the request
+    /// counted against, if reported
+    #[serde(default)]
+    pub resource: Option<String>,
+}
+
+impl RateLimit {
+    /// Extract rate-limit information from a set of response headers.
+    /// Returns `None` if any of the `X-RateLimit-Limit`,
+    /// `X-RateLimit-Remaining`, or `X-RateLimit-Reset` headers are missing or
+    /// invalid.  If `X-RateLimit-Used` is missing, it is computed from the
+    /// limit and the remaining quota.
+    pub fn from_headers(headers: &HeaderMap) -> Option<RateLimit> {
+        let get = |name: &HeaderName| headers.get(name)?.to_str().ok();
+        let get_u64 = |name: &HeaderName| get(name)?.parse::<u64>().ok();
+        let limit = get_u64(&RATELIMIT_LIMIT_HEADER)?;
+        let remaining = get_u64(&RATELIMIT_REMAINING_HEADER)?;
+        let reset = get_u64(&RATELIMIT_RESET_HEADER)?;
+        let used =
+            get_u64(&RATELIMIT_USED_HEADER).unwrap_or_else(|| limit.saturating_sub(remaining));
+        let resource = get(&RATELIMIT_RESOURCE_HEADER).map(ToOwned::to_owned);
+        Some(RateLimit {
+            limit,
+            remaining,
+            used,
+            reset,
+            resource,
+        })
+    }
+
+    /// Returns the time at which the current rate-limit window resets
+    pub fn reset_time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.reset)
+    }
+
+    /// Returns the time at which the current rate-limit window resets as a
+    /// [`time::OffsetDateTime`] in UTC
+    ///
+    /// This method is only available when the `time` feature is enabled.
+    #[cfg(feature = "time")]
+    pub fn reset_datetime(&self) -> time::OffsetDateTime {
+        time::OffsetDateTime::from(self.reset_time())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ureq::http::header::{HeaderValue, LINK};
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for &(name, value) in pairs {
+            headers.insert(
+                HeaderName::from_static(name),
+                HeaderValue::from_static(value),
+            );
+        }
+        headers
+    }
+
+    #[test]
+    fn rate_limit_from_headers() {
+        let h = headers(&[
+            ("x-ratelimit-limit", "5000"),
+            ("x-ratelimit-remaining", "4987"),
+            ("x-ratelimit-used", "13"),
+            ("x-ratelimit-reset", "1750000000"),
+            ("x-ratelimit-resource", "core"),
+        ]);
+        assert_eq!(
+            RateLimit::from_headers(&h),
+            Some(RateLimit {
+                limit: 5000,
+                remaining: 4987,
+                used: 13,
+                reset: 1750000000,
+                resource: Some("core".into()),
+            })
+        );
+    }
+
+    #[test]
+    fn rate_limit_from_headers_no_used() {
+        let h = headers(&[
+            ("x-ratelimit-limit", "30"),
+            ("x-ratelimit-remaining", "28"),
+            ("x-ratelimit-reset", "1750000000"),
+        ]);
+        assert_eq!(
+            RateLimit::from_headers(&h),
+            Some(RateLimit {
+                limit: 30,
+                remaining: 28,
+                used: 2,
+                reset: 1750000000,
+                resource: None,
+            })
+        );
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn rate_limit_reset_datetime() {
+        let rl = RateLimit {
+            limit: 5000,
+            remaining: 0,
+            used: 5000,
+            reset: 1750000000,
+            resource: None,
+        };
+        assert_eq!(
+            rl.reset_datetime(),
+            time::OffsetDateTime::from_unix_timestamp(1750000000).unwrap()
+        );
+    }
+
+    #[test]
+    fn rate_limit_from_headers_missing() {
+        let h = headers(&[("x-ratelimit-limit", "5000"), ("x-ratelimit-used", "13")]);
+        assert_eq!(RateLimit::from_headers(&h), None);
+    }
+
+    #[test]
+    fn meta_link() {
+        let mut h = HeaderMap::new();
+        h.insert(
+            LINK,
+            HeaderValue::from_static(concat!(
+                r#"<https://api.github.com/repositories/1300192/issues?page=2>; rel="next", "#,
+                r#"<https://api.github.com/repositories/1300192/issues?page=515>; rel="last""#,
+            )),
+        );
+        let meta = ResponseMeta {
+            url: Url::parse("https://api.github.com/repositories/1300192/issues").unwrap(),
+            status: StatusCode::OK,
+            headers: h,
+            rate_limit: None,
+            redirects: Vec::new(),
+        };
+        assert_eq!(
+            meta.link("last").unwrap().as_str(),
+            "https://api.github.com/repositories/1300192/issues?page=515"
+        );
+        assert_eq!(meta.link("prev"), None);
+    }
+
+    #[test]
+    fn response_ext() {
+        let r = Response::builder()
+            .header(
+                LINK,
+                concat!(
+                    r#"<https://api.github.com/repositories/1300192/issues?page=2>; rel="next", "#,
+                    r#"<https://api.github.com/repositories/1300192/issues?page=515>; rel="last""#,
+                ),
+            )
+            .header("x-github-request-id", "ABCD:1234")
+            .header("content-language", "ja")
+            .header("x-ratelimit-limit", "5000")
+            .header("x-ratelimit-remaining", "4987")
+            .header("x-ratelimit-reset", "1750000000")
+            .body(())
+            .unwrap();
+        assert_eq!(
+            r.next_link().unwrap().as_str(),
+            "https://api.github.com/repositories/1300192/issues?page=2"
+        );
+        let links = r.link_relations();
+        assert_eq!(links.len(), 2);
+        assert_eq!(
+            links["last"].as_str(),
+            "https://api.github.com/repositories/1300192/issues?page=515"
+        );
+        assert_eq!(r.request_id(), Some("ABCD:1234"));
+        assert_eq!(r.content_language(), Some("ja"));
+        assert_eq!(r.rate_limit().map(|rl| rl.remaining), Some(4987));
+        let meta = ResponseMeta::new(
+            Url::parse("https://api.github.com/repositories/1300192/issues").unwrap(),
+            &r,
+        );
+        assert_eq!(meta.content_language(), Some("ja"));
+
+        let r = Response::new(());
+        assert_eq!(r.next_link(), None);
+        assert!(r.link_relations().is_empty());
+        assert_eq!(r.request_id(), None);
+        assert_eq!(r.content_language(), None);
+        assert_eq!(r.rate_limit(), None);
+    }
+}
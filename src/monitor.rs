@@ -0,0 +1,131 @@
+use super::{Client, Method, RetryReason};
+use crate::util::lock;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use url::Url;
+
+/// The state of a request that failed and is waiting to be retried, as
+/// reported by [`RetryMonitor::waiting()`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RetryState {
+    /// The method of the request
+    pub method: Method,
+
+    /// The URL to which the request is being sent
+    pub url: Url,
+
+    /// The number of attempts made so far
+    pub attempt: u32,
+
+    /// The maximum number of attempts that will be made
+    pub max_attempts: u32,
+
+    /// The time at which the request will be retried
+    pub retry_at: SystemTime,
+
+    /// Why the request is being retried
+    pub reason: RetryReason,
+
+    /// The total time spent waiting before earlier retries of the request,
+    /// not counting the current wait
+    pub total_wait: Duration,
+}
+
+impl RetryState {
+    /// Returns how long remains until the request is retried, or zero if the
+    /// retry time has passed
+    pub fn retry_in(&self) -> Duration {
+        self.retry_at
+            .duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO)
+    }
+}
+
+/// A lightweight handle for inspecting which of a client's requests are
+/// currently waiting to be retried, e.g., so that a TUI can show "retrying
+/// in 37s (attempt 4/11)" without parsing logs.
+///
+/// `RetryMonitor` is returned from [`Client::retry_monitor()`].  It is cheap
+/// to clone and can be sent to another thread, such as a UI thread, while the
+/// client makes requests.
+#[derive(Clone, Debug, Default)]
+pub struct RetryMonitor(Arc<Mutex<RetryRegistry>>);
+
+/// The requests registered with a [`RetryMonitor`]
+#[derive(Debug, Default)]
+struct RetryRegistry {
+    /// The ID to assign to the next registered request
+    next_id: u64,
+
+    /// The requests that are waiting to be retried, keyed by ID
+    waiting: BTreeMap<u64, RetryState>,
+}
+
+impl RetryMonitor {
+    /// Returns the states of the client's requests that are currently
+    /// waiting to be retried, in the order in which they started waiting
+    pub fn waiting(&self) -> Vec<RetryState> {
+        lock(&self.0).waiting.values().cloned().collect()
+    }
+
+    /// Record that a request is waiting to be retried, returning a guard that
+    /// removes the record when dropped
+    pub(crate) fn register(&self, state: RetryState) -> WaitingRetry<'_> {
+        let mut registry = lock(&self.0);
+        let id = registry.next_id;
+        registry.next_id += 1;
+        registry.waiting.insert(id, state);
+        WaitingRetry { monitor: self, id }
+    }
+}
+
+/// A guard representing a request that is waiting to be retried
+#[derive(Debug)]
+pub(crate) struct WaitingRetry<'a> {
+    monitor: &'a RetryMonitor,
+    id: u64,
+}
+
+impl Drop for WaitingRetry<'_> {
+    fn drop(&mut self) {
+        lock(&self.monitor.0).waiting.remove(&self.id);
+    }
+}
+
+impl Client {
+    /// Returns a [`RetryMonitor`] for inspecting which requests made by the
+    /// client (or any of its clones & handles) are currently waiting to be
+    /// retried
+    pub fn retry_monitor(&self) -> RetryMonitor {
+        self.retry_monitor.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ureq::http::status::StatusCode;
+
+    #[test]
+    fn register_and_drop() {
+        let client = Client::builder().build().unwrap();
+        let monitor = client.retry_monitor();
+        let state = RetryState {
+            method: Method::Get,
+            url: Url::parse("https://api.github.com/user").unwrap(),
+            attempt: 4,
+            max_attempts: 11,
+            retry_at: SystemTime::now() + Duration::from_secs(37),
+            reason: RetryReason::ServerError {
+                status: StatusCode::BAD_GATEWAY,
+            },
+            total_wait: Duration::from_secs(3),
+        };
+        let guard = client.retry_monitor.register(state.clone());
+        assert_eq!(monitor.waiting(), [state]);
+        assert!(monitor.waiting()[0].retry_in() <= Duration::from_secs(37));
+        drop(guard);
+        assert!(monitor.waiting().is_empty());
+    }
+}
@@ -0,0 +1,149 @@
+use super::{Client, path_under};
+use thiserror::Error;
+use url::Url;
+
+/// A collection of [`Client`]s for different GitHub hosts (e.g., github.com
+/// and one or more GitHub Enterprise Server instances), each with its own
+/// token, settings, and rate-limit state, that routes each request to the
+/// client for the host it targets.
+///
+/// Clients are identified by the hosts of their base API URLs.  The client
+/// for `https://api.github.com` can also be referred to as `github.com`.
+#[derive(Clone, Debug, Default)]
+pub struct MultiClient {
+    clients: Vec<Client>,
+}
+
+impl MultiClient {
+    /// Create a new `MultiClient` with no clients
+    pub fn new() -> MultiClient {
+        MultiClient::default()
+    }
+
+    /// Add a client.  If a client with the same base API URL was already
+    /// added, it is replaced.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.clients.retain(|c| c.api_url != client.api_url);
+        self.clients.push(client);
+        self
+    }
+
+    /// Returns the client for the given host (e.g., `"github.com"` or
+    /// `"ghe.example.com"`), if any
+    pub fn client_for_host(&self, host: &str) -> Option<&Client> {
+        self.clients.iter().find(|c| serves_host(c, host))
+    }
+
+    /// Determine which client to use for `target`, which may be either:
+    ///
+    /// - an absolute URL, which is routed to the client whose base API URL it
+    ///   lies under, or else to the client for its host
+    ///
+    /// - a host followed by an API path (e.g.,
+    ///   `"ghe.example.com/repos/octocat/hello-world"`), which is routed to
+    ///   the client for the host
+    ///
+    /// Returns the client along with the URL or path to pass to its request
+    /// methods.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if no client is configured for the host that `target`
+    /// refers to
+    pub fn route<'a>(&self, target: &'a str) -> Result<(&Client, &'a str), RouteError> {
+        let err = || RouteError {
+            target: target.to_owned(),
+        };
+        if let Ok(url) = Url::parse(target) {
+            let client = self
+                .clients
+                .iter()
+                .filter(|c| path_under(&url, &c.api_url).is_some())
+                .max_by_key(|c| c.api_url.as_str().len())
+                .or_else(|| self.client_for_host(url.host_str()?))
+                .ok_or_else(err)?;
+            Ok((client, target))
+        } else {
+            let (host, _) = target.split_once('/').ok_or_else(err)?;
+            let client = self.client_for_host(host).ok_or_else(err)?;
+            Ok((client, &target[host.len()..]))
+        }
+    }
+}
+
+/// Returns `true` if `client` is the client for `host`
+fn serves_host(client: &Client, host: &str) -> bool {
+    match client.api_url.host_str() {
+        Some("api.github.com") => host == "api.github.com" || host == "github.com",
+        Some(h) => h == host,
+        None => false,
+    }
+}
+
+/// Error returned by [`MultiClient::route()`] when no client is configured
+/// for a target
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("no client configured for {target:?}")]
+pub struct RouteError {
+    /// The target that could not be routed
+    pub target: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn multi() -> MultiClient {
+        let ghes = Client::builder()
+            .with_api_url(Url::parse("https://ghe.example.com/api/v3").unwrap())
+            .with_token("ghes-token")
+            .build()
+            .unwrap();
+        MultiClient::new()
+            .with_client(Client::new("dotcom-token").unwrap())
+            .with_client(ghes)
+    }
+
+    #[test]
+    fn route_url() {
+        let multi = multi();
+        let (client, target) = multi
+            .route("https://ghe.example.com/api/v3/repos/octocat/hello-world")
+            .unwrap();
+        assert_eq!(client.api_url.as_str(), "https://ghe.example.com/api/v3");
+        assert_eq!(
+            target,
+            "https://ghe.example.com/api/v3/repos/octocat/hello-world"
+        );
+        let (client, _) = multi
+            .route("https://api.github.com/repos/octocat/hello-world")
+            .unwrap();
+        assert_eq!(client.api_url.as_str(), "https://api.github.com/");
+    }
+
+    #[test]
+    fn route_host_path() {
+        let multi = multi();
+        let (client, path) = multi
+            .route("ghe.example.com/repos/octocat/hello-world")
+            .unwrap();
+        assert_eq!(client.api_url.as_str(), "https://ghe.example.com/api/v3");
+        assert_eq!(path, "/repos/octocat/hello-world");
+        let (client, path) = multi.route("github.com/user").unwrap();
+        assert_eq!(client.api_url.as_str(), "https://api.github.com/");
+        assert_eq!(path, "/user");
+    }
+
+    #[test]
+    fn route_unknown() {
+        let multi = multi();
+        assert_eq!(
+            multi.route("gitlab.com/user").unwrap_err(),
+            RouteError {
+                target: "gitlab.com/user".into()
+            }
+        );
+        assert!(multi.route("https://example.com/user").is_err());
+        assert!(multi.route("/user").is_err());
+    }
+}
@@ -0,0 +1,76 @@
+use super::{Client, UsageReport};
+use crate::util::lock;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+impl Client {
+    /// Return a handle whose requests are tagged with the logical operation
+    /// name `name` (e.g., `"sync_issues"`).
+    ///
+    /// The operation name is included in the client's log messages about the
+    /// handle's requests, and the requests are tallied per operation in
+    /// [`usage_by_operation()`][Client::usage_by_operation], so that logs and
+    /// metrics can be grouped by what the program was doing rather than by
+    /// URL, which has too many distinct values to aggregate usefully.
+    ///
+    /// The handle shares all other state with the client.
+    pub fn operation(&self, name: &str) -> Client {
+        Client {
+            operation: Some(Arc::from(name)),
+            ..self.clone()
+        }
+    }
+
+    /// Returns the operation name set with [`operation()`][Client::operation],
+    /// if any
+    pub fn operation_name(&self) -> Option<&str> {
+        self.operation.as_deref()
+    }
+
+    /// Returns a summary of the requests made by handles created with
+    /// [`operation()`][Client::operation], keyed by operation name.  The
+    /// requests are also included in the totals returned by
+    /// [`usage_report()`][Client::usage_report].
+    pub fn usage_by_operation(&self) -> HashMap<String, UsageReport> {
+        let elapsed = self.created.elapsed();
+        lock(&self.operation_usage)
+            .iter()
+            .map(|(name, report)| {
+                let mut report = *report;
+                report.elapsed = elapsed;
+                (name.to_string(), report)
+            })
+            .collect()
+    }
+
+    /// Returns a suffix identifying the handle's operation, if any, for
+    /// appending to log messages
+    pub(crate) fn operation_tag(&self) -> String {
+        match self.operation.as_deref() {
+            Some(name) => format!(" [operation: {name}]"),
+            None => String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usage_grouped_by_operation() {
+        let client = Client::builder().build().unwrap();
+        let sync = client.operation("sync_issues");
+        assert_eq!(sync.operation_name(), Some("sync_issues"));
+        assert_eq!(client.operation_name(), None);
+        sync.update_usage(|u| u.requests += 1);
+        sync.update_usage(|u| u.retries += 1);
+        client.update_usage(|u| u.requests += 1);
+        let by_op = client.usage_by_operation();
+        assert_eq!(by_op.len(), 1);
+        assert_eq!(by_op["sync_issues"].requests, 1);
+        assert_eq!(by_op["sync_issues"].retries, 1);
+        assert_eq!(client.usage_report().requests, 2);
+        assert_eq!(sync.operation_tag(), " [operation: sync_issues]");
+    }
+}
@@ -0,0 +1,211 @@
+use super::util::RATELIMIT_REMAINING_HEADER;
+use super::{RateLimit, TokenType};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime};
+use ureq::http::header::HeaderMap;
+
+/// A set of settings controlling how a [`Client`][crate::Client] spaces out
+/// its requests in order to avoid triggering rate limits, as set with
+/// [`ClientBuilder::with_pacing_profile()`][crate::ClientBuilder::with_pacing_profile]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PacingProfile {
+    /// GitHub's general recommendations: mutating requests are spaced at
+    /// least one second apart, and other requests are not delayed
+    #[default]
+    Standard,
+
+    /// A stricter profile for the `GITHUB_TOKEN` provided to GitHub Actions
+    /// workflows, which has a much lower rate limit than a personal access
+    /// token: mutating requests are spaced at least two seconds apart, all
+    /// requests are spaced at least a quarter second apart, and once less
+    /// than a quarter of the primary rate limit remains, requests are spread
+    /// out evenly over the rest of the rate-limit window
+    Actions,
+}
+
+impl PacingProfile {
+    /// Determine the appropriate profile for the current environment and the
+    /// given token: [`PacingProfile::Actions`] if running under GitHub
+    /// Actions (i.e., if the `GITHUB_ACTIONS` environment variable is
+    /// `"true"`) with an installation token such as the built-in
+    /// `GITHUB_TOKEN`, [`PacingProfile::Standard`] otherwise
+    pub fn detect(token: Option<&str>) -> PacingProfile {
+        if std::env::var("GITHUB_ACTIONS").is_ok_and(|v| v == "true")
+            && token.map(TokenType::from_token) == Some(TokenType::Installation)
+        {
+            PacingProfile::Actions
+        } else {
+            PacingProfile::Standard
+        }
+    }
+
+    /// The minimum amount of time between consecutive mutating requests
+    pub(crate) fn mutation_delay(self) -> Duration {
+        match self {
+            PacingProfile::Standard => Duration::from_secs(1),
+            PacingProfile::Actions => Duration::from_secs(2),
+        }
+    }
+
+    /// The minimum amount of time between consecutive requests of any kind
+    pub(crate) fn request_interval(self) -> Duration {
+        match self {
+            PacingProfile::Standard => Duration::ZERO,
+            PacingProfile::Actions => Duration::from_millis(250),
+        }
+    }
+
+    /// The percentage of the primary rate limit below which requests are
+    /// throttled, if any
+    fn throttle_percent(self) -> Option<u64> {
+        match self {
+            PacingProfile::Standard => None,
+            PacingProfile::Actions => Some(25),
+        }
+    }
+
+    /// Given the most recent rate-limit information, return how long to wait
+    /// before the next request so that the remaining quota is spread evenly
+    /// over the rest of the rate-limit window
+    pub(crate) fn throttle_delay(self, rate_limit: &RateLimit, now: SystemTime) -> Duration {
+        let Some(pct) = self.throttle_percent() else {
+            return Duration::ZERO;
+        };
+        if rate_limit.remaining == 0
+            || rate_limit.remaining.saturating_mul(100) >= rate_limit.limit.saturating_mul(pct)
+        {
+            return Duration::ZERO;
+        }
+        let until_reset = rate_limit
+            .reset_time()
+            .duration_since(now)
+            .unwrap_or_default();
+        until_reset / u32::try_from(rate_limit.remaining).unwrap_or(u32::MAX)
+    }
+}
+
+/// Whether a [`Client`][crate::Client] should assume that the server
+/// enforces rate limits, as set with
+/// [`ClientBuilder::with_rate_limit_mode()`][crate::ClientBuilder::with_rate_limit_mode]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitMode {
+    /// Treat a 403 response as a possible rate-limit error only if it has
+    /// `X-RateLimit-*` headers or a `Retry-After` header.  This suits both
+    /// github.com and GitHub Enterprise Server instances with rate limiting
+    /// disabled, which send no `X-RateLimit-*` headers at all.
+    #[default]
+    Auto,
+
+    /// Always assume the server enforces rate limits, treating any 403
+    /// response whose body mentions "rate limit" as a rate-limit error even
+    /// if it lacks `X-RateLimit-*` headers
+    Enabled,
+
+    /// Assume the server does not enforce rate limits: 403 responses are only
+    /// retried if they have a `Retry-After` header, and requests are not
+    /// paced based on rate-limit information.  Mutating requests are still
+    /// spaced out as per the [`PacingProfile`].
+    Disabled,
+}
+
+impl RateLimitMode {
+    /// Given the headers of a 403 response, returns `true` if the response
+    /// should be checked for being a rate-limit error
+    pub(crate) fn expects_rate_limit(self, headers: &HeaderMap) -> bool {
+        match self {
+            RateLimitMode::Auto => headers.contains_key(RATELIMIT_REMAINING_HEADER),
+            RateLimitMode::Enabled => true,
+            RateLimitMode::Disabled => false,
+        }
+    }
+}
+
+/// The window over which per-minute rate limits are measured
+pub(crate) const PER_MINUTE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Given the path of an API URL relative to the base API URL, return the
+/// rate-limit resource with a per-minute limit that requests to the path
+/// count against, if any, along with the default limit for the resource
+/// (which depends on whether the client is `authenticated`)
+pub(crate) fn per_minute_resource(path: &str, authenticated: bool) -> Option<(&'static str, u32)> {
+    if path == "/search/code" || path.starts_with("/search/code/") {
+        Some(("code_search", 10))
+    } else if path.starts_with("/search/") {
+        Some(("search", if authenticated { 30 } else { 10 }))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    fn rate_limit(limit: u64, remaining: u64) -> RateLimit {
+        RateLimit {
+            limit,
+            remaining,
+            used: limit - remaining,
+            reset: 1_750_000_000,
+            resource: None,
+        }
+    }
+
+    #[test]
+    fn throttle_delay_actions() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_750_000_000 - 600);
+        let profile = PacingProfile::Actions;
+        assert_eq!(
+            profile.throttle_delay(&rate_limit(1000, 500), now),
+            Duration::ZERO
+        );
+        assert_eq!(
+            profile.throttle_delay(&rate_limit(1000, 200), now),
+            Duration::from_secs(3)
+        );
+        assert_eq!(
+            profile.throttle_delay(&rate_limit(1000, 0), now),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn test_per_minute_resource() {
+        assert_eq!(
+            per_minute_resource("/search/code", true),
+            Some(("code_search", 10))
+        );
+        assert_eq!(
+            per_minute_resource("/search/issues", true),
+            Some(("search", 30))
+        );
+        assert_eq!(
+            per_minute_resource("/search/repositories", false),
+            Some(("search", 10))
+        );
+        assert_eq!(per_minute_resource("/repos/octocat/search", true), None);
+    }
+
+    #[test]
+    fn rate_limit_mode() {
+        let mut headers = HeaderMap::new();
+        assert!(!RateLimitMode::Auto.expects_rate_limit(&headers));
+        assert!(RateLimitMode::Enabled.expects_rate_limit(&headers));
+        assert!(!RateLimitMode::Disabled.expects_rate_limit(&headers));
+        headers.insert(RATELIMIT_REMAINING_HEADER, "0".parse().unwrap());
+        assert!(RateLimitMode::Auto.expects_rate_limit(&headers));
+        assert!(!RateLimitMode::Disabled.expects_rate_limit(&headers));
+    }
+
+    #[test]
+    fn throttle_delay_standard() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_750_000_000 - 600);
+        assert_eq!(
+            PacingProfile::Standard.throttle_delay(&rate_limit(5000, 1), now),
+            Duration::ZERO
+        );
+    }
+}
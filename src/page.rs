@@ -1,31 +1,568 @@
-use super::util::get_next_link;
-use super::{Client, Method, RequestError};
-use serde::{Deserialize, de::DeserializeOwned};
+use super::drift::{self, DriftReport};
+use super::util::random_duration;
+use super::{Client, Method, ProgressEvent, RateLimit, RequestError, ResponseExt, ResponseMeta};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value};
 use std::collections::HashMap;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
 use thiserror::Error;
+use ureq::http::header::{ETAG, HeaderMap, HeaderName, HeaderValue};
 use url::Url;
 
+/// The number of items per page that GitHub returns when no `per_page` query
+/// parameter is given
+const DEFAULT_PER_PAGE: u64 = 30;
+
 /// An iterator that performs paginated GET requests and yields the returned
 /// items.
 ///
 /// `PaginationIter` is returned from the [`Client::paginate()`] method.
+///
+/// `PaginationIter` also implements [`DoubleEndedIterator`], so the items can
+/// be traversed in reverse with [`rev()`][Iterator::rev] or
+/// [`next_back()`][DoubleEndedIterator::next_back].  When the server provides
+/// `rel="last"` and `rel="prev"` links, iterating from the back starts at the
+/// last page and works backwards without fetching the pages in between;
+/// otherwise, all remaining pages are fetched in order first.  Items are never
+/// yielded twice when iterating from both ends.
 #[derive(Clone, Debug)]
 pub struct PaginationIter<'a, T> {
     client: &'a Client,
     next_url: NextUrl,
-    items: Option<std::vec::IntoIter<T>>,
+
+    /// The remaining items from the current page.  When `skip_errors` is
+    /// true, items that could not be decoded are stored as `Err` containing
+    /// the raw JSON value.
+    items: Option<std::vec::IntoIter<Result<T, Value>>>,
+    last_meta: Option<ResponseMeta>,
+    total_count: Option<u64>,
+    incomplete_results: Option<bool>,
+
+    /// The URL of the page whose items are in `items`
+    page_url: Option<Url>,
+
+    /// The rate-limit information reported in the response for the page
+    /// whose items are in `items`
+    page_rate_limit: Option<RateLimit>,
+
+    /// The `ETag` of the page whose items are in `items`
+    page_etag: Option<String>,
+
+    /// The total number of items in the page whose items are in `items`
+    page_len: u64,
+
+    /// The number of items in the first page fetched from the front that was
+    /// followed by another page, i.e., the size of a full page
+    full_page_len: Option<u64>,
+
+    /// The number of items from the current page that have been yielded
+    page_pos: usize,
+
+    /// The total number of items yielded
+    items_yielded: u64,
+
+    /// The URL of the most recent page request, if it failed
+    failed_url: Option<Url>,
+
+    /// When resuming from a checkpoint, the number of items to skip in the
+    /// first page fetched and the `ETag` that page had when the checkpoint was
+    /// made
+    resume: Option<(usize, Option<String>)>,
+
+    /// Additional headers to send with each page request
+    headers: HeaderMap,
+
+    /// Whether to continue iteration after a page or item fails
+    skip_errors: bool,
+
+    /// The remaining items from the page most recently fetched when iterating
+    /// from the back
+    back_items: Option<std::vec::IntoIter<Result<T, Value>>>,
+
+    /// The URL of the page whose items are in `back_items`
+    back_page_url: Option<Url>,
+
+    /// The rate-limit information reported in the response for the page
+    /// whose items are in `back_items`
+    back_page_rate_limit: Option<RateLimit>,
+
+    /// The next page to fetch when iterating from the back
+    back_url: BackUrl,
+
+    /// The minimum time to leave between the starts of consecutive page
+    /// requests
+    page_delay: Option<PageDelay>,
+
+    /// The time at which the most recent page request was started
+    last_page_request: Option<Instant>,
 }
 
 impl<'a, T> PaginationIter<'a, T> {
     pub(super) fn new(client: &'a Client, path: &str) -> Self {
+        PaginationIter::with_next_url(client, NextUrl::Path(path.to_owned()))
+    }
+
+    pub(super) fn from_url(client: &'a Client, url: Url) -> Self {
+        PaginationIter::with_next_url(client, NextUrl::Url(url))
+    }
+
+    fn with_next_url(client: &'a Client, next_url: NextUrl) -> Self {
         PaginationIter {
             client,
-            next_url: NextUrl::Path(path.to_owned()),
+            next_url,
             items: None,
+            last_meta: None,
+            total_count: None,
+            incomplete_results: None,
+            page_url: None,
+            page_rate_limit: None,
+            page_etag: None,
+            page_len: 0,
+            full_page_len: None,
+            page_pos: 0,
+            items_yielded: 0,
+            failed_url: None,
+            resume: None,
+            headers: HeaderMap::new(),
+            skip_errors: false,
+            back_items: None,
+            back_page_url: None,
+            back_page_rate_limit: None,
+            back_url: BackUrl::Start,
+            page_delay: None,
+            last_page_request: None,
+        }
+    }
+
+    /// Create an iterator that yields the given items without making any
+    /// requests
+    #[cfg(test)]
+    pub(crate) fn from_items(client: &'a Client, items: Vec<T>) -> Self {
+        let mut iter = PaginationIter::with_next_url(client, NextUrl::None);
+        iter.items = Some(items.into_iter().map(Ok).collect::<Vec<_>>().into_iter());
+        iter
+    }
+
+    /// Send the given header with each page request
+    pub(super) fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    pub(super) fn resume(client: &'a Client, checkpoint: PaginationCheckpoint) -> Self {
+        let next_url = match checkpoint.page_url {
+            Some(url) => NextUrl::Url(url),
+            None => NextUrl::None,
+        };
+        let mut iter = PaginationIter::with_next_url(client, next_url);
+        iter.items_yielded = checkpoint.items_yielded;
+        iter.resume = Some((checkpoint.skip, checkpoint.etag));
+        iter
+    }
+
+    /// Returns a [`PaginationCheckpoint`] recording the iterator's current
+    /// position.  The checkpoint can be serialized, saved, and later passed to
+    /// [`Client::resume_paginate()`] in order to continue iteration from the
+    /// same point, e.g., after a long-running program is restarted.
+    ///
+    /// If the most recent page request failed, the checkpoint will resume
+    /// from the failed page.
+    ///
+    /// Checkpoints only record the position of iteration from the front; any
+    /// items yielded by iterating from the back will be yielded again when
+    /// resuming.
+    pub fn checkpoint(&self) -> PaginationCheckpoint {
+        let (page_url, skip, etag) = match self.items.as_ref() {
+            Some(items) if items.len() > 0 => {
+                (self.page_url.clone(), self.page_pos, self.page_etag.clone())
+            }
+            _ => {
+                let url = match &self.next_url {
+                    NextUrl::Path(s) => self.client.mkurl(s).ok(),
+                    NextUrl::Url(url) => Some(url.clone()),
+                    NextUrl::None => self.failed_url.clone(),
+                };
+                // If resumed from a checkpoint but no page has been fetched
+                // yet, the items to skip are still pending
+                match &self.resume {
+                    Some((skip, etag)) => (url, *skip, etag.clone()),
+                    None => (url, 0, None),
+                }
+            }
+        };
+        PaginationCheckpoint {
+            page_url,
+            skip,
+            items_yielded: self.items_yielded,
+            etag,
+        }
+    }
+
+    /// Configure whether iteration should continue after an error.
+    ///
+    /// By default, once a page request fails or a page cannot be decoded, the
+    /// iterator yields the error and then stops.  When `skip` is `true`,
+    /// errors are instead yielded and then skipped over:
+    ///
+    /// - Each item in a page is decoded separately, and an item that cannot
+    ///   be decoded is yielded as an error without affecting the rest of the
+    ///   page.
+    ///
+    /// - If a page cannot be decoded at all, the error is yielded, and
+    ///   iteration continues with the page's `rel="next"` link.
+    ///
+    /// - If a page request fails, the error is yielded, and iteration
+    ///   continues with the following page, provided that the page is
+    ///   selected by a `page` query parameter and the previous page's
+    ///   `rel="last"` link shows that there are more pages to come.
+    ///   Otherwise, iteration ends.
+    pub fn with_skip_errors(mut self, skip: bool) -> Self {
+        self.skip_errors = skip;
+        self
+    }
+
+    /// Wait between page requests in order to spread a long crawl out over
+    /// time, as GitHub recommends for background jobs.
+    ///
+    /// Before each page request after the first, the iterator sleeps until
+    /// the given delay has passed since the previous page request was
+    /// started, so time spent waiting on the server counts towards the delay.
+    /// The delay is in addition to any pacing configured on the client.
+    ///
+    /// If the client is in no-sleep mode, a page request that would need to
+    /// wait instead yields [`RequestError::WouldSleep`], and calling
+    /// [`next()`][Iterator::next] again later will request the same page.
+    pub fn with_page_delay(mut self, delay: PageDelay) -> Self {
+        self.page_delay = Some(delay);
+        self
+    }
+
+    /// If the most recent page request failed, arrange for the next call to
+    /// [`next()`][Iterator::next] to request the failed page again, and return
+    /// `true`.  If there is no failed page to retry, return `false`.
+    ///
+    /// This allows callers to back off at their own pace after an error and
+    /// then continue iteration from where it left off.
+    pub fn retry_last(&mut self) -> bool {
+        if self.next_url == NextUrl::None
+            && let Some(url) = self.failed_url.take()
+        {
+            self.next_url = NextUrl::Url(url);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the value of the `total_count` field in the most recently
+    /// fetched page, if any page has been fetched yet and the page was a map
+    /// containing such a field (as is the case for search results)
+    pub fn total_count(&self) -> Option<u64> {
+        self.total_count
+    }
+
+    /// Returns the value of the `incomplete_results` field in the most
+    /// recently fetched page, if any page has been fetched yet and the page
+    /// was a map containing such a field (as is the case for search results)
+    pub fn incomplete_results(&self) -> Option<bool> {
+        self.incomplete_results
+    }
+
+    /// Returns an estimate of the total number of pages and items that the
+    /// iterator will fetch from the front, or `None` if no page has been
+    /// fetched yet, so that callers can report progress.
+    ///
+    /// The number of pages is taken from the `page` query parameter of the
+    /// most recent response's `rel="last"` link or, if there are no further
+    /// pages, of the most recently fetched page.  The number of items is then
+    /// estimated as the number of pages times the page size (taken from the
+    /// `per_page` query parameter or else from the size of the first full
+    /// page), using the actual size of the final page once it has been
+    /// fetched.  If the pages report a `total_count` field, that is used as
+    /// the number of items instead.
+    ///
+    /// The estimate is exact for endpoints with page-number pagination whose
+    /// results do not change during iteration.  Endpoints that use
+    /// cursor-based pagination do not provide a `rel="last"` link, and for
+    /// them this method only returns a value once the last page is reached.
+    pub fn page_count_hint(&self) -> Option<PageCountHint> {
+        let page_url = self.page_url.as_ref()?;
+        let per_page = page_url
+            .query_pairs()
+            .find(|(k, _)| k == "per_page")
+            .and_then(|(_, v)| v.parse::<u64>().ok())
+            .or(self.full_page_len)
+            .unwrap_or(DEFAULT_PER_PAGE);
+        let (pages, items) = if self.next_url == NextUrl::None && self.failed_url.is_none() {
+            let pages = page_number(page_url).unwrap_or(1);
+            (pages, pages.saturating_sub(1) * per_page + self.page_len)
+        } else {
+            let last = self.last_meta.as_ref()?.link("last")?;
+            let pages = page_number(&last)?;
+            (pages, pages.saturating_mul(per_page))
+        };
+        Some(PageCountHint {
+            pages,
+            items: self.total_count.unwrap_or(items),
+        })
+    }
+
+    /// Convert the iterator into one that yields each item together with a
+    /// [`PageMeta`] describing the page it came from, so that long crawls can
+    /// adapt their own pacing and record the provenance of each item
+    pub fn with_page_meta(self) -> WithPageMeta<'a, T> {
+        WithPageMeta { inner: self }
+    }
+
+    /// Returns metadata (status, headers, and rate-limit information) about
+    /// the response for the most recently fetched page, if any page has been
+    /// fetched yet.
+    ///
+    /// This can be used to, e.g., inspect the `rel="last"` link or the
+    /// remaining rate-limit quota in the middle of iteration.
+    pub fn last_response_meta(&self) -> Option<&ResponseMeta> {
+        self.last_meta.as_ref()
+    }
+
+    /// If a page delay is set and a page has already been requested, sleep
+    /// until the delay has passed since that request was started, and then
+    /// record the start of a new page request for `url`
+    fn wait_for_page(&mut self, url: &Url) -> Result<(), RequestError> {
+        if let Some(pd) = self.page_delay
+            && let Some(last) = self.last_page_request
+        {
+            let delay = pd.sample().saturating_sub(last.elapsed());
+            if !delay.is_zero() {
+                log::debug!("Sleeping for {delay:?} between page requests");
+                self.client.sleep_before(
+                    Method::Get,
+                    url,
+                    delay,
+                    None,
+                    ProgressEvent::PageDelay {
+                        url: url.clone(),
+                        delay,
+                    },
+                )?;
+            }
+        }
+        self.last_page_request = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Returns the URL of the page following the page at `url`, as
+    /// determined from the `page` query parameter and the most recently
+    /// fetched page's `rel="last"` link, or `None` if there is no such page or
+    /// it cannot be determined
+    fn page_after(&self, url: &Url) -> Option<Url> {
+        let last = self.last_meta.as_ref()?.link("last")?;
+        let page = page_number(url)?;
+        (page < page_number(&last)?).then(|| with_page_number(url, page + 1))
+    }
+}
+
+impl<T: DeserializeOwned> PaginationIter<'_, T> {
+    /// Validate and decode a page response body.  If `skip_errors` is true,
+    /// each item is decoded separately, and items that fail to decode are
+    /// returned as `Err` containing the raw value.
+    fn decode_page(&self, url: &Url, body: &[u8]) -> Result<Page<Result<T, Value>>, RequestError> {
+        #[cfg(feature = "validation")]
+        self.client.validate_response(Method::Get, url, body)?;
+        self.parse_page(url, body)
+            .map_err(|source| RequestError::Deserialize {
+                method: Method::Get,
+                url: url.clone(),
+                source: Box::new(ureq::Error::Json(source)),
+            })
+    }
+
+    /// Decode a page response body without validating it
+    fn parse_page(
+        &self,
+        url: &Url,
+        body: &[u8],
+    ) -> Result<Page<Result<T, Value>>, serde_json::Error> {
+        let mut report = DriftReport::default();
+        let page = if self.skip_errors {
+            serde_json::from_slice::<Page<Value>>(body)?.map(|v| {
+                if self.client.drift_detection {
+                    drift::from_value::<T>(v.clone(), &mut report).map_err(|_| v)
+                } else {
+                    T::deserialize(&v).map_err(|_| v)
+                }
+            })
+        } else if self.client.drift_detection {
+            serde_json::from_slice::<Page<Value>>(body)?
+                .try_map(|v| drift::from_value::<T>(v, &mut report))?
+                .map(Ok)
+        } else {
+            serde_json::from_slice::<Page<T>>(body)?.map(Ok)
+        };
+        report.log::<T>(Method::Get, url);
+        Ok(page)
+    }
+
+    /// Fetch the next page from the front and store its items in `items`.
+    /// Returns `None` if there are no more pages.
+    fn fetch_next_page(&mut self) -> Option<Result<(), RequestError>> {
+        let url = match std::mem::replace(&mut self.next_url, NextUrl::None) {
+            NextUrl::Path(s) => match self.client.mkurl(&s) {
+                Ok(url) => url,
+                Err(e) => return Some(Err(e)),
+            },
+            NextUrl::Url(url) => url,
+            NextUrl::None => return None,
+        };
+        if let Err(e) = self.wait_for_page(&url) {
+            self.next_url = NextUrl::Url(url);
+            return Some(Err(e));
         }
+        let resp = match self.client.get_page(url.clone(), &self.headers) {
+            Ok(r) => r,
+            Err(e) => {
+                if self.skip_errors {
+                    self.next_url = match self.page_after(&url) {
+                        Some(next) => NextUrl::Url(next),
+                        None => NextUrl::None,
+                    };
+                }
+                self.failed_url = Some(url);
+                return Some(Err(e));
+            }
+        };
+        self.failed_url = None;
+        self.last_meta = Some(ResponseMeta::new(url.clone(), &resp));
+        let next_url = match resp.next_link() {
+            Some(url) => NextUrl::Url(url),
+            None => NextUrl::None,
+        };
+        match self.decode_page(&url, resp.body()) {
+            Ok(page) => {
+                let etag = resp
+                    .headers()
+                    .get(ETAG)
+                    .and_then(|v| v.to_str().ok())
+                    .map(ToOwned::to_owned);
+                let page_len = u64::try_from(page.items.len()).unwrap_or(u64::MAX);
+                let mut items = page.items.into_iter();
+                self.page_pos = 0;
+                if let Some((skip, old_etag)) = self.resume.take() {
+                    if old_etag.is_some() && old_etag != etag {
+                        log::debug!(
+                            "Page at {url} has changed since checkpoint was made; items may be skipped or repeated"
+                        );
+                    }
+                    if skip > 0 {
+                        items.nth(skip - 1);
+                        self.page_pos = skip;
+                    }
+                }
+                self.total_count = page.total_count;
+                self.incomplete_results = page.incomplete_results;
+                self.items = Some(items);
+                self.page_url = Some(url.clone());
+                self.page_rate_limit = resp.rate_limit();
+                self.page_etag = etag;
+                self.page_len = page_len;
+                if self.full_page_len.is_none() && next_url != NextUrl::None {
+                    self.full_page_len = Some(page_len);
+                }
+            }
+            Err(e) => {
+                self.failed_url = Some(url);
+                if self.skip_errors {
+                    self.next_url = next_url;
+                }
+                return Some(Err(e));
+            }
+        }
+        self.next_url = next_url;
+        Some(Ok(()))
+    }
+
+    /// Fetch all remaining pages from the front and append their items to
+    /// `items`
+    fn drain_forward(&mut self) -> Result<(), RequestError> {
+        let mut rest = self.items.take().into_iter().flatten().collect::<Vec<_>>();
+        while let Some(r) = self.fetch_next_page() {
+            rest.extend(self.items.take().into_iter().flatten());
+            if let Err(e) = r {
+                self.items = Some(rest.into_iter());
+                return Err(e);
+            }
+        }
+        self.items = Some(rest.into_iter());
+        Ok(())
+    }
+
+    /// Decode an item that previously failed to decode in order to obtain the
+    /// error
+    fn item_error(&self, value: Value) -> Result<T, RequestError> {
+        serde_json::from_value::<T>(value).map_err(|source| RequestError::Deserialize {
+            method: Method::Get,
+            url: self
+                .page_url
+                .clone()
+                .unwrap_or_else(|| self.client.api_url.clone()),
+            source: Box::new(ureq::Error::Json(source)),
+        })
     }
 }
 
+impl<T: DeserializeOwned + Serialize> PaginationIter<'_, T> {
+    /// Consume the iterator, writing each item to `writer` as a single line
+    /// of JSON (i.e., in [JSON Lines](https://jsonlines.org) format) as pages
+    /// are fetched, and return the number of items written.
+    ///
+    /// The writer is not flushed; wrap it in a [`std::io::BufWriter`] for
+    /// efficiency if it is not already buffered.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if fetching a page fails or if an item cannot be
+    /// written.  Items yielded before the error will already have been
+    /// written.
+    pub fn write_ndjson<W: Write>(self, mut writer: W) -> Result<u64, WriteNdjsonError> {
+        let mut written = 0;
+        for item in self {
+            let item = item?;
+            serde_json::to_writer(&mut writer, &item).map_err(|source| {
+                WriteNdjsonError::Write {
+                    index: written,
+                    source: source.into(),
+                }
+            })?;
+            writer
+                .write_all(b"\n")
+                .map_err(|source| WriteNdjsonError::Write {
+                    index: written,
+                    source,
+                })?;
+            written += 1;
+        }
+        Ok(written)
+    }
+}
+
+/// Error returned by [`PaginationIter::write_ndjson()`]
+#[derive(Debug, Error)]
+pub enum WriteNdjsonError {
+    /// Fetching or decoding a page failed
+    #[error(transparent)]
+    Request(#[from] RequestError),
+
+    /// Serializing or writing an item failed
+    #[error("failed to write item {index} as JSON")]
+    Write {
+        /// The zero-based index of the item among those yielded
+        index: u64,
+
+        /// The inner error
+        source: io::Error,
+    },
+}
+
 impl<T> Iterator for PaginationIter<'_, T>
 where
     T: DeserializeOwned,
@@ -35,42 +572,244 @@ where
     fn next(&mut self) -> Option<Self::Item> {
         loop {
             if let Some(item) = self.items.as_mut().and_then(Iterator::next) {
-                return Some(Ok(item));
+                self.page_pos += 1;
+                self.items_yielded += 1;
+                return Some(item.or_else(|v| self.item_error(v)));
             } else {
                 self.items = None;
             }
-            let url = match std::mem::replace(&mut self.next_url, NextUrl::None) {
-                NextUrl::Path(s) => match self.client.mkurl(&s) {
-                    Ok(url) => url,
-                    Err(e) => return Some(Err(e)),
-                },
-                NextUrl::Url(url) => url,
-                NextUrl::None => return None,
+            if let NextUrl::Url(next) = &self.next_url
+                && self
+                    .back_page_url
+                    .as_ref()
+                    .is_some_and(|back| same_page(next, back))
+            {
+                // The next page was already fetched when iterating from the
+                // back, so continue with its remaining items.
+                self.next_url = NextUrl::None;
+                self.items = self.back_items.take();
+                self.page_url = self.back_page_url.take();
+                self.page_rate_limit = self.back_page_rate_limit.take();
+                self.page_pos = 0;
+                self.back_url = BackUrl::Met;
+                continue;
+            }
+            if let Err(e) = self.fetch_next_page()? {
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+impl<T> DoubleEndedIterator for PaginationIter<'_, T>
+where
+    T: DeserializeOwned,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self
+                .back_items
+                .as_mut()
+                .and_then(DoubleEndedIterator::next_back)
+            {
+                self.items_yielded += 1;
+                return Some(item.or_else(|v| self.item_error(v)));
+            } else {
+                self.back_items = None;
+            }
+            let url = match &self.back_url {
+                BackUrl::Start => {
+                    if self.last_meta.is_none()
+                        && let Some(Err(e)) = self.fetch_next_page()
+                    {
+                        return Some(Err(e));
+                    }
+                    let last = self.last_meta.as_ref().and_then(|m| m.link("last"));
+                    match (&self.next_url, last) {
+                        (NextUrl::None, _) => {
+                            self.back_url = BackUrl::Met;
+                            continue;
+                        }
+                        (_, Some(last)) => last,
+                        (_, None) => {
+                            if let Err(e) = self.drain_forward() {
+                                return Some(Err(e));
+                            }
+                            self.back_url = BackUrl::Met;
+                            continue;
+                        }
+                    }
+                }
+                BackUrl::Url(url) => url.clone(),
+                BackUrl::Met => {
+                    let item = self.items.as_mut()?.next_back()?;
+                    self.items_yielded += 1;
+                    return Some(item.or_else(|v| self.item_error(v)));
+                }
+                BackUrl::Finished => return None,
             };
-            let mut resp = match self.client.request::<()>(Method::Get, url.clone(), None) {
+            if self.page_url.as_ref().is_some_and(|p| same_page(p, &url)) {
+                self.back_url = BackUrl::Met;
+                continue;
+            }
+            if let Err(e) = self.wait_for_page(&url) {
+                return Some(Err(e));
+            }
+            let resp = match self.client.get_page(url.clone(), &self.headers) {
                 Ok(r) => r,
-                Err(e) => return Some(Err(e)),
+                Err(e) => {
+                    self.back_url = BackUrl::Finished;
+                    return Some(Err(e));
+                }
             };
-            match resp.body_mut().read_json::<Page<T>>() {
-                Ok(page) => self.items = Some(page.items.into_iter()),
-                Err(source) => {
-                    return Some(Err(RequestError::Deserialize {
-                        method: Method::Get,
-                        url,
-                        source: Box::new(source),
-                    }));
+            self.last_meta = Some(ResponseMeta::new(url.clone(), &resp));
+            match self.decode_page(&url, resp.body()) {
+                Ok(page) => {
+                    self.back_items = Some(page.items.into_iter());
+                    self.back_url = match resp.link("prev") {
+                        Some(prev) => BackUrl::Url(prev),
+                        None => BackUrl::Met,
+                    };
+                    self.back_page_url = Some(url);
+                    self.back_page_rate_limit = resp.rate_limit();
+                }
+                Err(e) => {
+                    self.back_url = BackUrl::Finished;
+                    return Some(Err(e));
                 }
             }
-            self.next_url = match get_next_link(&resp) {
-                Some(url) => NextUrl::Url(url),
-                None => NextUrl::None,
-            };
         }
     }
 }
 
 impl<T> std::iter::FusedIterator for PaginationIter<'_, T> where T: DeserializeOwned {}
 
+/// Metadata about the page from which an item was yielded by
+/// [`WithPageMeta`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PageMeta {
+    /// The URL of the page
+    pub url: Url,
+
+    /// The rate-limit information reported in the response for the page, as
+    /// of when the page was fetched
+    pub rate_limit: Option<RateLimit>,
+}
+
+/// A delay to leave between page requests made by a [`PaginationIter`], as
+/// set with [`PaginationIter::with_page_delay()`]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PageDelay {
+    /// Always wait the same amount of time
+    Fixed(Duration),
+
+    /// Wait a random amount of time between `min` and `max` (inclusive),
+    /// chosen anew for each page
+    Jittered {
+        /// The minimum delay
+        min: Duration,
+
+        /// The maximum delay
+        max: Duration,
+    },
+}
+
+impl PageDelay {
+    /// Returns the delay to use before the next page request
+    fn sample(self) -> Duration {
+        match self {
+            PageDelay::Fixed(d) => d,
+            PageDelay::Jittered { min, max } => random_duration(min, max),
+        }
+    }
+}
+
+/// An estimate of the size of a paginated result set, as returned by
+/// [`PaginationIter::page_count_hint()`]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct PageCountHint {
+    /// The estimated total number of pages
+    pub pages: u64,
+
+    /// The estimated total number of items
+    pub items: u64,
+}
+
+/// An iterator that yields the items of a [`PaginationIter`] together with
+/// metadata about the pages they came from.
+///
+/// `WithPageMeta` is returned from the [`PaginationIter::with_page_meta()`]
+/// method.
+#[derive(Clone, Debug)]
+pub struct WithPageMeta<'a, T> {
+    inner: PaginationIter<'a, T>,
+}
+
+impl<'a, T> WithPageMeta<'a, T> {
+    /// Return a reference to the inner [`PaginationIter`]
+    pub fn get_ref(&self) -> &PaginationIter<'a, T> {
+        &self.inner
+    }
+
+    /// Return the inner [`PaginationIter`]
+    pub fn into_inner(self) -> PaginationIter<'a, T> {
+        self.inner
+    }
+}
+
+impl<T> Iterator for WithPageMeta<'_, T>
+where
+    T: DeserializeOwned,
+{
+    type Item = Result<(T, PageMeta), RequestError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next()?;
+        Some(item.map(|value| {
+            let meta = PageMeta {
+                url: self
+                    .inner
+                    .page_url
+                    .clone()
+                    .unwrap_or_else(|| self.inner.client.api_url.clone()),
+                rate_limit: self.inner.page_rate_limit.clone(),
+            };
+            (value, meta)
+        }))
+    }
+}
+
+impl<T> std::iter::FusedIterator for WithPageMeta<'_, T> where T: DeserializeOwned {}
+
+/// A record of a [`PaginationIter`]'s position, as returned by
+/// [`PaginationIter::checkpoint()`], that can be used to resume iteration
+/// later via [`Client::resume_paginate()`]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PaginationCheckpoint {
+    /// The URL of the page from which to resume iteration, or `None` if
+    /// iteration had finished
+    pub page_url: Option<Url>,
+
+    /// The number of items at the start of the page at `page_url` that were
+    /// already yielded and should be skipped on resumption
+    pub skip: usize,
+
+    /// The total number of items yielded before the checkpoint was made
+    pub items_yielded: u64,
+
+    /// The `ETag` of the page at `page_url` when it was fetched, if any.  On
+    /// resumption, if the page's `ETag` has changed, a message is logged
+    /// noting that the page's contents have shifted.
+    pub etag: Option<String>,
+}
+
+impl PaginationCheckpoint {
+    /// Returns `true` if the checkpoint was made after iteration had finished
+    pub fn is_finished(&self) -> bool {
+        self.page_url.is_none()
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 enum NextUrl {
     Path(String),
@@ -78,12 +817,42 @@ enum NextUrl {
     None,
 }
 
+/// The state of iteration from the back of a [`PaginationIter`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum BackUrl {
+    /// No page has been fetched from the back yet
+    Start,
+
+    /// The URL of the next page to fetch
+    Url(Url),
+
+    /// Iteration from the back has reached the page currently being iterated
+    /// over from the front, and so further items are taken from `items`
+    Met,
+
+    /// A page request from the back failed, and iteration from the back has
+    /// stopped
+    Finished,
+}
+
+/// A single page of a paginated response, as used by [`PaginationIter`]
+///
+/// A page response body is either a JSON array of items or a JSON object
+/// containing exactly one array of items (such as the `items` field of search
+/// results or the `workflow_runs` field of the workflow runs endpoint)
+/// alongside other fields.  `Page` deserializes from either form, so a single
+/// page can be fetched with, e.g., `client.get::<Page<Issue>>(path)`.
+///
+/// The `total_count` and `incomplete_results` fields of an object response
+/// are exposed via dedicated accessors, and any other non-item fields via
+/// [`Page::extra()`].
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
 #[serde(bound = "T: DeserializeOwned", try_from = "RawPage<T>")]
-struct Page<T> {
+pub struct Page<T> {
     items: Vec<T>,
     total_count: Option<u64>,
     incomplete_results: Option<bool>,
+    extra: Map<String, Value>,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq)]
@@ -93,6 +862,89 @@ enum RawPage<T> {
     Map(HashMap<String, MapPageValue<T>>),
 }
 
+impl<T> Page<T> {
+    /// Returns the items in the page
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Returns the items in the page as a `Vec`
+    pub fn into_items(self) -> Vec<T> {
+        self.items
+    }
+
+    /// Returns the number of items in the page
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns `true` if the page contains no items
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns the value of the response's `total_count` field, if any.  For
+    /// search results, this is the total number of matches, which may exceed
+    /// the number of results that can actually be retrieved.
+    pub fn total_count(&self) -> Option<u64> {
+        self.total_count
+    }
+
+    /// Returns the value of the response's `incomplete_results` field, if
+    /// any.  For search results, this is `true` if the search timed out
+    /// before finding all matches.
+    pub fn incomplete_results(&self) -> Option<bool> {
+        self.incomplete_results
+    }
+
+    /// Returns the fields of an object response other than the array of
+    /// items, `total_count`, and `incomplete_results`.  This is empty for
+    /// array responses.
+    pub fn extra(&self) -> &Map<String, Value> {
+        &self.extra
+    }
+
+    /// Apply a function to each item in the page
+    pub fn map<U, F>(self, f: F) -> Page<U>
+    where
+        F: FnMut(T) -> U,
+    {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            total_count: self.total_count,
+            incomplete_results: self.incomplete_results,
+            extra: self.extra,
+        }
+    }
+
+    /// Apply a fallible function to each item in the page, returning the
+    /// first error encountered, if any
+    ///
+    /// # Errors
+    ///
+    /// Returns the first `Err` returned by `f`
+    pub fn try_map<U, E, F>(self, f: F) -> Result<Page<U>, E>
+    where
+        F: FnMut(T) -> Result<U, E>,
+    {
+        Ok(Page {
+            items: self.items.into_iter().map(f).collect::<Result<_, _>>()?,
+            total_count: self.total_count,
+            incomplete_results: self.incomplete_results,
+            extra: self.extra,
+        })
+    }
+}
+
+impl<T> IntoIterator for Page<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
 impl<T: DeserializeOwned> TryFrom<RawPage<T>> for Page<T> {
     type Error = ParsePageError;
 
@@ -102,16 +954,29 @@ impl<T: DeserializeOwned> TryFrom<RawPage<T>> for Page<T> {
                 items,
                 total_count: None,
                 incomplete_results: None,
+                extra: Map::new(),
             }),
             RawPage::Map(map) => {
                 let total_count = map.get("total_count").and_then(MapPageValue::as_u64);
                 let incomplete_results = map
                     .get("incomplete_results")
                     .and_then(MapPageValue::as_bool);
-                let mut lists = map
-                    .into_values()
-                    .filter_map(MapPageValue::into_list)
-                    .collect::<Vec<_>>();
+                let mut lists = Vec::new();
+                let mut extra = Map::new();
+                for (key, value) in map {
+                    let value = match value {
+                        MapPageValue::List(lst) => {
+                            lists.push(lst);
+                            continue;
+                        }
+                        MapPageValue::Count(_) if key == "total_count" => continue,
+                        MapPageValue::Bool(_) if key == "incomplete_results" => continue,
+                        MapPageValue::Count(n) => Value::from(n),
+                        MapPageValue::Bool(b) => Value::Bool(b),
+                        MapPageValue::Other(v) => v,
+                    };
+                    extra.insert(key, value);
+                }
                 if lists.len() == 1 {
                     let Some(items) = lists.pop() else {
                         unreachable!("Vec with 1 item should have something to pop");
@@ -120,6 +985,7 @@ impl<T: DeserializeOwned> TryFrom<RawPage<T>> for Page<T> {
                         items,
                         total_count,
                         incomplete_results,
+                        extra,
                     })
                 } else {
                     Err(ParsePageError::ListQty(lists.len()))
@@ -135,7 +1001,7 @@ enum MapPageValue<T> {
     Count(u64),
     Bool(bool),
     List(Vec<T>),
-    Other(serde::de::IgnoredAny),
+    Other(Value),
 }
 
 impl<T> MapPageValue<T> {
@@ -154,14 +1020,35 @@ impl<T> MapPageValue<T> {
             None
         }
     }
+}
 
-    fn into_list(self) -> Option<Vec<T>> {
-        if let MapPageValue::List(lst) = self {
-            Some(lst)
-        } else {
-            None
-        }
-    }
+/// Returns the value of the `page` query parameter of `url`, if any
+fn page_number(url: &Url) -> Option<u64> {
+    let (_, value) = url.query_pairs().find(|(k, _)| k == "page")?;
+    value.parse().ok()
+}
+
+/// Returns a copy of `url` with the `page` query parameter set to `page`
+fn with_page_number(url: &Url, page: u64) -> Url {
+    let pairs = url
+        .query_pairs()
+        .filter(|(k, _)| k != "page")
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect::<Vec<_>>();
+    let mut url = url.clone();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(pairs)
+        .append_pair("page", &page.to_string());
+    url
+}
+
+/// Returns `true` if `a` and `b` are the URLs of the same page, treating a URL
+/// without a `page` query parameter as referring to page 1
+fn same_page(a: &Url, b: &Url) -> bool {
+    a == b
+        || (page_number(a).unwrap_or(1) == page_number(b).unwrap_or(1)
+            && with_page_number(a, 1) == with_page_number(b, 1))
 }
 
 #[derive(Debug, Error)]
@@ -173,6 +1060,333 @@ enum ParsePageError {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::thread;
+
+    mod checkpoint {
+        use super::*;
+
+        #[test]
+        fn fresh_iter() {
+            let client = Client::new("hunter2").unwrap();
+            let iter = client.paginate::<Value>("/users/octocat/repos");
+            assert_eq!(
+                iter.checkpoint(),
+                PaginationCheckpoint {
+                    page_url: Some(
+                        Url::parse("https://api.github.com/users/octocat/repos").unwrap()
+                    ),
+                    skip: 0,
+                    items_yielded: 0,
+                    etag: None,
+                }
+            );
+        }
+
+        #[test]
+        fn resumed_iter() {
+            let client = Client::new("hunter2").unwrap();
+            let checkpoint = PaginationCheckpoint {
+                page_url: Some(
+                    Url::parse("https://api.github.com/users/octocat/repos?page=3").unwrap(),
+                ),
+                skip: 7,
+                items_yielded: 67,
+                etag: Some(r#"W/"abc123""#.into()),
+            };
+            let iter = client.resume_paginate::<Value>(checkpoint);
+            assert_eq!(
+                iter.checkpoint(),
+                PaginationCheckpoint {
+                    page_url: Some(
+                        Url::parse("https://api.github.com/users/octocat/repos?page=3").unwrap()
+                    ),
+                    skip: 7,
+                    items_yielded: 67,
+                    etag: Some(r#"W/"abc123""#.into()),
+                }
+            );
+        }
+
+        #[test]
+        fn serde_roundtrip() {
+            let checkpoint = PaginationCheckpoint {
+                page_url: Some(
+                    Url::parse("https://api.github.com/users/octocat/repos?page=3").unwrap(),
+                ),
+                skip: 7,
+                items_yielded: 67,
+                etag: None,
+            };
+            let s = serde_json::to_string(&checkpoint).unwrap();
+            assert_eq!(
+                s,
+                r#"{"page_url":"https://api.github.com/users/octocat/repos?page=3","skip":7,"items_yielded":67,"etag":null}"#
+            );
+            assert_eq!(
+                serde_json::from_str::<PaginationCheckpoint>(&s).unwrap(),
+                checkpoint
+            );
+        }
+    }
+
+    #[test]
+    fn retry_last() {
+        let client = Client::new("hunter2").unwrap();
+        let mut iter = client.paginate::<Value>("/users/octocat/repos");
+        assert!(!iter.retry_last());
+        let url = Url::parse("https://api.github.com/users/octocat/repos?page=2").unwrap();
+        iter.next_url = NextUrl::None;
+        iter.failed_url = Some(url.clone());
+        assert!(iter.retry_last());
+        assert_eq!(iter.next_url, NextUrl::Url(url));
+        assert_eq!(iter.failed_url, None);
+        assert!(!iter.retry_last());
+    }
+
+    #[test]
+    fn page_delay() {
+        let fixed = PageDelay::Fixed(Duration::from_millis(500));
+        assert_eq!(fixed.sample(), Duration::from_millis(500));
+        let min = Duration::from_millis(250);
+        let max = Duration::from_secs(2);
+        let jittered = PageDelay::Jittered { min, max };
+        for _ in 0..100 {
+            let d = jittered.sample();
+            assert!(min <= d && d <= max, "{d:?} out of range");
+        }
+        let backwards = PageDelay::Jittered { min: max, max: min };
+        assert_eq!(backwards.sample(), max);
+    }
+
+    #[test]
+    fn page_delay_no_sleep() {
+        let client = Client::builder().with_no_sleep(true).build().unwrap();
+        let mut iter = client
+            .paginate::<Value>("/users/octocat/repos")
+            .with_page_delay(PageDelay::Fixed(Duration::from_secs(60)));
+        let url = Url::parse("https://api.github.com/users/octocat/repos?page=2").unwrap();
+        iter.last_page_request = Some(Instant::now());
+        iter.next_url = NextUrl::Url(url.clone());
+        assert!(matches!(
+            iter.next(),
+            Some(Err(RequestError::WouldSleep { .. }))
+        ));
+        assert_eq!(iter.next_url, NextUrl::Url(url));
+    }
+
+    #[test]
+    fn page_delay_interrupted_by_shutdown() {
+        let client = Client::builder().build().unwrap();
+        let mut iter = client
+            .paginate::<Value>("/users/octocat/repos")
+            .with_page_delay(PageDelay::Fixed(Duration::from_secs(60)));
+        let url = Url::parse("https://api.github.com/users/octocat/repos?page=2").unwrap();
+        iter.last_page_request = Some(Instant::now());
+        iter.next_url = NextUrl::Url(url.clone());
+        thread::scope(|s| {
+            s.spawn(|| {
+                thread::sleep(Duration::from_millis(50));
+                assert!(client.shutdown(Duration::ZERO));
+            });
+            let start = Instant::now();
+            assert!(matches!(
+                iter.next(),
+                Some(Err(RequestError::ShutDown { .. }))
+            ));
+            assert!(start.elapsed() < Duration::from_secs(10));
+        });
+        assert_eq!(iter.next_url, NextUrl::Url(url));
+    }
+
+    #[test]
+    fn next_page_number() {
+        let url =
+            Url::parse("https://api.github.com/orgs/python/repos?per_page=100&page=3").unwrap();
+        assert_eq!(page_number(&url), Some(3));
+        assert_eq!(
+            with_page_number(&url, 4).as_str(),
+            "https://api.github.com/orgs/python/repos?per_page=100&page=4"
+        );
+        let url = Url::parse("https://api.github.com/orgs/python/repos?per_page=100").unwrap();
+        assert_eq!(page_number(&url), None);
+    }
+
+    #[test]
+    fn same_page_urls() {
+        let first = Url::parse("https://api.github.com/orgs/python/repos?per_page=100").unwrap();
+        let page1 =
+            Url::parse("https://api.github.com/orgs/python/repos?page=1&per_page=100").unwrap();
+        let page2 =
+            Url::parse("https://api.github.com/orgs/python/repos?per_page=100&page=2").unwrap();
+        assert!(same_page(&first, &page1));
+        assert!(same_page(&page2, &page2));
+        assert!(!same_page(&first, &page2));
+    }
+
+    #[test]
+    fn iterate_from_both_ends() {
+        let client = Client::new("hunter2").unwrap();
+        let page1 = Url::parse("https://api.github.com/users/octocat/repos").unwrap();
+        let page2 = Url::parse("https://api.github.com/users/octocat/repos?page=2").unwrap();
+        let mut iter = client.paginate::<u64>("/users/octocat/repos");
+        iter.items = Some(vec![Ok(1), Ok(2)].into_iter());
+        iter.page_url = Some(page1);
+        iter.next_url = NextUrl::Url(page2.clone());
+        iter.back_items = Some(vec![Ok(3), Ok(4)].into_iter());
+        iter.back_page_url = Some(page2);
+        iter.back_url =
+            BackUrl::Url(Url::parse("https://api.github.com/users/octocat/repos?page=1").unwrap());
+
+        let mut rev = iter.clone();
+        assert_eq!(
+            rev.by_ref().rev().collect::<Result<Vec<_>, _>>().unwrap(),
+            [4, 3, 2, 1]
+        );
+        assert!(rev.next().is_none());
+
+        assert_eq!(iter.next_back().unwrap().unwrap(), 4);
+        assert_eq!(iter.next().unwrap().unwrap(), 1);
+        assert_eq!(iter.next().unwrap().unwrap(), 2);
+        assert_eq!(iter.next().unwrap().unwrap(), 3);
+        assert!(iter.next().is_none());
+        assert!(iter.next_back().is_none());
+    }
+
+    #[test]
+    fn page_count_hint() {
+        let client = Client::new("hunter2").unwrap();
+        let page1 = Url::parse("https://api.github.com/users/octocat/repos?per_page=50").unwrap();
+        let page2 =
+            Url::parse("https://api.github.com/users/octocat/repos?per_page=50&page=2").unwrap();
+        let page7 =
+            Url::parse("https://api.github.com/users/octocat/repos?per_page=50&page=7").unwrap();
+        let mut iter = client.paginate::<u64>("/users/octocat/repos?per_page=50");
+        assert_eq!(iter.page_count_hint(), None);
+        let resp = ureq::http::Response::builder()
+            .header(
+                "link",
+                format!(r#"<{page2}>; rel="next", <{page7}>; rel="last""#),
+            )
+            .body(())
+            .unwrap();
+        iter.last_meta = Some(ResponseMeta::new(page1.clone(), &resp));
+        iter.page_url = Some(page1);
+        iter.next_url = NextUrl::Url(page2);
+        assert_eq!(
+            iter.page_count_hint(),
+            Some(PageCountHint {
+                pages: 7,
+                items: 350
+            })
+        );
+        iter.page_url = Some(page7);
+        iter.next_url = NextUrl::None;
+        iter.page_len = 12;
+        assert_eq!(
+            iter.page_count_hint(),
+            Some(PageCountHint {
+                pages: 7,
+                items: 312
+            })
+        );
+        iter.total_count = Some(311);
+        assert_eq!(iter.page_count_hint().map(|h| h.items), Some(311));
+    }
+
+    #[test]
+    fn with_page_meta() {
+        let client = Client::new("hunter2").unwrap();
+        let page1 = Url::parse("https://api.github.com/users/octocat/repos").unwrap();
+        let page2 = Url::parse("https://api.github.com/users/octocat/repos?page=2").unwrap();
+        let rate_limit = RateLimit {
+            limit: 5000,
+            remaining: 4321,
+            used: 679,
+            reset: 1750000000,
+            resource: Some("core".into()),
+        };
+        let mut iter = client.paginate::<u64>("/users/octocat/repos");
+        iter.items = Some(vec![Ok(1)].into_iter());
+        iter.page_url = Some(page1.clone());
+        iter.page_rate_limit = Some(rate_limit.clone());
+        iter.next_url = NextUrl::Url(page2.clone());
+        iter.back_items = Some(vec![Ok(2)].into_iter());
+        iter.back_page_url = Some(page2.clone());
+        iter.back_url = BackUrl::Met;
+        let items = iter
+            .with_page_meta()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            items,
+            [
+                (
+                    1,
+                    PageMeta {
+                        url: page1,
+                        rate_limit: Some(rate_limit)
+                    }
+                ),
+                (
+                    2,
+                    PageMeta {
+                        url: page2,
+                        rate_limit: None
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn write_ndjson() {
+        let client = Client::new("hunter2").unwrap();
+        let mut iter = client.paginate::<Value>("/users/octocat/repos");
+        iter.items = Some(
+            vec![
+                Ok(serde_json::json!({"name": "a", "stars": 1})),
+                Ok(serde_json::json!({"name": "b\nc", "stars": 2})),
+            ]
+            .into_iter(),
+        );
+        iter.next_url = NextUrl::None;
+        let mut out = Vec::new();
+        assert_eq!(iter.write_ndjson(&mut out).unwrap(), 2);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "{\"name\":\"a\",\"stars\":1}\n{\"name\":\"b\\nc\",\"stars\":2}\n"
+        );
+    }
+
+    #[test]
+    fn skip_bad_items() {
+        #[derive(Debug, Deserialize, Eq, PartialEq)]
+        struct Widget {
+            name: String,
+            power: u64,
+        }
+
+        let client = Client::new("hunter2").unwrap();
+        let url = Url::parse("https://api.github.com/widgets").unwrap();
+        let body = br#"[{"name": "Steve", "power": 9001}, {"name": "Gidget", "power": "high"}]"#;
+        let iter = client.paginate::<Widget>("/widgets");
+        assert!(iter.decode_page(&url, body).is_err());
+        let iter = iter.with_skip_errors(true);
+        assert_eq!(
+            iter.decode_page(&url, body).unwrap().items,
+            vec![
+                Ok(Widget {
+                    name: "Steve".into(),
+                    power: 9001
+                }),
+                Err(serde_json::json!({"name": "Gidget", "power": "high"})),
+            ]
+        );
+        assert!(matches!(
+            iter.item_error(serde_json::json!({"name": "Gidget", "power": "high"})),
+            Err(RequestError::Deserialize { .. })
+        ));
+    }
 
     mod deser_page {
         use super::*;
@@ -219,6 +1433,7 @@ mod tests {
                     ],
                     total_count: None,
                     incomplete_results: None,
+                    extra: Map::new(),
                 }
             );
         }
@@ -260,6 +1475,7 @@ mod tests {
                     ],
                     total_count: Some(17),
                     incomplete_results: None,
+                    extra: Map::new(),
                 }
             );
         }
@@ -300,6 +1516,7 @@ mod tests {
                     ],
                     total_count: None,
                     incomplete_results: None,
+                    extra: Map::new(),
                 }
             );
         }
@@ -342,6 +1559,10 @@ mod tests {
                     ],
                     total_count: Some(17),
                     incomplete_results: None,
+                    extra: serde_json::json!({"mode": "ponens"})
+                        .as_object()
+                        .unwrap()
+                        .clone(),
                 }
             );
         }
@@ -384,6 +1605,10 @@ mod tests {
                     ],
                     total_count: Some(17),
                     incomplete_results: None,
+                    extra: serde_json::json!({"modes": ["ponens", "tollens"]})
+                        .as_object()
+                        .unwrap()
+                        .clone(),
                 }
             );
         }
@@ -465,8 +1690,41 @@ mod tests {
                     ],
                     total_count: Some(100),
                     incomplete_results: Some(true),
+                    extra: Map::new(),
                 }
             );
         }
+
+        #[test]
+        fn accessors() {
+            let src = indoc! {r#"
+            {
+                "total_count": 2,
+                "incomplete_results": false,
+                "repository_selection": "all",
+                "widgets": [
+                    {
+                        "name": "Steve",
+                        "color": "aquamarine",
+                        "power": 9001
+                    },
+                    {
+                        "name": "Widget O'Malley",
+                        "color": "taupe",
+                        "power": 42
+                    }
+                ]
+            }
+            "#};
+            let page = serde_json::from_str::<Page<Widget>>(src).unwrap();
+            assert_eq!(page.len(), 2);
+            assert_eq!(page.items()[1].name, "Widget O'Malley");
+            assert_eq!(page.total_count(), Some(2));
+            assert_eq!(page.incomplete_results(), Some(false));
+            assert_eq!(page.extra().len(), 1);
+            assert_eq!(page.extra()["repository_selection"], "all");
+            let powers = page.map(|w| w.power).into_iter().collect::<Vec<_>>();
+            assert_eq!(powers, [9001, 42]);
+        }
     }
 }
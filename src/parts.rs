@@ -0,0 +1,419 @@
+use super::{Client, Method, MethodConvertError, RequestError};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use ureq::{
+    Body,
+    http::{
+        Request, Response, StatusCode, Uri,
+        header::{HeaderMap, HeaderName, HeaderValue},
+    },
+};
+use url::Url;
+
+/// A full description of a request to be made with [`Client::execute()`],
+/// consisting of a method, URL, extra headers, optional body, and
+/// per-request overrides of the client's policies.
+///
+/// `RequestParts` can be converted to & from an [`ureq::http::Request`] with
+/// an `Option<RequestBody>` body (with the [`RequestPolicy`] stored in the
+/// request's extensions), allowing middleware and test utilities built on
+/// the `http` crate's types to be used with `minigh`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RequestParts {
+    /// The HTTP method of the request
+    pub method: Method,
+
+    /// The URL to which to send the request
+    pub url: Url,
+
+    /// Headers to add to the request in addition to those that the client
+    /// sets automatically
+    pub headers: HeaderMap,
+
+    /// The request body, if any
+    pub body: Option<RequestBody>,
+
+    /// Overrides of the client's policies for this request
+    pub policy: RequestPolicy,
+}
+
+impl RequestParts {
+    /// Create a new `RequestParts` for a request with the given method & URL,
+    /// no extra headers, no body, and the client's default policies
+    pub fn new(method: Method, url: Url) -> RequestParts {
+        RequestParts {
+            method,
+            url,
+            headers: HeaderMap::new(),
+            body: None,
+            policy: RequestPolicy::default(),
+        }
+    }
+
+    /// Add a header to the request, replacing any previous values for the
+    /// same header
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Set the request body to `payload` serialized as JSON
+    pub fn with_json(mut self, payload: Value) -> Self {
+        self.body = Some(RequestBody::Json(payload));
+        self
+    }
+
+    /// Set the request body to `data`, sent as-is with the given
+    /// `Content-Type`
+    pub fn with_body<D: Into<Vec<u8>>, S: Into<String>>(
+        mut self,
+        data: D,
+        content_type: S,
+    ) -> Self {
+        self.body = Some(RequestBody::Raw {
+            data: data.into(),
+            content_type: content_type.into(),
+        });
+        self
+    }
+
+    /// Set the overrides of the client's policies for this request
+    pub fn with_policy(mut self, policy: RequestPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+}
+
+/// The body of a request described by a [`RequestParts`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RequestBody {
+    /// A JSON payload, sent with a `Content-Type` of `application/json`
+    Json(Value),
+
+    /// A body of any other kind, sent as-is
+    Raw {
+        /// The body
+        data: Vec<u8>,
+
+        /// The `Content-Type` of the body
+        content_type: String,
+    },
+}
+
+impl From<Value> for RequestBody {
+    fn from(value: Value) -> RequestBody {
+        RequestBody::Json(value)
+    }
+}
+
+/// Overrides of a client's policies for a single request made with
+/// [`Client::execute()`].  Fields that are `None` (or empty) leave the
+/// client's own settings in effect.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RequestPolicy {
+    /// Fail with [`RequestError::DeadlineExceeded`] if the request (including
+    /// any retries and waits) does not complete within this amount of time.
+    /// If the client also has a total deadline, whichever is sooner applies.
+    pub timeout: Option<Duration>,
+
+    /// Whether to fail instead of sleeping before sending or retrying the
+    /// request, as set client-wide by
+    /// [`ClientBuilder::with_no_sleep()`][crate::ClientBuilder::with_no_sleep]
+    pub no_sleep: Option<bool>,
+
+    /// The maximum amount of time to sleep in response to a rate-limit error,
+    /// as set client-wide by
+    /// [`ClientBuilder::with_max_rate_limit_wait()`][crate::ClientBuilder::with_max_rate_limit_wait]
+    pub max_rate_limit_wait: Option<Duration>,
+
+    /// Status codes to return as `Ok` responses instead of as errors, as for
+    /// [`Client::expect_statuses()`].  If nonempty, these replace any
+    /// statuses set on the client handle.
+    pub expected_statuses: Vec<u16>,
+}
+
+impl RequestPolicy {
+    /// Create a new `RequestPolicy` that overrides nothing
+    pub fn new() -> RequestPolicy {
+        RequestPolicy::default()
+    }
+
+    /// Set the timeout for the request
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set whether to fail instead of sleeping
+    pub fn with_no_sleep(mut self, enabled: bool) -> Self {
+        self.no_sleep = Some(enabled);
+        self
+    }
+
+    /// Set the maximum amount of time to sleep in response to a rate-limit
+    /// error
+    pub fn with_max_rate_limit_wait(mut self, max_wait: Duration) -> Self {
+        self.max_rate_limit_wait = Some(max_wait);
+        self
+    }
+
+    /// Set the status codes to return as `Ok` responses
+    pub fn with_expected_statuses<I: IntoIterator<Item = u16>>(mut self, statuses: I) -> Self {
+        self.expected_statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// Return `client`, or a handle to it with the policy's overrides
+    /// applied
+    fn apply<'a>(&self, client: &'a Client) -> Cow<'a, Client> {
+        if *self == RequestPolicy::default() {
+            return Cow::Borrowed(client);
+        }
+        let mut client = client.clone();
+        if let Some(timeout) = self.timeout {
+            let deadline = Instant::now() + timeout;
+            client.deadline = Some(client.deadline.map_or(deadline, |d| d.min(deadline)));
+        }
+        if let Some(no_sleep) = self.no_sleep {
+            client.no_sleep = no_sleep;
+        }
+        if let Some(max_wait) = self.max_rate_limit_wait {
+            client.max_rate_limit_wait = Some(max_wait);
+        }
+        if !self.expected_statuses.is_empty() {
+            client.expected_statuses = self
+                .expected_statuses
+                .iter()
+                .filter_map(|&s| StatusCode::from_u16(s).ok())
+                .collect();
+        }
+        Cow::Owned(client)
+    }
+}
+
+impl From<RequestParts> for Request<Option<RequestBody>> {
+    fn from(parts: RequestParts) -> Request<Option<RequestBody>> {
+        let Ok(uri) = parts.url.as_str().parse::<Uri>() else {
+            unreachable!("a valid URL should be a valid URI");
+        };
+        let mut req = Request::new(parts.body);
+        *req.method_mut() = parts.method.into();
+        *req.uri_mut() = uri;
+        *req.headers_mut() = parts.headers;
+        req.extensions_mut().insert(parts.policy);
+        req
+    }
+}
+
+impl TryFrom<Request<Option<RequestBody>>> for RequestParts {
+    type Error = RequestConvertError;
+
+    /// Convert an [`ureq::http::Request`] to a `RequestParts`.  The
+    /// [`RequestPolicy`] is taken from the request's extensions, if present.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the request's method is not supported by `minigh` or
+    /// if its URI is not an absolute URL.
+    fn try_from(req: Request<Option<RequestBody>>) -> Result<RequestParts, RequestConvertError> {
+        let (mut parts, body) = req.into_parts();
+        let method = Method::try_from(parts.method)?;
+        let url =
+            Url::parse(&parts.uri.to_string()).map_err(|source| RequestConvertError::Url {
+                uri: parts.uri,
+                source,
+            })?;
+        Ok(RequestParts {
+            method,
+            url,
+            headers: parts.headers,
+            body,
+            policy: parts
+                .extensions
+                .remove::<RequestPolicy>()
+                .unwrap_or_default(),
+        })
+    }
+}
+
+impl TryFrom<Request<Option<Value>>> for RequestParts {
+    type Error = RequestConvertError;
+
+    /// Convert an [`ureq::http::Request`] with an optional JSON payload to a
+    /// `RequestParts`
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the request's method is not supported by `minigh` or
+    /// if its URI is not an absolute URL.
+    fn try_from(req: Request<Option<Value>>) -> Result<RequestParts, RequestConvertError> {
+        RequestParts::try_from(req.map(|payload| payload.map(RequestBody::Json)))
+    }
+}
+
+/// Error returned when converting an [`ureq::http::Request`] to a
+/// [`RequestParts`] fails
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum RequestConvertError {
+    /// The request's method is not supported by `minigh`
+    #[error(transparent)]
+    Method(#[from] MethodConvertError),
+
+    /// The request's URI is not an absolute URL
+    #[error("request URI {uri} is not an absolute URL")]
+    Url {
+        /// The request's URI
+        uri: Uri,
+
+        /// The inner [`url::ParseError`]
+        source: url::ParseError,
+    },
+}
+
+impl Client {
+    /// Perform the request described by `parts` and return the response with
+    /// its body unread.  This is the most general way to make a request:
+    /// the request goes through the same pacing, retry, and rate-limit
+    /// handling as all other requests, but the body may be of any type, and
+    /// the client's policies may be overridden for the single request via
+    /// `parts.policy`.
+    ///
+    /// The headers in `parts.headers` are added to the request after the
+    /// client's own headers, overriding them if they have the same names.
+    ///
+    /// See [`request()`][Client::request] for information on lower-level
+    /// behavior.
+    pub fn execute(&self, parts: &RequestParts) -> Result<Response<Body>, RequestError> {
+        self.execute_with(parts, true, |resp| Ok(self.track_body(resp)))
+    }
+
+    /// Perform the request described by `parts` as for
+    /// [`send_with_retries()`][Client::send_with_retries], passing the
+    /// successful response to `finish`
+    pub(crate) fn execute_with<R, F>(
+        &self,
+        parts: &RequestParts,
+        allow_expected: bool,
+        finish: F,
+    ) -> Result<R, RequestError>
+    where
+        F: FnMut(Response<Body>) -> Result<R, ureq::Error>,
+    {
+        let client = parts.policy.apply(self);
+        let method = parts.method;
+        let url = parts.url.clone();
+        let headers = &parts.headers;
+        match &parts.body {
+            None => {
+                client.send_body_with_retries(method, url, None, headers, allow_expected, finish)
+            }
+            Some(RequestBody::Json(payload)) => client.send_with_retries(
+                method,
+                url,
+                Some(payload),
+                headers,
+                allow_expected,
+                finish,
+            ),
+            Some(RequestBody::Raw { data, content_type }) => client.send_body_with_retries(
+                method,
+                url,
+                Some((data, content_type)),
+                headers,
+                allow_expected,
+                finish,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn roundtrip() {
+        let mut parts = RequestParts::new(
+            Method::Patch,
+            Url::parse("https://api.github.com/repos/octocat/hello-world/issues/1").unwrap(),
+        );
+        parts.headers.insert(
+            HeaderName::from_static("x-request-id"),
+            HeaderValue::from_static("abc123"),
+        );
+        parts.body = Some(RequestBody::Json(json!({"state": "closed"})));
+        parts.policy = RequestPolicy::new().with_timeout(Duration::from_secs(30));
+        let req = Request::from(parts.clone());
+        assert_eq!(req.method(), ureq::http::Method::PATCH);
+        assert_eq!(
+            req.uri(),
+            "https://api.github.com/repos/octocat/hello-world/issues/1"
+        );
+        assert_eq!(req.headers()["x-request-id"], "abc123");
+        assert_eq!(RequestParts::try_from(req).unwrap(), parts);
+    }
+
+    #[test]
+    fn from_json_request() {
+        let mut req = Request::new(Some(json!({"body": "Hi"})));
+        *req.method_mut() = ureq::http::Method::POST;
+        *req.uri_mut() =
+            Uri::from_static("https://api.github.com/repos/octocat/hello-world/issues/1/comments");
+        let parts = RequestParts::try_from(req).unwrap();
+        assert_eq!(parts.body, Some(RequestBody::Json(json!({"body": "Hi"}))));
+        assert_eq!(parts.policy, RequestPolicy::default());
+    }
+
+    #[test]
+    fn policy_overrides() {
+        let client = Client::new("hunter2").unwrap();
+        assert!(matches!(
+            RequestPolicy::new().apply(&client),
+            Cow::Borrowed(_)
+        ));
+        let policy = RequestPolicy::new()
+            .with_timeout(Duration::from_secs(5))
+            .with_no_sleep(true)
+            .with_expected_statuses([404]);
+        let handle = policy.apply(&client);
+        assert!(handle.no_sleep);
+        assert!(handle.deadline.is_some());
+        assert_eq!(&*handle.expected_statuses, [StatusCode::NOT_FOUND]);
+        assert!(!client.no_sleep);
+        assert!(client.deadline.is_none());
+    }
+
+    #[test]
+    fn timeout_exceeded() {
+        let client = Client::new("hunter2").unwrap();
+        let parts = RequestParts::new(Method::Get, Url::parse("https://127.0.0.1:9/").unwrap())
+            .with_policy(RequestPolicy::new().with_timeout(Duration::ZERO));
+        assert!(matches!(
+            client.execute(&parts),
+            Err(RequestError::DeadlineExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn unsupported_method() {
+        let mut req = Request::new(None::<RequestBody>);
+        *req.method_mut() = ureq::http::Method::OPTIONS;
+        assert!(matches!(
+            RequestParts::try_from(req),
+            Err(RequestConvertError::Method(_))
+        ));
+    }
+
+    #[test]
+    fn relative_uri() {
+        let mut req = Request::new(None::<RequestBody>);
+        *req.uri_mut() = Uri::from_static("/user");
+        assert!(matches!(
+            RequestParts::try_from(req),
+            Err(RequestConvertError::Url { .. })
+        ));
+    }
+}
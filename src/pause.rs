@@ -0,0 +1,126 @@
+use super::Client;
+use crate::util::lock;
+use serde::{Deserialize, Serialize};
+use std::sync::{Condvar, Mutex, PoisonError};
+use std::time::Instant;
+
+/// What a [`Client`] does with requests issued while it is paused, as set
+/// with [`ClientBuilder::with_pause_policy()`][crate::ClientBuilder::with_pause_policy]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PausePolicy {
+    /// Block the calling thread until the client is resumed
+    #[default]
+    Block,
+
+    /// Fail the request immediately with
+    /// [`RequestError::Paused`][crate::RequestError::Paused]
+    Fail,
+}
+
+/// Shared paused/resumed state of a client
+#[derive(Debug, Default)]
+pub(crate) struct PauseGate {
+    /// Whether the client is currently paused
+    paused: Mutex<bool>,
+
+    /// Notified when the client is resumed
+    resumed: Condvar,
+}
+
+impl PauseGate {
+    pub(crate) fn is_paused(&self) -> bool {
+        *lock(&self.paused)
+    }
+
+    fn set_paused(&self, paused: bool) {
+        *lock(&self.paused) = paused;
+        if !paused {
+            self.resumed.notify_all();
+        }
+    }
+
+    /// Block until the gate is not paused or until `deadline` (if any) is
+    /// reached.  Returns `true` if the gate is not paused on return.
+    pub(crate) fn wait(&self, deadline: Option<Instant>) -> bool {
+        let mut paused = lock(&self.paused);
+        while *paused {
+            if let Some(deadline) = deadline {
+                let timeout = deadline.saturating_duration_since(Instant::now());
+                if timeout.is_zero() {
+                    return false;
+                }
+                paused = self
+                    .resumed
+                    .wait_timeout(paused, timeout)
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .0;
+            } else {
+                paused = self
+                    .resumed
+                    .wait(paused)
+                    .unwrap_or_else(PoisonError::into_inner);
+            }
+        }
+        true
+    }
+}
+
+impl Client {
+    /// Pause the client (and all of its clones): until
+    /// [`resume()`][Client::resume] is called, no new requests are sent.
+    /// Requests issued while paused either block or fail, depending on the
+    /// [`PausePolicy`] set with
+    /// [`ClientBuilder::with_pause_policy()`][crate::ClientBuilder::with_pause_policy].
+    ///
+    /// Requests already in progress are not interrupted, though any retries
+    /// they need to make are subject to the pause.
+    pub fn pause(&self) {
+        log::debug!("Pausing client");
+        self.pause.set_paused(true);
+    }
+
+    /// Resume a paused client, unblocking any requests waiting on it
+    pub fn resume(&self) {
+        log::debug!("Resuming client");
+        self.pause.set_paused(false);
+    }
+
+    /// Returns `true` if the client is currently paused
+    pub fn is_paused(&self) -> bool {
+        self.pause.is_paused()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn wait_until_resumed() {
+        let gate = PauseGate::default();
+        gate.set_paused(true);
+        thread::scope(|s| {
+            let waiter = s.spawn(|| gate.wait(None));
+            thread::sleep(Duration::from_millis(50));
+            gate.set_paused(false);
+            assert!(waiter.join().unwrap());
+        });
+    }
+
+    #[test]
+    fn wait_times_out() {
+        let gate = PauseGate::default();
+        gate.set_paused(true);
+        assert!(!gate.wait(Some(Instant::now() + Duration::from_millis(20))));
+        assert!(gate.is_paused());
+    }
+
+    #[test]
+    fn not_paused() {
+        let gate = PauseGate::default();
+        assert!(gate.wait(Some(Instant::now())));
+    }
+}
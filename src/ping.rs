@@ -0,0 +1,114 @@
+use super::{Client, Method, RequestError};
+use crate::util::{Retrier, RetryDecision};
+use std::time::{Duration, Instant};
+
+impl Client {
+    /// Check that the client can reach the GitHub API by making a single
+    /// request to the cheap `/zen` endpoint, and return the time taken for
+    /// the round trip.
+    ///
+    /// This confirms that the network connection, TLS setup, and
+    /// authentication headers all work without spending any meaningful
+    /// amount of rate-limit quota, making it useful as a self-check when a
+    /// program starts up.  Unlike other requests, the request is made
+    /// immediately, without pacing or retries.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the client has been shut down, if the request fails,
+    /// or if the server returns a 4xx or 5xx response (e.g., 401 for a bad
+    /// token)
+    pub fn ping(&self) -> Result<Duration, RequestError> {
+        let url = self.mkurl("/zen")?;
+        let Some(_in_flight) = self.shutdown.enter() else {
+            log::debug!("Client is shut down; not making GET request to {url}");
+            return Err(RequestError::ShutDown {
+                method: Method::Get,
+                url,
+            });
+        };
+        let mut req = self.agent().get(url.as_str());
+        if let Some(auth) = self.auth_override.as_ref() {
+            req = req.header(&self.auth_header, auth);
+        }
+        log::debug!("GET {url}");
+        let start = Instant::now();
+        let resp = req.call();
+        self.update_usage(|u| u.requests += 1);
+        if let Ok(r) = &resp {
+            self.record_rate_limit(r);
+        }
+        let RetryDecision::Success(mut r) =
+            Retrier::new(Method::Get, url.clone()).finalize(resp)?
+        else {
+            unreachable!("Retrier::finalize() should not request a retry");
+        };
+        r.body_mut()
+            .read_to_vec()
+            .map_err(|source| RequestError::Deserialize {
+                method: Method::Get,
+                url: url.clone(),
+                source: Box::new(source),
+            })?;
+        let latency = start.elapsed();
+        log::debug!("Ping to {url} took {latency:?}");
+        Ok(latency)
+    }
+
+    /// Establish a connection to the GitHub API server ahead of time by
+    /// making a cheap GET request to the base API URL, so that the DNS lookup,
+    /// TCP connection, and TLS handshake are not part of the latency of the
+    /// first real request.  The connection is kept in the client's connection
+    /// pool and reused by subsequent requests (provided they are made before
+    /// the pool's idle timeout expires).  Returns the time taken.
+    ///
+    /// This is meant for latency-sensitive programs, such as command-line
+    /// tools, that can warm up the connection while doing other work, e.g., on
+    /// a background thread.  The response's status code is ignored, as any
+    /// response shows that the connection was established.  Like [`ping()`][Client::ping], the request
+    /// is made immediately, without pacing or retries.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the client has been shut down or if the connection
+    /// could not be established or the request otherwise failed at the
+    /// transport level
+    pub fn warm_up(&self) -> Result<Duration, RequestError> {
+        let url = self.api_url.clone();
+        let Some(_in_flight) = self.shutdown.enter() else {
+            log::debug!("Client is shut down; not making GET request to {url}");
+            return Err(RequestError::ShutDown {
+                method: Method::Get,
+                url,
+            });
+        };
+        let mut req = self.agent().get(url.as_str());
+        if let Some(auth) = self.auth_override.as_ref() {
+            req = req.header(&self.auth_header, auth);
+        }
+        log::debug!("GET {url}");
+        let start = Instant::now();
+        let mut resp = req.call().map_err(|source| RequestError::Send {
+            method: Method::Get,
+            url: url.clone(),
+            source: Box::new(source),
+            request: None,
+        })?;
+        self.update_usage(|u| u.requests += 1);
+        self.record_rate_limit(&resp);
+        // Read the body so that the connection is returned to the pool
+        resp.body_mut()
+            .read_to_vec()
+            .map_err(|source| RequestError::Deserialize {
+                method: Method::Get,
+                url: url.clone(),
+                source: Box::new(source),
+            })?;
+        let elapsed = start.elapsed();
+        log::debug!(
+            "Connection to {url} warmed up in {elapsed:?} (status {})",
+            resp.status()
+        );
+        Ok(elapsed)
+    }
+}
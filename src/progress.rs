@@ -0,0 +1,356 @@
+use super::{Client, ClientBuilder, Method, ServerWarning};
+use crate::util::is_connect_error;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use ureq::http::status::StatusCode;
+use url::Url;
+
+/// An event reported to the callback set with
+/// [`ClientBuilder::with_progress_hook()`] when the client is about to sleep
+/// or when the server reports something the user should know about, so that
+/// programs can tell their users why nothing appears to be happening
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ProgressEvent {
+    /// The client is pausing before sending a request in order to avoid
+    /// exceeding a rate limit
+    RateLimitWait {
+        /// The method of the request
+        method: Method,
+
+        /// The URL to which the request will be sent
+        url: Url,
+
+        /// The time at which the request will be sent
+        until: SystemTime,
+    },
+
+    /// The client is pausing before sending a mutating request in order to
+    /// leave the minimum delay between mutating requests (one second, or two
+    /// seconds under [`PacingProfile::Actions`][crate::PacingProfile::Actions])
+    MutationDelay {
+        /// The method of the mutating request being delayed
+        method: Method,
+
+        /// The URL to which the request will be sent
+        url: Url,
+
+        /// How long the client will wait before sending the request
+        delay: Duration,
+    },
+
+    /// The client is pausing before requesting the next page of a paginated
+    /// listing in order to leave the delay set with
+    /// [`PaginationIter::with_page_delay()`][crate::PaginationIter::with_page_delay]
+    PageDelay {
+        /// The URL of the page that will be requested
+        url: Url,
+
+        /// How long the client will wait before requesting the page
+        delay: Duration,
+    },
+
+    /// A request failed, and the client is waiting before retrying it
+    Retrying {
+        /// The method of the request
+        method: Method,
+
+        /// The URL to which the request is being sent
+        url: Url,
+
+        /// The number of attempts made so far
+        attempt: u32,
+
+        /// How long the client will wait before retrying
+        delay: Duration,
+
+        /// Why the request is being retried
+        reason: RetryReason,
+    },
+
+    /// The server included a `Warning` header in its response to a request,
+    /// e.g., because the request used a deprecated feature
+    ServerWarning {
+        /// The method of the request
+        method: Method,
+
+        /// The URL to which the request was sent
+        url: Url,
+
+        /// The warning
+        warning: ServerWarning,
+    },
+}
+
+/// The reason that a request is being retried, for aggregating retries by
+/// cause
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum RetryReason {
+    /// The server responded with a 5xx error
+    ServerError {
+        /// The status code of the response
+        status: StatusCode,
+    },
+
+    /// The primary rate limit was exceeded, and the client is waiting for it
+    /// to reset
+    PrimaryRateLimit {
+        /// The time at which the rate limit resets, if the server said
+        reset: Option<SystemTime>,
+    },
+
+    /// A secondary rate limit was triggered
+    SecondaryRateLimit,
+
+    /// The legacy "abuse detection mechanism" 403 error, returned by older
+    /// GitHub Enterprise Server versions without any rate-limit headers, was
+    /// received, and the client is cooling down for the period set with
+    /// [`ClientBuilder::with_abuse_cooldown()`]
+    AbuseDetection,
+
+    /// The request failed due to a transport-level error, such as a dropped
+    /// connection
+    Transport {
+        /// The category of the error
+        kind: TransportErrorKind,
+    },
+
+    /// The server responded with a `Retry-After` header
+    RetryAfter {
+        /// The delay requested by the server
+        duration: Duration,
+    },
+}
+
+/// The category of a transport-level error that caused a request to be
+/// retried
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum TransportErrorKind {
+    /// The server's hostname could not be resolved, or the connection could
+    /// not be established
+    Connect,
+
+    /// A timeout elapsed
+    Timeout,
+
+    /// No data was received while reading the response body for longer than
+    /// the idle-read timeout set with
+    /// [`ClientBuilder::with_read_idle_timeout()`][crate::ClientBuilder::with_read_idle_timeout]
+    ReadIdle,
+
+    /// An I/O error occurred, such as the connection being reset
+    Io,
+
+    /// The server sent a malformed HTTP response
+    Protocol,
+
+    /// Some other error occurred
+    Other,
+}
+
+impl TransportErrorKind {
+    /// Categorize a [`ureq::Error`]
+    pub(crate) fn from_error(e: &ureq::Error) -> TransportErrorKind {
+        match e {
+            e if is_connect_error(e) => TransportErrorKind::Connect,
+            ureq::Error::Timeout(ureq::Timeout::RecvBody) => TransportErrorKind::ReadIdle,
+            ureq::Error::Timeout(_) => TransportErrorKind::Timeout,
+            ureq::Error::Io(_) => TransportErrorKind::Io,
+            ureq::Error::Protocol(_) => TransportErrorKind::Protocol,
+            _ => TransportErrorKind::Other,
+        }
+    }
+}
+
+impl fmt::Display for TransportErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            TransportErrorKind::Connect => "connection failure",
+            TransportErrorKind::Timeout => "timeout",
+            TransportErrorKind::ReadIdle => "stalled response body",
+            TransportErrorKind::Io => "I/O error",
+            TransportErrorKind::Protocol => "protocol error",
+            TransportErrorKind::Other => "transport error",
+        };
+        f.write_str(s)
+    }
+}
+
+impl ProgressEvent {
+    /// Returns the time at which the client will resume, i.e., send or retry
+    /// the request.  For [`ProgressEvent::MutationDelay`],
+    /// [`ProgressEvent::PageDelay`], and [`ProgressEvent::Retrying`] events,
+    /// this is computed from the current time and the delay.  Returns `None` for events that
+    /// do not involve sleeping.
+    pub fn resume_at(&self) -> Option<SystemTime> {
+        match self {
+            ProgressEvent::RateLimitWait { until, .. } => Some(*until),
+            ProgressEvent::MutationDelay { delay, .. }
+            | ProgressEvent::PageDelay { delay, .. }
+            | ProgressEvent::Retrying { delay, .. } => Some(SystemTime::now() + *delay),
+            ProgressEvent::ServerWarning { .. } => None,
+        }
+    }
+
+    /// Like [`resume_at()`][ProgressEvent::resume_at], but returns a
+    /// [`time::OffsetDateTime`] in UTC
+    ///
+    /// This method is only available when the `time` feature is enabled.
+    #[cfg(feature = "time")]
+    pub fn resume_datetime(&self) -> Option<time::OffsetDateTime> {
+        self.resume_at().map(time::OffsetDateTime::from)
+    }
+}
+
+impl RetryReason {
+    /// Returns `true` if the retry is in response to a rate-limit error
+    pub fn is_rate_limit(self) -> bool {
+        matches!(
+            self,
+            RetryReason::PrimaryRateLimit { .. }
+                | RetryReason::SecondaryRateLimit
+                | RetryReason::AbuseDetection
+                | RetryReason::RetryAfter { .. }
+        )
+    }
+}
+
+impl fmt::Display for RetryReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RetryReason::ServerError { status } => write!(f, "server error {}", status.as_u16()),
+            RetryReason::PrimaryRateLimit { .. } => f.write_str("primary rate limit exceeded"),
+            RetryReason::SecondaryRateLimit => f.write_str("secondary rate limit triggered"),
+            RetryReason::AbuseDetection => f.write_str("abuse detection mechanism triggered"),
+            RetryReason::Transport { kind } => write!(f, "{kind}"),
+            RetryReason::RetryAfter { duration } => {
+                write!(f, "server requested waiting {duration:?}")
+            }
+        }
+    }
+}
+
+/// A callback invoked with [`ProgressEvent`]s
+#[derive(Clone)]
+pub(crate) struct ProgressHook(Arc<dyn Fn(&ProgressEvent) + Send + Sync>);
+
+impl fmt::Debug for ProgressHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressHook(..)")
+    }
+}
+
+impl PartialEq for ProgressHook {
+    fn eq(&self, other: &ProgressHook) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for ProgressHook {}
+
+impl ClientBuilder {
+    /// Set a callback to invoke whenever the client is about to sleep before
+    /// sending or retrying a request because of a rate limit or an error, or
+    /// whenever the server includes a `Warning` header in a response.
+    ///
+    /// Unlike the client's log messages, which are aimed at developers, these
+    /// events are meant to be shown to end users, e.g., so that a command-line
+    /// program can print "rate limited, resuming at 14:32" instead of
+    /// appearing to hang.  The callback is invoked on the thread making the
+    /// request, immediately before it sleeps or after it receives the
+    /// response, and it should return quickly.
+    ///
+    /// By default, no callback is set.
+    pub fn with_progress_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&ProgressEvent) + Send + Sync + 'static,
+    {
+        self.progress_hook = Some(ProgressHook(Arc::new(hook)));
+        self
+    }
+}
+
+impl Client {
+    /// Pass `event` to the progress callback, if any
+    pub(crate) fn report_progress(&self, event: ProgressEvent) {
+        if let Some(ProgressHook(hook)) = self.progress_hook.as_ref() {
+            hook(&event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn hook_receives_events() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let client = ClientBuilder::new()
+            .with_progress_hook(move |e| sink.lock().unwrap().push(e.clone()))
+            .build()
+            .unwrap();
+        let event = ProgressEvent::Retrying {
+            method: Method::Get,
+            url: Url::parse("https://api.github.com/zen").unwrap(),
+            attempt: 1,
+            delay: Duration::from_millis(100),
+            reason: RetryReason::Transport {
+                kind: TransportErrorKind::Io,
+            },
+        };
+        client.report_progress(event.clone());
+        assert_eq!(*events.lock().unwrap(), [event]);
+    }
+
+    #[test]
+    fn mutation_delay_event() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let client = ClientBuilder::new()
+            .with_api_url(Url::parse("https://127.0.0.1:9").unwrap())
+            .with_total_deadline(Duration::from_millis(500))
+            .with_progress_hook(move |e| sink.lock().unwrap().push(e.clone()))
+            .build()
+            .unwrap();
+        *crate::util::lock(&client.last_mutation) =
+            std::time::Instant::now().checked_sub(Duration::from_millis(950));
+        let r = client.post::<_, serde_json::Value>("/user/repos", &serde_json::json!({}));
+        assert!(r.is_err());
+        let events = events.lock().unwrap();
+        let Some(ProgressEvent::MutationDelay { method, url, delay }) = events.first() else {
+            panic!("First event was not MutationDelay: {events:?}");
+        };
+        assert_eq!(*method, Method::Post);
+        assert_eq!(url.as_str(), "https://127.0.0.1:9/user/repos");
+        assert!(*delay <= Duration::from_millis(50));
+    }
+
+    #[test]
+    fn builder_eq() {
+        let builder = ClientBuilder::new().with_progress_hook(|_| ());
+        assert_eq!(builder.clone(), builder);
+        assert_ne!(builder, ClientBuilder::new().with_progress_hook(|_| ()));
+    }
+
+    #[test]
+    fn transport_error_kinds() {
+        assert_eq!(
+            TransportErrorKind::from_error(&ureq::Error::Timeout(ureq::Timeout::RecvBody)),
+            TransportErrorKind::ReadIdle
+        );
+        assert_eq!(
+            TransportErrorKind::from_error(&ureq::Error::Timeout(ureq::Timeout::Global)),
+            TransportErrorKind::Timeout
+        );
+        assert_eq!(
+            TransportErrorKind::from_error(&ureq::Error::Timeout(ureq::Timeout::Connect)),
+            TransportErrorKind::Connect
+        );
+    }
+}
@@ -0,0 +1,430 @@
+use super::{Client, PaginationIter, RequestError};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use thiserror::Error;
+use url::form_urlencoded;
+
+/// The largest `per_page` value accepted by the GitHub API
+pub const MAX_PER_PAGE: u32 = 100;
+
+/// Common query parameters for paginated list endpoints.
+///
+/// A `PageParams` can be passed to [`Client::get_with_query()`],
+/// [`Client::paginate_with_query()`], or the other `*_with_query()` methods,
+/// either on its own or as a `#[serde(flatten)]` field of a larger parameters
+/// struct.  Fields that are `None` are omitted from the query.
+///
+/// The `per_page` value is checked by [`with_query()`], so an out-of-range page
+/// size is reported before any request is made rather than by a 422 response
+/// from the server.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, Serialize)]
+pub struct PageParams {
+    /// The number of items per page, from 1 to [`MAX_PER_PAGE`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<u32>,
+
+    /// The page number to fetch, starting from 1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u64>,
+
+    /// The direction in which to sort the results
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direction: Option<Direction>,
+
+    /// The field by which to sort the results (e.g., `"created"` or
+    /// `"updated"`); the accepted values depend on the endpoint
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<String>,
+}
+
+impl PageParams {
+    /// Create a new `PageParams` with all fields set to `None`
+    pub fn new() -> PageParams {
+        PageParams::default()
+    }
+
+    /// Set the number of items per page
+    pub fn with_per_page(mut self, per_page: u32) -> PageParams {
+        self.per_page = Some(per_page);
+        self
+    }
+
+    /// Set the page number to fetch
+    pub fn with_page(mut self, page: u64) -> PageParams {
+        self.page = Some(page);
+        self
+    }
+
+    /// Set the direction in which to sort the results
+    pub fn with_direction(mut self, direction: Direction) -> PageParams {
+        self.direction = Some(direction);
+        self
+    }
+
+    /// Set the field by which to sort the results
+    pub fn with_sort<S: Into<String>>(mut self, sort: S) -> PageParams {
+        self.sort = Some(sort.into());
+        self
+    }
+
+    /// Check that the parameters are acceptable to the GitHub API
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if `per_page` is zero or greater than [`MAX_PER_PAGE`]
+    pub fn validate(&self) -> Result<(), QueryError> {
+        match self.per_page {
+            Some(n) if n == 0 || n > MAX_PER_PAGE => Err(QueryError::PerPage(n)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The direction in which to sort results
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// Ascending order
+    Asc,
+
+    /// Descending order
+    Desc,
+}
+
+/// Append the fields of `query`, which may be any value that serializes to a
+/// map (such as a [`PageParams`], a struct, or a `HashMap`), to `path` as
+/// query parameters.  `path` may be either a URL or a URL path, with or
+/// without an existing query string.
+///
+/// Strings, numbers, and booleans are used as-is, arrays of them are joined
+/// with commas (as for the `labels` parameter of the issues endpoints), and
+/// `null` fields are omitted.  The parameters are appended in order by name.
+///
+/// A `per_page` parameter, whether from a [`PageParams`] or from any other
+/// query type, must be an integer from 1 to [`MAX_PER_PAGE`].
+///
+/// # Example
+///
+/// ```
+/// use minigh::{Direction, PageParams, with_query};
+///
+/// let params = PageParams::new().with_per_page(100).with_direction(Direction::Asc);
+/// assert_eq!(
+///     with_query("/repos/octocat/hello-world/issues?state=open", &params).unwrap(),
+///     "/repos/octocat/hello-world/issues?state=open&direction=asc&per_page=100",
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Returns `Err` if `query` fails to serialize, does not serialize to a map,
+/// has a field that is not a scalar or an array of scalars, or has an
+/// out-of-range `per_page`
+pub fn with_query<Q: Serialize + ?Sized>(path: &str, query: &Q) -> Result<String, QueryError> {
+    let params = match serde_json::to_value(query) {
+        Ok(Value::Object(params)) => params,
+        Ok(_) => return Err(QueryError::NotAMap),
+        Err(e) => return Err(QueryError::Serialize(e.to_string())),
+    };
+    if let Some(n) = params.get("per_page").and_then(per_page_value)
+        && (n == 0 || n > u64::from(MAX_PER_PAGE))
+    {
+        return Err(QueryError::PerPage(u32::try_from(n).unwrap_or(u32::MAX)));
+    }
+    let mut pairs = form_urlencoded::Serializer::new(String::new());
+    for (name, value) in &params {
+        let value = match value {
+            Value::Null => continue,
+            Value::Array(items) => items
+                .iter()
+                .map(|v| scalar(v).ok_or_else(|| QueryError::NotScalar(name.clone())))
+                .collect::<Result<Vec<_>, _>>()?
+                .join(","),
+            v => scalar(v).ok_or_else(|| QueryError::NotScalar(name.clone()))?,
+        };
+        pairs.append_pair(name, &value);
+    }
+    let pairs = pairs.finish();
+    if pairs.is_empty() {
+        return Ok(path.to_owned());
+    }
+    let sep = if path.contains('?') { '&' } else { '?' };
+    Ok(format!("{path}{sep}{pairs}"))
+}
+
+/// Returns the value of a `per_page` parameter given as an unsigned integer or
+/// a string thereof
+fn per_page_value(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Returns the query-string form of a string, number, or boolean
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(_) | Value::Bool(_) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+/// Error returned by [`with_query()`] and [`PageParams::validate()`]
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum QueryError {
+    /// The `per_page` parameter was outside the range accepted by the API
+    #[error("per_page must be between 1 and {MAX_PER_PAGE}, got {0}")]
+    PerPage(u32),
+
+    /// The query parameters failed to serialize
+    #[error("failed to serialize query parameters: {0}")]
+    Serialize(String),
+
+    /// The query parameters did not serialize to a map
+    #[error("query parameters must serialize to a map")]
+    NotAMap,
+
+    /// A query parameter was not a string, number, boolean, or array thereof
+    #[error("value for query parameter {0:?} is not a string, number, boolean, or array thereof")]
+    NotScalar(String),
+}
+
+impl Client {
+    /// Like [`get()`][Client::get], but the fields of `query` are appended
+    /// to `path` as query parameters as described in [`with_query()`]
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RequestError::Query`] error if the query cannot be
+    /// constructed; see [`get()`][Client::get] for other errors.
+    pub fn get_with_query<T, Q>(&self, path: &str, query: &Q) -> Result<T, RequestError>
+    where
+        T: DeserializeOwned,
+        Q: Serialize + ?Sized,
+    {
+        self.get(&append(path, query)?)
+    }
+
+    /// Like [`paginate()`][Client::paginate], but the fields of `query` are
+    /// appended to `path` as query parameters as described in
+    /// [`with_query()`]
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RequestError::Query`] error if the query cannot be
+    /// constructed
+    pub fn paginate_with_query<T, Q>(
+        &self,
+        path: &str,
+        query: &Q,
+    ) -> Result<PaginationIter<'_, T>, RequestError>
+    where
+        T: DeserializeOwned,
+        Q: Serialize + ?Sized,
+    {
+        Ok(self.paginate(&append(path, query)?))
+    }
+
+    /// Like [`post()`][Client::post], but the fields of `query` are appended
+    /// to `path` as query parameters as described in [`with_query()`]
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RequestError::Query`] error if the query cannot be
+    /// constructed; see [`post()`][Client::post] for other errors.
+    pub fn post_with_query<T, U, Q>(
+        &self,
+        path: &str,
+        query: &Q,
+        payload: &T,
+    ) -> Result<U, RequestError>
+    where
+        T: Serialize,
+        U: DeserializeOwned,
+        Q: Serialize + ?Sized,
+    {
+        self.post(&append(path, query)?, payload)
+    }
+
+    /// Like [`put()`][Client::put], but the fields of `query` are appended to
+    /// `path` as query parameters as described in [`with_query()`]
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RequestError::Query`] error if the query cannot be
+    /// constructed; see [`put()`][Client::put] for other errors.
+    pub fn put_with_query<T, U, Q>(
+        &self,
+        path: &str,
+        query: &Q,
+        payload: &T,
+    ) -> Result<U, RequestError>
+    where
+        T: Serialize,
+        U: DeserializeOwned,
+        Q: Serialize + ?Sized,
+    {
+        self.put(&append(path, query)?, payload)
+    }
+
+    /// Like [`patch()`][Client::patch], but the fields of `query` are
+    /// appended to `path` as query parameters as described in
+    /// [`with_query()`]
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RequestError::Query`] error if the query cannot be
+    /// constructed; see [`patch()`][Client::patch] for other errors.
+    pub fn patch_with_query<T, U, Q>(
+        &self,
+        path: &str,
+        query: &Q,
+        payload: &T,
+    ) -> Result<U, RequestError>
+    where
+        T: Serialize,
+        U: DeserializeOwned,
+        Q: Serialize + ?Sized,
+    {
+        self.patch(&append(path, query)?, payload)
+    }
+
+    /// Like [`delete()`][Client::delete], but the fields of `query` are
+    /// appended to `path` as query parameters as described in
+    /// [`with_query()`]
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RequestError::Query`] error if the query cannot be
+    /// constructed; see [`delete()`][Client::delete] for other errors.
+    pub fn delete_with_query<Q>(&self, path: &str, query: &Q) -> Result<(), RequestError>
+    where
+        Q: Serialize + ?Sized,
+    {
+        self.delete(&append(path, query)?)
+    }
+}
+
+/// Call [`with_query()`] and convert any error to a [`RequestError`]
+fn append<Q: Serialize + ?Sized>(path: &str, query: &Q) -> Result<String, RequestError> {
+    with_query(path, query).map_err(|source| RequestError::Query {
+        path: path.to_owned(),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn page_params() {
+        let params = PageParams::new()
+            .with_per_page(50)
+            .with_page(3)
+            .with_direction(Direction::Desc)
+            .with_sort("updated");
+        assert_eq!(
+            with_query("/user/repos", &params).unwrap(),
+            "/user/repos?direction=desc&page=3&per_page=50&sort=updated"
+        );
+    }
+
+    #[test]
+    fn empty_params() {
+        assert_eq!(
+            with_query("/user/repos", &PageParams::new()).unwrap(),
+            "/user/repos"
+        );
+    }
+
+    #[test]
+    fn invalid_per_page() {
+        for n in [0, 101] {
+            let params = PageParams::new().with_per_page(n);
+            assert_eq!(params.validate(), Err(QueryError::PerPage(n)));
+            assert_eq!(
+                with_query("/user/repos", &params),
+                Err(QueryError::PerPage(n))
+            );
+            assert_eq!(
+                with_query("/user/repos", &json!({"per_page": n})),
+                Err(QueryError::PerPage(n))
+            );
+            assert_eq!(
+                with_query("/user/repos", &json!({"per_page": n.to_string()})),
+                Err(QueryError::PerPage(n))
+            );
+        }
+        assert_eq!(
+            with_query("/user/repos", &json!({"per_page": 5_000_000_000u64})),
+            Err(QueryError::PerPage(u32::MAX))
+        );
+        assert_eq!(PageParams::new().with_per_page(100).validate(), Ok(()));
+        assert_eq!(
+            with_query("/user/repos", &json!({"per_page": 100})).unwrap(),
+            "/user/repos?per_page=100"
+        );
+    }
+
+    #[test]
+    fn flattened() {
+        #[derive(Serialize)]
+        struct IssueParams {
+            state: &'static str,
+            labels: Vec<&'static str>,
+            since: Option<String>,
+            #[serde(flatten)]
+            page: PageParams,
+        }
+
+        let params = IssueParams {
+            state: "open",
+            labels: vec!["bug", "good first issue"],
+            since: None,
+            page: PageParams::new().with_per_page(100),
+        };
+        assert_eq!(
+            with_query("/repos/octocat/hello-world/issues", &params).unwrap(),
+            "/repos/octocat/hello-world/issues?labels=bug%2Cgood+first+issue&per_page=100&state=open"
+        );
+    }
+
+    #[test]
+    fn bad_queries() {
+        assert_eq!(with_query("/user", &json!(42)), Err(QueryError::NotAMap));
+        assert_eq!(
+            with_query("/user", &json!({"filter": {"a": 1}})),
+            Err(QueryError::NotScalar("filter".into()))
+        );
+    }
+
+    #[test]
+    fn client_error() {
+        let client = Client::new("hunter2").unwrap();
+        let r = client
+            .paginate_with_query::<Value, _>("/user/repos", &PageParams::new().with_per_page(1000));
+        assert!(matches!(
+            r,
+            Err(RequestError::Query {
+                source: QueryError::PerPage(1000),
+                ..
+            })
+        ));
+        let r = client.post_with_query::<_, Value, _>(
+            "/repos/octocat/hello-world/issues",
+            &json!(["not", "a", "map"]),
+            &json!({"title": "Hi"}),
+        );
+        assert!(matches!(
+            r,
+            Err(RequestError::Query {
+                source: QueryError::NotAMap,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,237 @@
+use super::{Client, Method, RequestError};
+use std::io::Read;
+use std::ops::{Bound, RangeBounds};
+use ureq::http::{
+    header::{CONTENT_RANGE, HeaderMap, HeaderValue, RANGE},
+    status::StatusCode,
+};
+
+/// A portion of a response body retrieved with [`Client::get_range()`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PartialContent {
+    /// The bytes in the requested range
+    pub data: Vec<u8>,
+
+    /// The position of `data` within the complete body, as reported by the
+    /// response's `Content-Range` header.  This is `None` if the server
+    /// ignored the `Range` header and sent the complete body (from which the
+    /// requested range was then extracted) or did not send a valid
+    /// `Content-Range` header.
+    pub content_range: Option<ContentRange>,
+}
+
+/// The parsed value of a `Content-Range` header of a 206 response
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ContentRange {
+    /// The offset of the first byte of the partial content
+    pub start: u64,
+
+    /// The offset just past the last byte of the partial content
+    pub end: u64,
+
+    /// The length of the complete body, if the server reported it
+    pub complete_length: Option<u64>,
+}
+
+impl ContentRange {
+    /// Parse a `Content-Range` header value of the form `bytes
+    /// FIRST-LAST/LENGTH` or `bytes FIRST-LAST/*`
+    fn parse(s: &str) -> Option<ContentRange> {
+        let (range, length) = s.trim().strip_prefix("bytes ")?.split_once('/')?;
+        let (first, last) = range.split_once('-')?;
+        let start = first.parse::<u64>().ok()?;
+        let last = last.parse::<u64>().ok()?;
+        if last < start {
+            return None;
+        }
+        let complete_length = match length {
+            "*" => None,
+            n => Some(n.parse::<u64>().ok()?),
+        };
+        Some(ContentRange {
+            start,
+            end: last.checked_add(1)?,
+            complete_length,
+        })
+    }
+}
+
+impl Client {
+    /// Make a GET request to `path` for only the bytes of the response body
+    /// in `range`, using a `Range` header.  `path` may be either a complete
+    /// URL or a URL path to append to the base GitHub API URL.
+    ///
+    /// This is useful for inspecting the start of a large file, such as a
+    /// release asset, without downloading all of it.  If the server responds
+    /// with 206 Partial Content, its body is returned along with the parsed
+    /// `Content-Range` header.  If the server instead ignores the `Range`
+    /// header and responds with the complete body, only as much of the body
+    /// as is needed is read, and the requested bytes are returned with a
+    /// `content_range` of `None`.  In either case, fewer bytes than requested
+    /// are returned if the body is shorter than the end of the range.
+    ///
+    /// See [`request()`][Client::request] for information on lower-level
+    /// behavior.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RequestError::EmptyRange`] error if `range` is empty.
+    /// Returns other errors if the request fails, including if the server
+    /// responds with 416 Range Not Satisfiable because the range starts past
+    /// the end of the body.
+    pub fn get_range<R: RangeBounds<u64>>(
+        &self,
+        path: &str,
+        range: R,
+    ) -> Result<PartialContent, RequestError> {
+        let url = self.mkurl(path)?;
+        let empty = || RequestError::EmptyRange {
+            method: Method::Get,
+            url: url.clone(),
+        };
+        let start = match range.start_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(&n) => n.checked_add(1).ok_or_else(empty)?,
+            Bound::Unbounded => 0,
+        };
+        // The position of the last requested byte, or `None` if the range
+        // extends to the end of the body
+        let last = match range.end_bound() {
+            Bound::Included(&n) => Some(n),
+            Bound::Excluded(&n) => Some(n.checked_sub(1).ok_or_else(empty)?),
+            Bound::Unbounded => None,
+        };
+        if last.is_some_and(|last| last < start) {
+            return Err(empty());
+        }
+        let spec = match last {
+            Some(last) => format!("bytes={start}-{last}"),
+            None => format!("bytes={start}-"),
+        };
+        let Ok(value) = HeaderValue::from_str(&spec) else {
+            unreachable!("Range header value should be valid");
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert(RANGE, value);
+        self.send_with_retries::<(), _, _>(Method::Get, url, None, &headers, false, |resp| {
+            let (parts, body) = resp.into_parts();
+            let mut reader = body.into_reader();
+            let mut data = Vec::new();
+            if parts.status == StatusCode::PARTIAL_CONTENT {
+                reader.read_to_end(&mut data)?;
+                let content_range = parts
+                    .headers
+                    .get(CONTENT_RANGE)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(ContentRange::parse);
+                Ok(PartialContent {
+                    data,
+                    content_range,
+                })
+            } else {
+                log::debug!(
+                    "Server ignored Range header; extracting requested bytes from complete body"
+                );
+                match last.and_then(|last| last.checked_add(1)) {
+                    Some(end) => reader.take(end).read_to_end(&mut data)?,
+                    None => reader.read_to_end(&mut data)?,
+                };
+                let skip = usize::try_from(start).unwrap_or(usize::MAX).min(data.len());
+                data.drain(..skip);
+                Ok(PartialContent {
+                    data,
+                    content_range: None,
+                })
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockResponse, MockServer};
+
+    #[test]
+    fn parse_content_range() {
+        assert_eq!(
+            ContentRange::parse("bytes 0-511/1048576"),
+            Some(ContentRange {
+                start: 0,
+                end: 512,
+                complete_length: Some(1_048_576),
+            })
+        );
+        assert_eq!(
+            ContentRange::parse("bytes 100-199/*"),
+            Some(ContentRange {
+                start: 100,
+                end: 200,
+                complete_length: None,
+            })
+        );
+        assert_eq!(ContentRange::parse("bytes */1048576"), None);
+        assert_eq!(ContentRange::parse("bytes 10-5/100"), None);
+        assert_eq!(ContentRange::parse("items 0-9/100"), None);
+    }
+
+    #[test]
+    fn empty_range() {
+        let client = Client::new("hunter2").unwrap();
+        for r in [
+            client.get_range("https://127.0.0.1:9/", 10..10),
+            client.get_range(
+                "https://127.0.0.1:9/",
+                (Bound::Included(10), Bound::Included(9)),
+            ),
+            client.get_range("https://127.0.0.1:9/", ..0),
+        ] {
+            assert!(matches!(r, Err(RequestError::EmptyRange { .. })));
+        }
+    }
+
+    #[test]
+    fn partial_content() {
+        let server = MockServer::start(|_| {
+            MockResponse::new(206, "world").with_header("Content-Range", "bytes 7-11/13")
+        });
+        let client = server.client_builder().build().unwrap();
+        let r = client.get_range("/download", 7..12).unwrap();
+        assert_eq!(
+            r,
+            PartialContent {
+                data: b"world".to_vec(),
+                content_range: Some(ContentRange {
+                    start: 7,
+                    end: 12,
+                    complete_length: Some(13),
+                }),
+            }
+        );
+        assert_eq!(server.requests()[0].header("range"), Some("bytes=7-11"));
+    }
+
+    #[test]
+    fn range_ignored() {
+        let server = MockServer::start(|_| MockResponse::new(200, "Hello, world!"));
+        let client = server.client_builder().build().unwrap();
+        let r = client.get_range("/download", 7..12).unwrap();
+        assert_eq!(
+            r,
+            PartialContent {
+                data: b"world".to_vec(),
+                content_range: None,
+            }
+        );
+        let r = client.get_range("/download", 2..=u64::MAX).unwrap();
+        assert_eq!(r.data, b"llo, world!");
+        let r = client.get_range("/download", 20..).unwrap();
+        assert!(r.data.is_empty());
+        let reqs = server.requests();
+        assert_eq!(
+            reqs[1].header("range"),
+            Some("bytes=2-18446744073709551615")
+        );
+        assert_eq!(reqs[2].header("range"), Some("bytes=20-"));
+    }
+}
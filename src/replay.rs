@@ -0,0 +1,478 @@
+use super::{ClientBuilder, ClientConfig, Method, RequestError};
+use crate::BuildClientError;
+use crate::datetime::{compact_timestamp, iso8601_timestamp};
+use crate::dump::is_redacted;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
+use thiserror::Error;
+use ureq::{
+    Body,
+    http::{
+        Response,
+        header::{HeaderMap, HeaderName},
+    },
+};
+use url::Url;
+
+/// A record of a failed request and every attempt made to perform it,
+/// written to a file by a client configured with
+/// [`ClientBuilder::with_replay_dir()`], that can be attached to bug reports
+/// and later loaded with [`ReplayBundle::load()`] and re-executed with
+/// [`ReplayBundle::replay()`].
+///
+/// The bundle contains the client's [`ClientConfig`] (which never includes
+/// the token), the request, and the status, headers, and (for error
+/// responses) body of each response received.  The values of headers that
+/// may contain credentials are redacted.  Note that the request body is
+/// recorded in full.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ReplayBundle {
+    /// The version of `minigh` that wrote the bundle
+    pub minigh_version: String,
+
+    /// When the request failed, as an ISO 8601 timestamp
+    pub created: String,
+
+    /// The settings of the client that made the request
+    pub config: ClientConfig,
+
+    /// The request
+    pub request: ReplayRequest,
+
+    /// Each attempt made to perform the request, in order
+    pub attempts: Vec<ReplayAttempt>,
+
+    /// The error with which the request failed
+    pub error: String,
+}
+
+/// A request recorded in a [`ReplayBundle`]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ReplayRequest {
+    /// The HTTP method of the request
+    pub method: Method,
+
+    /// The URL to which the request was first sent
+    pub url: Url,
+
+    /// The headers supplied with the request in addition to the client's
+    /// own, with the values of headers that may contain credentials redacted
+    pub headers: Vec<(String, String)>,
+
+    /// The `Content-Type` of the request body, if any
+    pub content_type: Option<String>,
+
+    /// The request body, if any
+    pub body: Option<ReplayBody>,
+}
+
+/// A single attempt to perform a request, recorded in a [`ReplayBundle`]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ReplayAttempt {
+    /// The URL to which the attempt was sent
+    pub url: Url,
+
+    /// The status code of the response, or `None` if no response was
+    /// received
+    pub status: Option<u16>,
+
+    /// The headers of the response, with the values of headers that may
+    /// contain credentials redacted
+    pub headers: Vec<(String, String)>,
+
+    /// The body of the response, if the response had an error status
+    pub body: Option<ReplayBody>,
+
+    /// The transport-level error that occurred, if any
+    pub error: Option<String>,
+
+    /// How long the client waited before the next attempt, if it retried
+    #[serde(with = "crate::config::opt_secs")]
+    pub retry_delay: Option<Duration>,
+}
+
+/// A request or response body recorded in a [`ReplayBundle`]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ReplayBody {
+    /// A body that is valid UTF-8
+    Text(String),
+
+    /// A body that is not valid UTF-8, recorded as an array of bytes
+    Bytes(Vec<u8>),
+}
+
+impl ReplayBody {
+    fn new(bytes: Vec<u8>) -> ReplayBody {
+        match String::from_utf8(bytes) {
+            Ok(s) => ReplayBody::Text(s),
+            Err(e) => ReplayBody::Bytes(e.into_bytes()),
+        }
+    }
+
+    /// Returns the body as bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            ReplayBody::Text(s) => s.as_bytes(),
+            ReplayBody::Bytes(bs) => bs,
+        }
+    }
+}
+
+impl ReplayBundle {
+    /// Read a bundle from the file at `path`
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the file cannot be read or is not a valid bundle
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<ReplayBundle, ReplayError> {
+        let src = std::fs::read(path).map_err(ReplayError::Io)?;
+        serde_json::from_slice(&src).map_err(ReplayError::Json)
+    }
+
+    /// Write the bundle as JSON to the file at `path`
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the file cannot be written
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ReplayError> {
+        let content = serde_json::to_vec_pretty(self).map_err(ReplayError::Json)?;
+        std::fs::write(path, content).map_err(ReplayError::Io)
+    }
+
+    /// Re-execute the recorded request with a client built from the recorded
+    /// configuration (with replay recording turned off) and `token`, and
+    /// return the response with its body read into memory
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the client cannot be built or the request fails
+    pub fn replay(&self, token: Option<&str>) -> Result<Response<Vec<u8>>, ReplayError> {
+        let mut config = self.config.clone();
+        config.replay_dir = None;
+        let mut builder = ClientBuilder::from_config(&config);
+        if let Some(token) = token {
+            builder = builder.with_token(token);
+        }
+        let client = builder
+            .build()
+            .map_err(|e| ReplayError::Build(Box::new(e)))?;
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.request.headers {
+            if value == REDACTED {
+                continue;
+            }
+            if let (Ok(name), Ok(value)) = (name.parse::<HeaderName>(), value.parse()) {
+                headers.append(name, value);
+            }
+        }
+        let body = self.request.body.as_ref().map(|b| {
+            (
+                b.as_bytes(),
+                self.request.content_type.as_deref().unwrap_or_default(),
+            )
+        });
+        log::debug!(
+            "Replaying {} request to {}",
+            self.request.method,
+            self.request.url
+        );
+        client
+            .send_body_with_retries(
+                self.request.method,
+                self.request.url.clone(),
+                body,
+                &headers,
+                true,
+                |resp| {
+                    let (parts, mut body) = resp.into_parts();
+                    Ok(Response::from_parts(parts, body.read_to_vec()?))
+                },
+            )
+            .map_err(|e| ReplayError::Request(Box::new(e)))
+    }
+}
+
+/// Error returned by [`ReplayBundle`] methods
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    /// Reading or writing the bundle file failed
+    #[error("failed to read or write replay bundle")]
+    Io(#[source] std::io::Error),
+
+    /// The bundle could not be (de)serialized
+    #[error("failed to (de)serialize replay bundle")]
+    Json(#[source] serde_json::Error),
+
+    /// Building a client from the bundle's configuration failed
+    #[error("failed to build client for replay")]
+    Build(#[source] Box<BuildClientError>),
+
+    /// The replayed request failed
+    #[error("replayed request failed")]
+    Request(#[source] Box<RequestError>),
+}
+
+/// The placeholder for redacted header values
+const REDACTED: &str = "<redacted>";
+
+/// Writes a [`ReplayBundle`] to a file in a directory for each request that
+/// fails after at least one attempt
+#[derive(Debug)]
+pub(crate) struct ReplayRecorder {
+    /// The directory in which to write the files
+    dir: PathBuf,
+
+    /// The configuration of the client
+    config: ClientConfig,
+
+    /// The header in which the client sends its token
+    auth_header: HeaderName,
+
+    /// The number of bundles written so far, used to keep filenames unique
+    counter: AtomicU64,
+}
+
+impl ReplayRecorder {
+    pub(crate) fn new(dir: PathBuf, config: ClientConfig, auth_header: HeaderName) -> Self {
+        ReplayRecorder {
+            dir,
+            config,
+            auth_header,
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    /// Start recording a request
+    pub(crate) fn start(
+        &self,
+        method: Method,
+        url: &Url,
+        body: Option<(&[u8], &str)>,
+        headers: &HeaderMap,
+    ) -> ReplayRecording {
+        ReplayRecording {
+            request: ReplayRequest {
+                method,
+                url: url.clone(),
+                headers: self.header_pairs(headers),
+                content_type: body.map(|(_, ct)| ct.to_owned()),
+                body: body.map(|(b, _)| ReplayBody::new(b.to_vec())),
+            },
+            attempts: Vec::new(),
+        }
+    }
+
+    /// If `recording` includes at least one attempt, write a bundle for it
+    /// and the final error `e` to a new file in the directory.  Failure to
+    /// write the file is logged but otherwise ignored.
+    pub(crate) fn finish(&self, recording: ReplayRecording, e: &RequestError) {
+        if recording.attempts.is_empty() {
+            return;
+        }
+        let now = SystemTime::now();
+        let bundle = ReplayBundle {
+            minigh_version: env!("CARGO_PKG_VERSION").to_owned(),
+            created: iso8601_timestamp(now),
+            config: self.config.clone(),
+            request: recording.request,
+            attempts: recording.attempts,
+            error: format!("{e:#}"),
+        };
+        let n = self.counter.fetch_add(1, Ordering::Relaxed) + 1;
+        let path = self.dir.join(format!(
+            "{}-{n:06}-{}.replay.json",
+            compact_timestamp(now),
+            bundle.request.method
+        ));
+        let r = std::fs::create_dir_all(&self.dir)
+            .map_err(ReplayError::Io)
+            .and_then(|()| bundle.save(&path));
+        match r {
+            Ok(()) => log::debug!("Wrote replay bundle to {}", path.display()),
+            Err(e) => log::debug!("Failed to write replay bundle to {}: {e}", path.display()),
+        }
+    }
+
+    /// Record an attempt to perform a request.  If the response has an error
+    /// status, its body is read into memory and recorded, and an equivalent
+    /// response is returned.
+    pub(crate) fn record(
+        &self,
+        recording: &mut ReplayRecording,
+        url: &Url,
+        resp: Result<Response<Body>, ureq::Error>,
+    ) -> Result<Response<Body>, ureq::Error> {
+        let (resp, attempt) = match resp {
+            Ok(r) if r.status().is_client_error() || r.status().is_server_error() => {
+                let (parts, mut body) = r.into_parts();
+                let mut builder = Body::builder();
+                if let Some(mime) = body.mime_type() {
+                    builder = builder.mime_type(mime);
+                }
+                if let Some(charset) = body.charset() {
+                    builder = builder.charset(charset);
+                }
+                let data = body.read_to_vec()?;
+                let attempt = ReplayAttempt {
+                    url: url.clone(),
+                    status: Some(parts.status.as_u16()),
+                    headers: self.header_pairs(&parts.headers),
+                    body: Some(ReplayBody::new(data.clone())),
+                    error: None,
+                    retry_delay: None,
+                };
+                (Ok(Response::from_parts(parts, builder.data(data))), attempt)
+            }
+            Ok(r) => {
+                let attempt = ReplayAttempt {
+                    url: url.clone(),
+                    status: Some(r.status().as_u16()),
+                    headers: self.header_pairs(r.headers()),
+                    body: None,
+                    error: None,
+                    retry_delay: None,
+                };
+                (Ok(r), attempt)
+            }
+            Err(e) => {
+                let attempt = ReplayAttempt {
+                    url: url.clone(),
+                    status: None,
+                    headers: Vec::new(),
+                    body: None,
+                    error: Some(e.to_string()),
+                    retry_delay: None,
+                };
+                (Err(e), attempt)
+            }
+        };
+        recording.attempts.push(attempt);
+        resp
+    }
+
+    /// Convert headers to name-value pairs, redacting sensitive values
+    fn header_pairs(&self, headers: &HeaderMap) -> Vec<(String, String)> {
+        headers
+            .iter()
+            .map(|(name, value)| {
+                let value = if is_redacted(name, &self.auth_header) {
+                    REDACTED.to_owned()
+                } else {
+                    String::from_utf8_lossy(value.as_bytes()).into_owned()
+                };
+                (name.as_str().to_owned(), value)
+            })
+            .collect()
+    }
+}
+
+/// A request being recorded by a [`ReplayRecorder`]
+#[derive(Debug)]
+pub(crate) struct ReplayRecording {
+    request: ReplayRequest,
+    attempts: Vec<ReplayAttempt>,
+}
+
+impl ReplayRecording {
+    /// Record that reading the body of the most recent response failed with
+    /// the error `e`
+    pub(crate) fn record_read_error(&mut self, e: &ureq::Error) {
+        if let Some(attempt) = self.attempts.last_mut() {
+            attempt.error = Some(e.to_string());
+        }
+    }
+
+    /// Record that the client waited `delay` before the next attempt
+    pub(crate) fn record_retry_delay(&mut self, delay: Duration) {
+        if let Some(attempt) = self.attempts.last_mut() {
+            attempt.retry_delay = Some(delay);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ureq::http::{
+        StatusCode,
+        header::{AUTHORIZATION, CONTENT_TYPE, HeaderValue, SET_COOKIE},
+    };
+
+    #[test]
+    fn record_and_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("minigh-replay-test-{}", std::process::id()));
+        let recorder = ReplayRecorder::new(
+            dir.clone(),
+            ClientBuilder::new().with_token("hunter2").config(),
+            AUTHORIZATION,
+        );
+        let url = Url::parse("https://api.github.com/markdown/raw").unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer hunter2"));
+        let mut recording =
+            recorder.start(Method::Post, &url, Some((b"# Hi", "text/plain")), &headers);
+
+        let mut resp = Response::new(Body::builder().data(b"oops".to_vec()));
+        *resp.status_mut() = StatusCode::BAD_GATEWAY;
+        resp.headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+        resp.headers_mut()
+            .insert(SET_COOKIE, HeaderValue::from_static("session=abc"));
+        let mut resp = recorder.record(&mut recording, &url, Ok(resp)).unwrap();
+        assert_eq!(resp.body_mut().read_to_string().unwrap(), "oops");
+        recording.record_retry_delay(Duration::from_millis(1500));
+        let r = recorder.record(&mut recording, &url, Err(ureq::Error::ConnectionFailed));
+        assert!(r.is_err());
+
+        let e = RequestError::Send {
+            method: Method::Post,
+            url: url.clone(),
+            source: Box::new(ureq::Error::ConnectionFailed),
+            request: None,
+        };
+        recorder.finish(recording, &e);
+        let path = std::fs::read_dir(&dir)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        let src = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(!src.contains("hunter2"));
+        assert!(!src.contains("session=abc"));
+        let bundle = serde_json::from_str::<ReplayBundle>(&src).unwrap();
+        assert_eq!(bundle.request.method, Method::Post);
+        assert_eq!(
+            bundle.request.headers,
+            [("authorization".to_owned(), REDACTED.to_owned())]
+        );
+        assert_eq!(bundle.request.body, Some(ReplayBody::Text("# Hi".into())));
+        assert_eq!(bundle.request.content_type.as_deref(), Some("text/plain"));
+        assert_eq!(bundle.attempts.len(), 2);
+        assert_eq!(bundle.attempts[0].status, Some(502));
+        assert_eq!(
+            bundle.attempts[0].body,
+            Some(ReplayBody::Text("oops".into()))
+        );
+        assert_eq!(
+            bundle.attempts[0].retry_delay,
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(bundle.attempts[1].status, None);
+        assert!(bundle.attempts[1].error.is_some());
+        assert_eq!(bundle.config, ClientBuilder::new().config());
+    }
+
+    #[test]
+    fn binary_body() {
+        let body = ReplayBody::new(vec![0xFF, 0x00]);
+        assert_eq!(body, ReplayBody::Bytes(vec![0xFF, 0x00]));
+        let s = serde_json::to_string(&body).unwrap();
+        assert_eq!(s, "[255,0]");
+        assert_eq!(serde_json::from_str::<ReplayBody>(&s).unwrap(), body);
+    }
+}
@@ -0,0 +1,263 @@
+use super::{Client, Method, QueryError, RequestBody, RequestError, RequestParts, RequestPolicy};
+use serde::{Serialize, de::DeserializeOwned};
+use std::fmt;
+use std::time::Duration;
+use ureq::{
+    Body,
+    http::{
+        Response,
+        header::{HeaderMap, HeaderName, HeaderValue},
+    },
+};
+
+/// A builder for a single request with per-request customizations, created
+/// with [`Client::req()`].
+///
+/// Query parameters, extra headers, a timeout, and a body can be set on the
+/// builder before the request is sent with
+/// [`send_json()`][RequestBuilder::send_json] or
+/// [`send_raw()`][RequestBuilder::send_raw].  Errors in constructing the
+/// request (such as query parameters that cannot be serialized) are reported
+/// when it is sent.
+///
+/// # Example
+///
+/// ```no_run
+/// # use minigh::{Client, Method, PageParams};
+/// # use serde_json::Value;
+/// # use std::time::Duration;
+/// # let client = Client::new("token").unwrap();
+/// let issues = client
+///     .req(Method::Get, "/repos/octocat/hello-world/issues")
+///     .with_query(&PageParams::new().with_per_page(10))
+///     .with_timeout(Duration::from_secs(30))
+///     .send_json::<Vec<Value>>()?;
+/// # Ok::<(), minigh::RequestError>(())
+/// ```
+pub struct RequestBuilder<'a> {
+    client: &'a Client,
+    method: Method,
+    path: String,
+    headers: HeaderMap,
+    body: Option<RequestBody>,
+    policy: RequestPolicy,
+    error: Option<Deferred>,
+}
+
+/// An error encountered while building a request, reported when the request
+/// is sent
+#[derive(Debug)]
+enum Deferred {
+    Query { source: QueryError, path: String },
+    Json(serde_json::Error),
+}
+
+impl<'a> RequestBuilder<'a> {
+    pub(crate) fn new(client: &'a Client, method: Method, path: &str) -> Self {
+        RequestBuilder {
+            client,
+            method,
+            path: path.to_owned(),
+            headers: HeaderMap::new(),
+            body: None,
+            policy: RequestPolicy::default(),
+            error: None,
+        }
+    }
+
+    /// Append the fields of `query` to the request path as query parameters,
+    /// as described for [`with_query()`][crate::with_query].  This may be
+    /// called multiple times.
+    pub fn with_query<Q: Serialize + ?Sized>(mut self, query: &Q) -> Self {
+        if self.error.is_none() {
+            match super::with_query(&self.path, query) {
+                Ok(path) => self.path = path,
+                Err(source) => {
+                    self.error = Some(Deferred::Query {
+                        source,
+                        path: self.path.clone(),
+                    });
+                }
+            }
+        }
+        self
+    }
+
+    /// Add a header to the request, replacing any previous values for the
+    /// same header.  Headers set here override the client's own headers.
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Fail with [`RequestError::DeadlineExceeded`] if the request (including
+    /// any retries and waits) does not complete within `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.policy.timeout = Some(timeout);
+        self
+    }
+
+    /// Override the client's policies for this request.  This replaces any
+    /// timeout set with [`with_timeout()`][RequestBuilder::with_timeout].
+    pub fn with_policy(mut self, policy: RequestPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Send `payload`, serialized as JSON, as the request body
+    pub fn with_json<T: Serialize + ?Sized>(mut self, payload: &T) -> Self {
+        match serde_json::to_value(payload) {
+            Ok(value) => self.body = Some(RequestBody::Json(value)),
+            Err(e) => {
+                if self.error.is_none() {
+                    self.error = Some(Deferred::Json(e));
+                }
+            }
+        }
+        self
+    }
+
+    /// Send `data` as-is as the request body with the given `Content-Type`
+    pub fn with_body<D: Into<Vec<u8>>>(mut self, data: D, content_type: &str) -> Self {
+        self.body = Some(RequestBody::Raw {
+            data: data.into(),
+            content_type: content_type.to_owned(),
+        });
+        self
+    }
+
+    /// Send the request and deserialize the response body as JSON.
+    ///
+    /// See [`Client::request()`] for information on lower-level behavior.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the request could not be constructed, if the request
+    /// fails, or if the response body cannot be deserialized as `U`
+    pub fn send_json<U: DeserializeOwned>(self) -> Result<U, RequestError> {
+        let client = self.client;
+        let parts = self.into_parts()?;
+        let r = client.execute_with(&parts, false, |resp| {
+            let (parts, mut body) = resp.into_parts();
+            Ok(Response::from_parts(parts, body.read_to_vec()?))
+        })?;
+        client.decode_json(parts.method, parts.url, r.body())
+    }
+
+    /// Send the request and return the response with its body unread.
+    /// Statuses set with [`Client::expect_statuses()`] or
+    /// [`RequestPolicy::expected_statuses`] are returned as `Ok` responses.
+    ///
+    /// See [`Client::request()`] for information on lower-level behavior.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the request could not be constructed or if the
+    /// request fails
+    pub fn send_raw(self) -> Result<Response<Body>, RequestError> {
+        let client = self.client;
+        client.execute(&self.into_parts()?)
+    }
+
+    /// Resolve the request path and convert the builder to a
+    /// [`RequestParts`], reporting any errors encountered while building
+    fn into_parts(self) -> Result<RequestParts, RequestError> {
+        let json_error = match self.error {
+            Some(Deferred::Query { source, path }) => {
+                return Err(RequestError::Query { source, path });
+            }
+            Some(Deferred::Json(e)) => Some(e),
+            None => None,
+        };
+        let url = self.client.mkurl(&self.path)?;
+        if let Some(source) = json_error {
+            return Err(RequestError::Send {
+                method: self.method,
+                url,
+                source: Box::new(ureq::Error::Json(source)),
+                request: None,
+            });
+        }
+        Ok(RequestParts {
+            method: self.method,
+            url,
+            headers: self.headers,
+            body: self.body,
+            policy: self.policy,
+        })
+    }
+}
+
+impl fmt::Debug for RequestBuilder<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestBuilder")
+            .field("method", &self.method)
+            .field("path", &self.path)
+            .field("headers", &self.headers)
+            .field("body", &self.body)
+            .field("policy", &self.policy)
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Client {
+    /// Start building a request with method `method` to `path`, which may be
+    /// either a complete URL or a URL path to append to the base GitHub API
+    /// URL.  See [`RequestBuilder`].
+    pub fn req(&self, method: Method, path: &str) -> RequestBuilder<'_> {
+        RequestBuilder::new(self, method, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PageParams;
+    use serde_json::{Value, json};
+
+    #[test]
+    fn build_parts() {
+        let client = Client::new("hunter2").unwrap();
+        let parts = client
+            .req(Method::Post, "/repos/octocat/hello-world/issues")
+            .with_query(&json!({"state": "open"}))
+            .with_query(&PageParams::new().with_per_page(50))
+            .with_header(
+                HeaderName::from_static("x-request-id"),
+                HeaderValue::from_static("abc123"),
+            )
+            .with_timeout(Duration::from_secs(10))
+            .with_json(&json!({"title": "Hi"}))
+            .into_parts()
+            .unwrap();
+        assert_eq!(parts.method, Method::Post);
+        assert_eq!(
+            parts.url.as_str(),
+            "https://api.github.com/repos/octocat/hello-world/issues?state=open&per_page=50"
+        );
+        assert_eq!(parts.headers["x-request-id"], "abc123");
+        assert_eq!(parts.body, Some(RequestBody::Json(json!({"title": "Hi"}))));
+        assert_eq!(parts.policy.timeout, Some(Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn deferred_query_error() {
+        let client = Client::new("hunter2").unwrap();
+        let r = client
+            .req(Method::Get, "/user/repos")
+            .with_query(&PageParams::new().with_per_page(1000))
+            .send_json::<Value>();
+        assert!(matches!(r, Err(RequestError::Query { .. })));
+    }
+
+    #[test]
+    fn timeout_exceeded() {
+        let client = Client::new("hunter2").unwrap();
+        let r = client
+            .req(Method::Get, "https://127.0.0.1:9/")
+            .with_timeout(Duration::ZERO)
+            .send_raw();
+        assert!(matches!(r, Err(RequestError::DeadlineExceeded { .. })));
+    }
+}
@@ -0,0 +1,120 @@
+use super::{Client, ClientBuilder, Method, RequestError};
+use crate::util::lock;
+use serde::{Serialize, de::DeserializeOwned};
+use ureq::http::header::HeaderMap;
+
+/// A recommended allowlist of response headers to retain with
+/// [`ClientBuilder::with_retained_headers()`]: `ETag`, `Link`, the
+/// `X-RateLimit-*` headers, and `X-GitHub-Request-Id`
+pub const RECOMMENDED_RETAINED_HEADERS: &[&str] = &[
+    "etag",
+    "link",
+    "x-ratelimit-limit",
+    "x-ratelimit-remaining",
+    "x-ratelimit-reset",
+    "x-ratelimit-used",
+    "x-ratelimit-resource",
+    "x-github-request-id",
+];
+
+impl ClientBuilder {
+    /// Set an allowlist of response headers (e.g.,
+    /// [`RECOMMENDED_RETAINED_HEADERS`]) to retain from every response
+    /// received by the client.  The retained headers of the most recent
+    /// response can be retrieved with [`Client::last_retained_headers()`],
+    /// and those of a specific request by making it with
+    /// [`Client::request_json_retained()`].
+    ///
+    /// This is a lighter-weight alternative to keeping a full
+    /// [`ResponseMeta`][crate::ResponseMeta] for every response.
+    ///
+    /// By default, no headers are retained.
+    pub fn with_retained_headers<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.retained_headers = names.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+impl Client {
+    /// Returns the headers in the allowlist set with
+    /// [`ClientBuilder::with_retained_headers()`] from the most recent
+    /// response received by the client or any of its clones.  Returns an
+    /// empty map if no allowlist was set or no response has been received
+    /// yet.
+    pub fn last_retained_headers(&self) -> HeaderMap {
+        lock(&self.last_retained_headers).clone()
+    }
+
+    /// Like [`request_json()`][Client::request_json], but also returns the
+    /// response's headers in the allowlist set with
+    /// [`ClientBuilder::with_retained_headers()`]
+    pub fn request_json_retained<T: Serialize, U: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        payload: Option<&T>,
+    ) -> Result<(U, HeaderMap), RequestError> {
+        let url = self.mkurl(path)?;
+        let r = self.request_buffered::<T>(method, url.clone(), payload, &HeaderMap::new())?;
+        let headers = self.retain_headers(r.headers());
+        let value = self.decode_json(method, url, r.body())?;
+        Ok((value, headers))
+    }
+
+    /// Return the headers in `headers` that are in the client's allowlist
+    fn retain_headers(&self, headers: &HeaderMap) -> HeaderMap {
+        let mut retained = HeaderMap::new();
+        for name in self.retained_headers.iter() {
+            for value in headers.get_all(name) {
+                retained.append(name.clone(), value.clone());
+            }
+        }
+        retained
+    }
+
+    /// If the client has a header allowlist, store the allowlisted headers of
+    /// a response as the most recently retained headers
+    pub(crate) fn record_retained_headers(&self, headers: &HeaderMap) {
+        if !self.retained_headers.is_empty() {
+            *lock(&self.last_retained_headers) = self.retain_headers(headers);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BuildClientError;
+    use ureq::http::header::{ETAG, HeaderValue, SERVER};
+
+    #[test]
+    fn retains_allowlisted_headers() {
+        let client = Client::builder()
+            .with_retained_headers(RECOMMENDED_RETAINED_HEADERS.iter().copied())
+            .build()
+            .unwrap();
+        assert!(client.last_retained_headers().is_empty());
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, HeaderValue::from_static("\"abc123\""));
+        headers.insert(SERVER, HeaderValue::from_static("github.com"));
+        client.record_retained_headers(&headers);
+        let retained = client.last_retained_headers();
+        assert_eq!(retained.len(), 1);
+        assert_eq!(retained[ETAG], "\"abc123\"");
+    }
+
+    #[test]
+    fn invalid_retained_header() {
+        let r = Client::builder()
+            .with_retained_headers(["X Request Id"])
+            .build();
+        assert!(matches!(
+            r,
+            Err(BuildClientError::InvalidHeaderName { ref name, .. }) if name == "X Request Id"
+        ));
+    }
+}
@@ -0,0 +1,188 @@
+use super::Client;
+use crate::util::lock;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+
+/// The name of the stream used by clients not created with
+/// [`Client::with_stream()`]
+pub(crate) static DEFAULT_STREAM: &str = "default";
+
+/// A scheduler that limits the number of requests a client has in progress
+/// at once and, when requests have to wait for a free slot, hands out slots
+/// round-robin between the streams the waiting requests belong to
+#[derive(Debug)]
+pub(crate) struct FairScheduler {
+    /// The maximum number of requests that may be in progress at once
+    limit: usize,
+
+    state: Mutex<SchedulerState>,
+
+    /// Notified whenever slots are granted to waiting requests
+    granted: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct SchedulerState {
+    /// The number of requests currently holding a slot
+    active: usize,
+
+    /// The ticket to give to the next request to arrive
+    next_ticket: u64,
+
+    /// The tickets of the requests waiting for a slot, keyed by stream
+    queues: HashMap<Arc<str>, VecDeque<u64>>,
+
+    /// The streams with waiting requests, in the order in which they will be
+    /// granted slots
+    order: VecDeque<Arc<str>>,
+
+    /// The tickets of waiting requests that have been granted slots but have
+    /// not yet woken up
+    granted: HashSet<u64>,
+}
+
+impl SchedulerState {
+    /// Grant free slots to waiting requests, taking one request from each
+    /// stream in turn.  Returns `true` if any slots were granted.
+    fn dispatch(&mut self, limit: usize) -> bool {
+        let mut any = false;
+        while self.active < limit
+            && let Some(stream) = self.order.pop_front()
+        {
+            let Some(queue) = self.queues.get_mut(&stream) else {
+                unreachable!("Stream in order list should have a queue");
+            };
+            let Some(ticket) = queue.pop_front() else {
+                unreachable!("Stream in order list should have waiting requests");
+            };
+            if queue.is_empty() {
+                self.queues.remove(&stream);
+            } else {
+                self.order.push_back(stream);
+            }
+            self.granted.insert(ticket);
+            self.active += 1;
+            any = true;
+        }
+        any
+    }
+}
+
+impl FairScheduler {
+    pub(crate) fn new(limit: usize) -> FairScheduler {
+        FairScheduler {
+            limit: limit.max(1),
+            state: Mutex::new(SchedulerState::default()),
+            granted: Condvar::new(),
+        }
+    }
+
+    /// Block until a slot is available for a request belonging to `stream`,
+    /// and return a permit that frees the slot when dropped
+    pub(crate) fn acquire(&self, stream: &Arc<str>) -> Permit<'_> {
+        let mut state = lock(&self.state);
+        let ticket = state.next_ticket;
+        state.next_ticket += 1;
+        let queue = state.queues.entry(Arc::clone(stream)).or_default();
+        queue.push_back(ticket);
+        if queue.len() == 1 {
+            state.order.push_back(Arc::clone(stream));
+        }
+        if state.dispatch(self.limit) {
+            self.granted.notify_all();
+        }
+        while !state.granted.remove(&ticket) {
+            state = self
+                .granted
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        Permit { scheduler: self }
+    }
+
+    /// Free a slot and grant it to the next waiting request, if any
+    fn release(&self) {
+        let mut state = lock(&self.state);
+        state.active = state.active.saturating_sub(1);
+        if state.dispatch(self.limit) {
+            self.granted.notify_all();
+        }
+    }
+}
+
+/// A slot in a [`FairScheduler`], freed when dropped
+#[derive(Debug)]
+pub(crate) struct Permit<'a> {
+    scheduler: &'a FairScheduler,
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        self.scheduler.release();
+    }
+}
+
+impl Client {
+    /// Return a handle that issues its requests as part of the stream named
+    /// `name`.
+    ///
+    /// When a limit on concurrent requests has been set with
+    /// [`ClientBuilder::with_max_concurrent_requests()`][crate::ClientBuilder::with_max_concurrent_requests],
+    /// requests waiting for a free slot are served round-robin between
+    /// streams, so that one task's long pagination cannot starve small
+    /// requests issued by other tasks sharing the same client.  Requests from
+    /// clients not created with this method belong to a stream named
+    /// `"default"`.
+    ///
+    /// The handle shares all other state with the client.
+    pub fn with_stream(&self, name: &str) -> Client {
+        Client {
+            stream: Arc::from(name),
+            ..self.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_robin() {
+        let sched = FairScheduler::new(1);
+        let bulk = Arc::<str>::from("bulk");
+        let small = Arc::<str>::from("small");
+        let mut state = lock(&sched.state);
+        state.active = 1;
+        for (ticket, stream) in [&bulk, &bulk, &bulk, &small].into_iter().enumerate() {
+            let ticket = u64::try_from(ticket).unwrap();
+            let queue = state.queues.entry(Arc::clone(stream)).or_default();
+            queue.push_back(ticket);
+            if queue.len() == 1 {
+                state.order.push_back(Arc::clone(stream));
+            }
+        }
+        let mut served = Vec::new();
+        for _ in 0..4 {
+            state.active -= 1;
+            assert!(state.dispatch(1));
+            let ticket = *state.granted.iter().next().unwrap();
+            state.granted.remove(&ticket);
+            served.push(ticket);
+        }
+        assert_eq!(served, [0, 3, 1, 2]);
+        assert!(!state.dispatch(1));
+    }
+
+    #[test]
+    fn acquire_and_release() {
+        let sched = FairScheduler::new(2);
+        let stream = Arc::<str>::from(DEFAULT_STREAM);
+        let p1 = sched.acquire(&stream);
+        let p2 = sched.acquire(&stream);
+        assert_eq!(lock(&sched.state).active, 2);
+        drop(p1);
+        drop(p2);
+        assert_eq!(lock(&sched.state).active, 0);
+    }
+}
@@ -0,0 +1,430 @@
+use super::datetime::Date;
+use super::{Client, Method, PaginationIter, RequestError};
+use serde::{Deserialize, de::DeserializeOwned};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fmt;
+use std::marker::PhantomData;
+use url::Url;
+
+/// The maximum number of results that the GitHub search API will return for a
+/// single query
+const SEARCH_RESULT_CAP: u64 = 1000;
+
+/// The maximum number of items that can be returned per page by the search
+/// API
+const SEARCH_PER_PAGE: &str = "100";
+
+/// The earliest date used when partitioning a search query by date.  (The
+/// oldest repositories on GitHub date from October 2007.)
+const EARLIEST_DATE: (i64, u32, u32) = (2007, 10, 1);
+
+/// A search result item paired with the text match metadata that GitHub
+/// returns for it when the `text-match` media type is requested, as yielded
+/// by [`Client::paginate_text_matches()`]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct WithTextMatches<T> {
+    /// The item itself, deserialized from all fields other than
+    /// `text_matches`
+    #[serde(flatten)]
+    pub item: T,
+
+    /// The item's text matches
+    #[serde(default)]
+    pub text_matches: Vec<TextMatch>,
+}
+
+/// A fragment of a search result's text containing matches for the search
+/// terms
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct TextMatch {
+    /// The API URL of the object containing the text
+    pub object_url: Option<String>,
+
+    /// The type of the object containing the text (e.g., `"Issue"`)
+    pub object_type: Option<String>,
+
+    /// The name of the field containing the text (e.g., `"body"`)
+    pub property: String,
+
+    /// The fragment of text
+    pub fragment: String,
+
+    /// The matches within the fragment
+    pub matches: Vec<TextMatchSpan>,
+}
+
+/// A single search term match within a [`TextMatch`] fragment
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct TextMatchSpan {
+    /// The matched text
+    pub text: String,
+
+    /// The start & end offsets of the match within the fragment
+    pub indices: (u64, u64),
+}
+
+/// A date qualifier on which [`Client::search_sliced()`] can partition a
+/// search query
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DateField {
+    /// The `created:` qualifier
+    Created,
+
+    /// The `updated:` qualifier
+    Updated,
+}
+
+impl DateField {
+    /// Returns the name of the qualifier, without the trailing colon
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DateField::Created => "created",
+            DateField::Updated => "updated",
+        }
+    }
+}
+
+impl fmt::Display for DateField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+/// A single-day slice of a [`SlicedSearch`] that matched more results than
+/// the search API will return, so that only the first 1000 of its results
+/// were retrieved
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TruncatedSlice {
+    /// The URL of the first page of the slice's search results
+    pub url: Url,
+
+    /// The total number of results that the slice matched
+    pub total_count: u64,
+}
+
+/// An iterator that performs a search query in date-range slices so as to
+/// retrieve more than the 1000 results that the GitHub search API is willing
+/// to return for a single query.
+///
+/// `SlicedSearch` is returned from the [`Client::search_sliced()`] method.
+#[derive(Debug)]
+pub struct SlicedSearch<'a, T> {
+    client: &'a Client,
+
+    /// The URL of the search endpoint, or `None` if it could not be
+    /// constructed
+    endpoint: Option<Url>,
+
+    /// The user's search query, without any date qualifier
+    query: String,
+
+    /// The qualifier on which to partition the query
+    field: DateField,
+
+    /// Inclusive date ranges that have yet to be searched, with the earliest
+    /// range at the end
+    pending: Vec<(Date, Date)>,
+
+    /// The paginated results for the date range currently being searched
+    current: Option<PaginationIter<'a, Value>>,
+
+    /// An item fetched from `current` while checking the total count that has
+    /// not yet been yielded
+    peeked: Option<Value>,
+
+    /// The URL for the first page of the current date range, used when
+    /// reporting errors
+    current_url: Option<Url>,
+
+    /// Keys of the items yielded so far
+    seen: HashSet<String>,
+
+    /// Slices whose results exceeded the cap and could not be split further
+    truncated: Vec<TruncatedSlice>,
+
+    /// An error encountered when constructing the endpoint URL that has yet to
+    /// be yielded
+    error: Option<RequestError>,
+
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<'a, T> SlicedSearch<'a, T> {
+    pub(super) fn new(client: &'a Client, endpoint: &str, query: &str, field: DateField) -> Self {
+        let (endpoint, error) = match client.mkurl(endpoint) {
+            Ok(url) => (Some(url), None),
+            Err(e) => (None, Some(e)),
+        };
+        let (y, m, d) = EARLIEST_DATE;
+        let Some(start) = Date::from_ymd(y, m, d) else {
+            unreachable!("EARLIEST_DATE should be a valid date");
+        };
+        SlicedSearch {
+            client,
+            endpoint,
+            query: query.to_owned(),
+            field,
+            pending: vec![(start, Date::today())],
+            current: None,
+            peeked: None,
+            current_url: None,
+            seen: HashSet::new(),
+            truncated: Vec::new(),
+            error,
+            _item: PhantomData,
+        }
+    }
+
+    /// Returns the single-day slices searched so far whose results exceeded
+    /// the 1000-result cap and could not be split further.  Some results from
+    /// these slices were not retrieved, so if this is nonempty once
+    /// iteration is done, the search was incomplete.
+    pub fn truncated_slices(&self) -> &[TruncatedSlice] {
+        &self.truncated
+    }
+
+    /// Returns `true` if no slice searched so far has exceeded the
+    /// 1000-result cap, i.e., if all of the results have been (or will be)
+    /// yielded
+    pub fn is_complete(&self) -> bool {
+        self.truncated.is_empty()
+    }
+
+    /// Construct the URL for searching the given date range
+    fn slice_url(&self, endpoint: &Url, start: Date, end: Date) -> Url {
+        let mut url = endpoint.clone();
+        url.query_pairs_mut()
+            .append_pair(
+                "q",
+                &format!("{} {}:{start}..{end}", self.query, self.field),
+            )
+            .append_pair("per_page", SEARCH_PER_PAGE);
+        url
+    }
+
+    /// Fetch the next item from the current date range, if any
+    fn next_raw(&mut self) -> Option<Result<Value, RequestError>> {
+        if let Some(v) = self.peeked.take() {
+            return Some(Ok(v));
+        }
+        let r = self.current.as_mut()?.next();
+        if r.is_none() {
+            self.current = None;
+        }
+        r
+    }
+}
+
+impl<T> Iterator for SlicedSearch<'_, T>
+where
+    T: DeserializeOwned,
+{
+    type Item = Result<T, RequestError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.error.take() {
+            return Some(Err(e));
+        }
+        let endpoint = self.endpoint.clone()?;
+        loop {
+            match self.next_raw() {
+                Some(Ok(value)) => {
+                    if !self.seen.insert(item_key(&value)) {
+                        continue;
+                    }
+                    return Some(serde_json::from_value::<T>(value).map_err(|source| {
+                        RequestError::Deserialize {
+                            method: Method::Get,
+                            url: self.current_url.clone().unwrap_or_else(|| endpoint.clone()),
+                            source: Box::new(ureq::Error::Json(source)),
+                        }
+                    }));
+                }
+                Some(Err(e)) => {
+                    self.current = None;
+                    return Some(Err(e));
+                }
+                None => (),
+            }
+            let (start, end) = self.pending.pop()?;
+            let url = self.slice_url(&endpoint, start, end);
+            let mut iter = PaginationIter::from_url(self.client, url.clone());
+            let first = iter.next();
+            let total = iter.total_count().unwrap_or_default();
+            let span = start.days_until(end);
+            if total > SEARCH_RESULT_CAP && span > 0 {
+                log::debug!(
+                    "Search for {}:{start}..{end} returned {total} results; splitting",
+                    self.field
+                );
+                let mid = start.add_days(span / 2);
+                self.pending.push((mid.add_days(1), end));
+                self.pending.push((start, mid));
+                continue;
+            } else if total > SEARCH_RESULT_CAP {
+                log::warn!(
+                    "Search for {}:{start}..{end} returned {total} results, but range cannot be split further; some results will be missed",
+                    self.field
+                );
+                self.truncated.push(TruncatedSlice {
+                    url: url.clone(),
+                    total_count: total,
+                });
+            }
+            self.current_url = Some(url);
+            match first {
+                Some(Ok(value)) => {
+                    self.peeked = Some(value);
+                    self.current = Some(iter);
+                }
+                Some(Err(e)) => return Some(Err(e)),
+                None => (),
+            }
+        }
+    }
+}
+
+/// Returns a key identifying the given search result for the purposes of
+/// deduplication
+fn item_key(value: &Value) -> String {
+    match value.get("url").and_then(Value::as_str) {
+        Some(url) => url.to_owned(),
+        None => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockResponse, MockServer};
+    use serde_json::json;
+
+    #[test]
+    fn slice_url() {
+        let client = Client::new("hunter2").unwrap();
+        let search = SlicedSearch::<Value>::new(
+            &client,
+            "/search/issues",
+            "repo:rust-lang/rust is:pr",
+            DateField::Created,
+        );
+        let endpoint = search.endpoint.clone().unwrap();
+        let url = search.slice_url(
+            &endpoint,
+            Date::from_ymd(2020, 1, 1).unwrap(),
+            Date::from_ymd(2020, 6, 30).unwrap(),
+        );
+        assert_eq!(
+            url.as_str(),
+            "https://api.github.com/search/issues?q=repo%3Arust-lang%2Frust+is%3Apr+created%3A2020-01-01..2020-06-30&per_page=100"
+        );
+    }
+
+    #[test]
+    fn truncated_slice() {
+        // Every range containing 2020-01-01 is over the cap, down to that
+        // single day; all other ranges are empty.
+        let server = MockServer::start(|req| {
+            let url = Url::parse(&format!("http://localhost{}", req.path)).unwrap();
+            let q = url
+                .query_pairs()
+                .find(|(k, _)| k == "q")
+                .map(|(_, v)| v.into_owned())
+                .unwrap();
+            let (_, range) = q.split_once("created:").unwrap();
+            let (start, end) = range.split_once("..").unwrap();
+            let body = if start <= "2020-01-01" && "2020-01-01" <= end {
+                json!({
+                    "total_count": 1500,
+                    "incomplete_results": false,
+                    "items": [{"url": "https://api.github.com/repos/octocat/hello-world/issues/1"}],
+                })
+            } else {
+                json!({"total_count": 0, "incomplete_results": false, "items": []})
+            };
+            MockResponse::json(200, &body)
+        });
+        let client = server.client_builder().build().unwrap();
+        let mut search =
+            client.search_sliced::<Value>("/search/issues", "is:issue", DateField::Created);
+        let items = search.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(items.len(), 1);
+        assert!(!search.is_complete());
+        let truncated = search.truncated_slices();
+        assert_eq!(truncated.len(), 1);
+        assert_eq!(truncated[0].total_count, 1500);
+        assert!(
+            truncated[0]
+                .url
+                .as_str()
+                .contains("created%3A2020-01-01..2020-01-01")
+        );
+    }
+
+    #[test]
+    fn item_key_url() {
+        let value = json!({
+            "id": 1,
+            "url": "https://api.github.com/repos/octocat/Hello-World/issues/1347",
+        });
+        assert_eq!(
+            item_key(&value),
+            "https://api.github.com/repos/octocat/Hello-World/issues/1347"
+        );
+    }
+
+    #[test]
+    fn item_key_no_url() {
+        let value = json!({"name": "foo"});
+        assert_eq!(item_key(&value), r#"{"name":"foo"}"#);
+    }
+
+    #[test]
+    fn deserialize_text_matches() {
+        #[derive(Debug, Deserialize, Eq, PartialEq)]
+        struct Issue {
+            number: u64,
+        }
+
+        let value = json!({
+            "number": 1347,
+            "text_matches": [
+                {
+                    "object_url": "https://api.github.com/repositories/1296269/issues/1347",
+                    "object_type": "Issue",
+                    "property": "body",
+                    "fragment": "windows is broken",
+                    "matches": [{"text": "windows", "indices": [0, 7]}],
+                }
+            ],
+        });
+        let item = serde_json::from_value::<WithTextMatches<Issue>>(value).unwrap();
+        assert_eq!(
+            item,
+            WithTextMatches {
+                item: Issue { number: 1347 },
+                text_matches: vec![TextMatch {
+                    object_url: Some(
+                        "https://api.github.com/repositories/1296269/issues/1347".into()
+                    ),
+                    object_type: Some("Issue".into()),
+                    property: "body".into(),
+                    fragment: "windows is broken".into(),
+                    matches: vec![TextMatchSpan {
+                        text: "windows".into(),
+                        indices: (0, 7),
+                    }],
+                }],
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_no_text_matches() {
+        let value = json!({"name": "foo"});
+        let item = serde_json::from_value::<WithTextMatches<Value>>(value).unwrap();
+        assert_eq!(item.item, json!({"name": "foo"}));
+        assert!(item.text_matches.is_empty());
+    }
+}
@@ -0,0 +1,284 @@
+use super::{Client, Method, RequestError};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+use ureq::http::{
+    header::{HeaderMap, HeaderName},
+    status::StatusCode,
+};
+
+/// The name of the `X-GitHub-Enterprise-Version` header
+const ENTERPRISE_VERSION_HEADER: HeaderName =
+    HeaderName::from_static("x-github-enterprise-version");
+
+/// The REST API version supported by all servers that support API versioning
+/// at all, assumed to be the only supported version when the server does not
+/// report its supported versions
+static BASELINE_API_VERSION: &str = "2022-11-28";
+
+/// Information about the server a client talks to, as returned by
+/// [`Client::server_meta()`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ServerMeta {
+    /// The GitHub Enterprise Server version, as reported by the
+    /// `X-GitHub-Enterprise-Version` header or the `installed_version` field
+    /// of `/meta`, or `None` if the server is not GitHub Enterprise Server
+    pub enterprise_version: Option<ServerVersion>,
+
+    /// The REST API versions supported by the server, as reported by
+    /// `/versions`, or `None` if the server does not report them
+    pub api_versions: Option<Vec<String>>,
+
+    /// The full response body of `/meta`
+    pub meta: Value,
+}
+
+impl ServerMeta {
+    /// Returns `true` if the server is GitHub Enterprise Server
+    pub fn is_enterprise(&self) -> bool {
+        self.enterprise_version.is_some()
+    }
+
+    /// Returns `true` if the server is github.com or a GitHub Enterprise
+    /// Server instance of version `major.minor` or later
+    pub fn enterprise_at_least(&self, major: u32, minor: u32) -> bool {
+        self.enterprise_version
+            .as_ref()
+            .is_none_or(|v| (v.major, v.minor) >= (major, minor))
+    }
+
+    /// Returns `true` if the server supports the given REST API version
+    /// (e.g., `"2022-11-28"`).  If the server does not report its supported
+    /// versions, only `"2022-11-28"` is assumed to be supported.
+    pub fn supports_api_version(&self, version: &str) -> bool {
+        match self.api_versions.as_ref() {
+            Some(versions) => versions.iter().any(|v| v == version),
+            None => version == BASELINE_API_VERSION,
+        }
+    }
+
+    /// Returns `true` if the server supports fine-grained personal access
+    /// tokens, which are available on github.com and on GitHub Enterprise
+    /// Server 3.10 and later
+    pub fn supports_fine_grained_tokens(&self) -> bool {
+        self.enterprise_at_least(3, 10)
+    }
+}
+
+/// A GitHub Enterprise Server version number
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ServerVersion {
+    /// The major version
+    pub major: u32,
+
+    /// The minor version
+    pub minor: u32,
+
+    /// The patch version, or 0 if not given
+    pub patch: u32,
+}
+
+impl fmt::Display for ServerVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for ServerVersion {
+    type Err = ParseServerVersionError;
+
+    /// Parse a version of the form `major.minor` or `major.minor.patch`,
+    /// optionally preceded by `enterprise-server@` and followed by other
+    /// text after a hyphen or plus sign (e.g., `"3.12.4-rc1"`)
+    fn from_str(s: &str) -> Result<ServerVersion, ParseServerVersionError> {
+        let s = s.trim();
+        let s = s.strip_prefix("enterprise-server@").unwrap_or(s);
+        let core = s.split(['-', '+']).next().unwrap_or(s);
+        let mut parts = core.split('.').map(str::parse::<u32>);
+        let (Some(Ok(major)), Some(Ok(minor))) = (parts.next(), parts.next()) else {
+            return Err(ParseServerVersionError);
+        };
+        let patch = match parts.next() {
+            Some(Ok(patch)) => patch,
+            Some(Err(_)) => return Err(ParseServerVersionError),
+            None => 0,
+        };
+        if parts.next().is_some() {
+            return Err(ParseServerVersionError);
+        }
+        Ok(ServerVersion {
+            major,
+            minor,
+            patch,
+        })
+    }
+}
+
+/// Error returned by [`Client::check_api_version()`]
+#[derive(Debug, Error)]
+pub enum ApiVersionError {
+    /// Fetching information about the server failed
+    #[error(transparent)]
+    Request(#[from] RequestError),
+
+    /// The server does not support the client's configured API version
+    #[error(
+        "server does not support REST API version {version}; supported versions: {}; set a supported version with ClientBuilder::with_api_version()",
+        supported.join(", ")
+    )]
+    Unsupported {
+        /// The API version configured on the client
+        version: String,
+
+        /// The API versions that the server supports
+        supported: Vec<String>,
+    },
+}
+
+/// Error returned when parsing an invalid [`ServerVersion`]
+#[derive(Clone, Copy, Debug, Eq, Error, Hash, PartialEq)]
+#[error("invalid GitHub Enterprise Server version")]
+pub struct ParseServerVersionError;
+
+impl Client {
+    /// Fetch information about the server from `/meta` and `/versions` so
+    /// that code can check whether the server supports a feature before
+    /// trying to use it.
+    ///
+    /// If the server does not support `/versions` (as is the case for older
+    /// GitHub Enterprise Server releases), [`ServerMeta::api_versions`] will
+    /// be `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if either request fails (other than with a 404 for
+    /// `/versions`) or a response body cannot be decoded
+    pub fn server_meta(&self) -> Result<ServerMeta, RequestError> {
+        let url = self.mkurl("/meta")?;
+        let r = self.request_buffered::<()>(Method::Get, url.clone(), None, &HeaderMap::new())?;
+        let meta = self.decode_json::<Value>(Method::Get, url, r.body())?;
+        let enterprise_version = r
+            .headers()
+            .get(ENTERPRISE_VERSION_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .or_else(|| meta.get("installed_version").and_then(Value::as_str))
+            .and_then(|s| s.parse::<ServerVersion>().ok());
+        let api_versions = match self.get::<Vec<String>>("/versions") {
+            Ok(versions) => Some(versions),
+            Err(RequestError::Status(e)) if e.status == StatusCode::NOT_FOUND => None,
+            Err(e) => return Err(e),
+        };
+        Ok(ServerMeta {
+            enterprise_version,
+            api_versions,
+            meta,
+        })
+    }
+
+    /// Check that the server supports the REST API version that the client
+    /// sends in the `X-GitHub-Api-Version` header (see
+    /// [`ClientBuilder::with_api_version()`][crate::ClientBuilder::with_api_version]),
+    /// using the information returned by
+    /// [`server_meta()`][Client::server_meta], and return that information.
+    ///
+    /// Servers reject requests for unsupported versions with a 400 response,
+    /// so programs that may talk to older GitHub Enterprise Server instances
+    /// can call this method once at startup in order to fail fast with a
+    /// clear message instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiVersionError::Unsupported`] if the server does not
+    /// support the configured version, or [`ApiVersionError::Request`] if
+    /// [`server_meta()`][Client::server_meta] fails
+    pub fn check_api_version(&self) -> Result<ServerMeta, ApiVersionError> {
+        let meta = self.server_meta()?;
+        if meta.supports_api_version(&self.api_version) {
+            Ok(meta)
+        } else {
+            let supported = meta
+                .api_versions
+                .unwrap_or_else(|| vec![BASELINE_API_VERSION.to_owned()]);
+            log::debug!(
+                "Server does not support API version {}; supported versions: {supported:?}",
+                self.api_version
+            );
+            Err(ApiVersionError::Unsupported {
+                version: self.api_version.to_string(),
+                supported,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("3.12.4", 3, 12, 4)]
+    #[case("3.9", 3, 9, 0)]
+    #[case("enterprise-server@3.14", 3, 14, 0)]
+    #[case("3.13.0-rc1", 3, 13, 0)]
+    fn parse_version(#[case] s: &str, #[case] major: u32, #[case] minor: u32, #[case] patch: u32) {
+        assert_eq!(
+            s.parse::<ServerVersion>(),
+            Ok(ServerVersion {
+                major,
+                minor,
+                patch
+            })
+        );
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("3")]
+    #[case("3.x")]
+    #[case("3.12.4.1")]
+    fn parse_bad_version(#[case] s: &str) {
+        assert_eq!(s.parse::<ServerVersion>(), Err(ParseServerVersionError));
+    }
+
+    #[test]
+    fn capabilities() {
+        let dotcom = ServerMeta {
+            enterprise_version: None,
+            api_versions: Some(vec!["2022-11-28".into(), "2026-03-10".into()]),
+            meta: Value::Null,
+        };
+        assert!(!dotcom.is_enterprise());
+        assert!(dotcom.supports_fine_grained_tokens());
+        assert!(dotcom.supports_api_version("2026-03-10"));
+        assert!(!dotcom.supports_api_version("2099-01-01"));
+
+        let ghes = ServerMeta {
+            enterprise_version: Some(ServerVersion {
+                major: 3,
+                minor: 9,
+                patch: 2,
+            }),
+            api_versions: None,
+            meta: Value::Null,
+        };
+        assert!(ghes.is_enterprise());
+        assert!(!ghes.supports_fine_grained_tokens());
+        assert!(ghes.enterprise_at_least(3, 9));
+        assert!(ghes.supports_api_version("2022-11-28"));
+        assert!(!ghes.supports_api_version("2026-03-10"));
+    }
+
+    #[test]
+    fn unsupported_api_version_message() {
+        let e = ApiVersionError::Unsupported {
+            version: "2026-03-10".into(),
+            supported: vec!["2022-11-28".into()],
+        };
+        assert_eq!(
+            e.to_string(),
+            "server does not support REST API version 2026-03-10; supported versions: 2022-11-28; set a supported version with ClientBuilder::with_api_version()"
+        );
+    }
+}
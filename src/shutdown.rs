@@ -0,0 +1,254 @@
+use super::Client;
+use crate::util::lock;
+use std::io::{self, Read};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use ureq::{Agent, Body, http::Response};
+
+/// Shared state for shutting down a client: whether new requests are
+/// accepted, and how many requests are in progress
+#[derive(Debug, Default)]
+pub(crate) struct ShutdownGate {
+    state: Mutex<GateState>,
+
+    /// Notified whenever a request finishes
+    finished: Condvar,
+
+    /// Notified when the client is shut down
+    closed: Condvar,
+}
+
+/// The state protected by a [`ShutdownGate`]'s mutex
+#[derive(Debug, Default)]
+struct GateState {
+    /// Whether the client has been shut down
+    closed: bool,
+
+    /// The number of requests in progress
+    in_flight: usize,
+}
+
+impl ShutdownGate {
+    /// Register the start of a request, returning a guard that marks the
+    /// request as finished when dropped, or `None` if the client has been
+    /// shut down
+    pub(crate) fn enter(self: &Arc<Self>) -> Option<InFlight> {
+        let mut state = lock(&self.state);
+        if state.closed {
+            None
+        } else {
+            state.in_flight += 1;
+            Some(InFlight(Arc::clone(self)))
+        }
+    }
+
+    /// Register a request in progress even if the client has been shut down.
+    /// This is only for extending a request that is already registered, and
+    /// so it must only be called while another guard for the request is
+    /// held.
+    fn hold(self: &Arc<Self>) -> InFlight {
+        lock(&self.state).in_flight += 1;
+        InFlight(Arc::clone(self))
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        lock(&self.state).closed
+    }
+
+    /// Sleep for `delay` or until the client is shut down, whichever comes
+    /// first.  Returns `true` if the full delay elapsed without the client
+    /// being shut down.
+    pub(crate) fn sleep(&self, delay: Duration) -> bool {
+        let end = Instant::now() + delay;
+        let mut state = lock(&self.state);
+        while !state.closed {
+            let timeout = end.saturating_duration_since(Instant::now());
+            if timeout.is_zero() {
+                return true;
+            }
+            state = self
+                .closed
+                .wait_timeout(state, timeout)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+        false
+    }
+
+    /// Stop accepting requests and block until there are no requests in
+    /// progress or until `deadline` is reached.  Returns `true` if there are
+    /// no requests in progress on return.
+    fn close(&self, deadline: Instant) -> bool {
+        let mut state = lock(&self.state);
+        state.closed = true;
+        self.closed.notify_all();
+        while state.in_flight > 0 {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            if timeout.is_zero() {
+                return false;
+            }
+            state = self
+                .finished
+                .wait_timeout(state, timeout)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+        true
+    }
+}
+
+/// A guard representing a request in progress
+#[derive(Debug)]
+pub(crate) struct InFlight(Arc<ShutdownGate>);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        let mut state = lock(&self.0.state);
+        state.in_flight = state.in_flight.saturating_sub(1);
+        if state.in_flight == 0 {
+            self.0.finished.notify_all();
+        }
+    }
+}
+
+/// A response body reader that keeps its request registered as in progress
+/// until it is dropped
+struct TrackedReader<R> {
+    reader: R,
+    _in_flight: InFlight,
+}
+
+impl<R: Read> Read for TrackedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+impl Client {
+    /// Keep the request that produced `resp` registered as in progress until
+    /// its body is dropped, so that shutting down waits for bodies that are
+    /// still being streamed.  Must be called while the request's own guard
+    /// is held.
+    pub(crate) fn track_body(&self, resp: Response<Body>) -> Response<Body> {
+        let in_flight = self.shutdown.hold();
+        let (parts, body) = resp.into_parts();
+        let mut builder = Body::builder();
+        if let Some(mime_type) = body.mime_type() {
+            builder = builder.mime_type(mime_type);
+        }
+        if let Some(charset) = body.charset() {
+            builder = builder.charset(charset);
+        }
+        let body = builder.reader(TrackedReader {
+            reader: body.into_reader(),
+            _in_flight: in_flight,
+        });
+        Response::from_parts(parts, body)
+    }
+
+    /// Shut down the client (and all of its clones & handles) gracefully:
+    /// new requests immediately fail with
+    /// [`RequestError::ShutDown`][crate::RequestError::ShutDown], while
+    /// requests already in progress, including any retries they go on to
+    /// make, are allowed to finish.  This method blocks until all requests in
+    /// progress have finished or until `timeout` elapses, and it returns
+    /// `true` if all requests finished.
+    ///
+    /// Responses returned with their bodies unread (e.g., by
+    /// [`request()`][Client::request]) count as in progress until their
+    /// bodies are dropped.
+    ///
+    /// This lets services stop cleanly on termination signals instead of
+    /// abandoning mutations partway through their retries.  Once this method
+    /// returns, the client's connection pool is discarded, so that idle
+    /// pooled connections are closed; connections still in use by requests
+    /// in progress are closed once those requests finish.
+    ///
+    /// Shutting down cannot be undone.
+    pub fn shutdown(&self, timeout: Duration) -> bool {
+        log::debug!("Shutting down client");
+        let drained = self.shutdown.close(Instant::now() + timeout);
+        if !drained {
+            log::debug!("Requests still in progress after shutdown timeout");
+        }
+        let mut agent = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        *agent = Agent::new_with_config(agent.config().clone());
+        drained
+    }
+
+    /// Returns `true` if [`shutdown()`][Client::shutdown] has been called on
+    /// the client or any of its clones
+    pub fn is_shut_down(&self) -> bool {
+        self.shutdown.is_closed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockResponse, MockServer};
+    use crate::{Method, RequestError};
+    use std::thread;
+
+    #[test]
+    fn shutdown_waits_for_in_flight() {
+        let gate = Arc::new(ShutdownGate::default());
+        let guard = gate.enter().unwrap();
+        thread::scope(|s| {
+            let closer = s.spawn(|| gate.close(Instant::now() + Duration::from_secs(10)));
+            thread::sleep(Duration::from_millis(50));
+            assert!(gate.is_closed());
+            assert!(gate.enter().is_none());
+            drop(guard);
+            assert!(closer.join().unwrap());
+        });
+    }
+
+    #[test]
+    fn shutdown_times_out() {
+        let gate = Arc::new(ShutdownGate::default());
+        let _guard = gate.enter().unwrap();
+        assert!(!gate.close(Instant::now() + Duration::from_millis(20)));
+    }
+
+    #[test]
+    fn shutdown_interrupts_sleep() {
+        let gate = ShutdownGate::default();
+        assert!(gate.sleep(Duration::from_millis(10)));
+        thread::scope(|s| {
+            let sleeper = s.spawn(|| gate.sleep(Duration::from_secs(60)));
+            thread::sleep(Duration::from_millis(50));
+            assert!(gate.close(Instant::now()));
+            assert!(!sleeper.join().unwrap());
+        });
+    }
+
+    #[test]
+    fn requests_fail_after_shutdown() {
+        let client = Client::builder().build().unwrap();
+        let clone = client.clone();
+        assert!(clone.shutdown(Duration::ZERO));
+        assert!(client.is_shut_down());
+        assert!(matches!(
+            client.get::<serde_json::Value>("/zen"),
+            Err(RequestError::ShutDown { .. })
+        ));
+        assert!(matches!(client.ping(), Err(RequestError::ShutDown { .. })));
+        assert!(matches!(
+            client.warm_up(),
+            Err(RequestError::ShutDown { .. })
+        ));
+    }
+
+    #[test]
+    fn shutdown_waits_for_unread_body() {
+        let server = MockServer::start(|_| MockResponse::new(200, "Hello, world!"));
+        let client = server.client_builder().build().unwrap();
+        let url = client.resolve("/zen").unwrap();
+        let mut resp = client.request::<()>(Method::Get, url, None).unwrap();
+        assert!(!client.shutdown(Duration::from_millis(20)));
+        assert_eq!(resp.body_mut().read_to_string().unwrap(), "Hello, world!");
+        drop(resp);
+        assert!(client.shutdown(Duration::ZERO));
+    }
+}
@@ -0,0 +1,114 @@
+use super::Method;
+use crate::dump::is_redacted;
+use std::fmt;
+use ureq::http::header::{HeaderMap, HeaderName, HeaderValue};
+use url::Url;
+
+/// A record of a request, attached to [`RequestError`][crate::RequestError]s
+/// so that failure reports contain everything needed to reproduce the
+/// request.
+///
+/// The values of headers that may contain credentials, such as
+/// `Authorization`, are replaced with `<redacted>`, and the payload itself is
+/// not recorded, only its size.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RequestSnapshot {
+    /// The HTTP method of the request
+    pub method: Method,
+
+    /// The URL to which the request was last sent (after any failover to a
+    /// fallback base API URL)
+    pub url: Url,
+
+    /// The `Accept` and `X-GitHub-Api-Version` headers sent by the client,
+    /// along with any additional headers supplied by the caller, with
+    /// credentials redacted
+    pub headers: HeaderMap,
+
+    /// The length in bytes of the request body, if any
+    pub payload_len: Option<usize>,
+}
+
+impl RequestSnapshot {
+    /// Create a snapshot of a request with the given client headers & caller
+    /// headers, the latter overriding the former.  `auth_header` is the
+    /// header in which the client sends its token.
+    pub(crate) fn new(
+        method: Method,
+        url: Url,
+        client_headers: &HeaderMap,
+        headers: &HeaderMap,
+        auth_header: &HeaderName,
+        payload_len: Option<usize>,
+    ) -> RequestSnapshot {
+        let mut snapshot_headers = client_headers.clone();
+        for name in headers.keys() {
+            snapshot_headers.remove(name);
+        }
+        for (name, value) in headers {
+            let value = if is_redacted(name, auth_header) {
+                HeaderValue::from_static("<redacted>")
+            } else {
+                value.clone()
+            };
+            snapshot_headers.append(name.clone(), value);
+        }
+        RequestSnapshot {
+            method,
+            url,
+            headers: snapshot_headers,
+            payload_len,
+        }
+    }
+}
+
+impl fmt::Display for RequestSnapshot {
+    /// Format the snapshot as a request line, one line per header, and the
+    /// payload size, if any
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.method, self.url)?;
+        for (name, value) in &self.headers {
+            write!(f, "\n{name}: {}", String::from_utf8_lossy(value.as_bytes()))?;
+        }
+        if let Some(len) = self.payload_len {
+            write!(f, "\n[payload: {len} bytes]")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ureq::http::header::{ACCEPT, AUTHORIZATION};
+
+    #[test]
+    fn snapshot_redacts_and_overrides() {
+        let mut client_headers = HeaderMap::new();
+        client_headers.insert(
+            ACCEPT,
+            HeaderValue::from_static("application/vnd.github+json"),
+        );
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            ACCEPT,
+            HeaderValue::from_static("application/vnd.github.raw"),
+        );
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer hunter2"));
+        let snapshot = RequestSnapshot::new(
+            Method::Post,
+            Url::parse("https://api.github.com/markdown").unwrap(),
+            &client_headers,
+            &headers,
+            &AUTHORIZATION,
+            Some(42),
+        );
+        assert_eq!(
+            snapshot.to_string(),
+            "POST https://api.github.com/markdown\n\
+             accept: application/vnd.github.raw\n\
+             authorization: <redacted>\n\
+             [payload: 42 bytes]"
+        );
+    }
+}
@@ -0,0 +1,366 @@
+use super::{Client, PaginationIter, RequestError};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use std::fmt::Write;
+use thiserror::Error;
+
+/// Fill in the `{name}` placeholders in the URL path template `template`
+/// (e.g., `"/repos/{owner}/{repo}/issues/{number}"`) with the corresponding
+/// fields of `params`, which may be any value that serializes to a map, such
+/// as a `HashMap`, a struct, or a `serde_json::json!` object.
+///
+/// Each field must be a string, number, or boolean.  Substituted values are
+/// percent-encoded as single path segments, so characters like `/`, `?`, and
+/// `#` in a value cannot change the structure of the path.  Values that are
+/// empty or are `.` or `..` are rejected, as they would likewise change the
+/// structure of the path once the URL is normalized.
+///
+/// # Errors
+///
+/// Returns `Err` if `params` does not serialize to a map, if a placeholder
+/// has no corresponding field or its field is not a scalar, if a field is
+/// empty or is `.` or `..`, or if `template` contains an unterminated
+/// placeholder
+pub fn expand_path<P: Serialize + ?Sized>(
+    template: &str,
+    params: &P,
+) -> Result<String, PathTemplateError> {
+    let Ok(Value::Object(params)) = serde_json::to_value(params) else {
+        return Err(PathTemplateError::NotAMap);
+    };
+    let mut path = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(i) = rest.find('{') {
+        path.push_str(&rest[..i]);
+        let Some((name, after)) = rest[(i + 1)..].split_once('}') else {
+            return Err(PathTemplateError::Unterminated);
+        };
+        let value = match params.get(name) {
+            Some(Value::String(s)) => s.clone(),
+            Some(v @ (Value::Number(_) | Value::Bool(_))) => v.to_string(),
+            Some(_) => return Err(PathTemplateError::NotScalar(name.to_owned())),
+            None => return Err(PathTemplateError::Missing(name.to_owned())),
+        };
+        if !is_valid_segment(&value) {
+            return Err(PathTemplateError::InvalidSegment(name.to_owned()));
+        }
+        encode_segment(&mut path, &value);
+        rest = after;
+    }
+    path.push_str(rest);
+    Ok(path)
+}
+
+/// Returns `true` if `value` can be used as a single path segment, i.e., if it
+/// is nonempty and is not `.` or `..`.  (Percent-encoding the dots does not
+/// help, as URL parsers treat `%2E` in such segments the same as `.`.)
+pub(crate) fn is_valid_segment(value: &str) -> bool {
+    !matches!(value, "" | "." | "..")
+}
+
+/// Append `value` to `out`, percent-encoding every byte other than
+/// unreserved characters
+pub(crate) fn encode_segment(out: &mut String, value: &str) {
+    for b in value.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~') {
+            out.push(char::from(b));
+        } else {
+            let _ = write!(out, "%{b:02X}");
+        }
+    }
+}
+
+/// Error returned by [`expand_path()`]
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum PathTemplateError {
+    /// The parameters did not serialize to a map
+    #[error("path template parameters must serialize to a map")]
+    NotAMap,
+
+    /// No parameter was supplied for a placeholder
+    #[error("no value supplied for path placeholder {{{0}}}")]
+    Missing(String),
+
+    /// The parameter for a placeholder was not a string, number, or boolean
+    #[error("value for path placeholder {{{0}}} is not a string, number, or boolean")]
+    NotScalar(String),
+
+    /// The value for a placeholder was empty or was `.` or `..`, any of which
+    /// would change the structure of the path
+    #[error("value for path placeholder {{{0}}} is empty or is a dot segment")]
+    InvalidSegment(String),
+
+    /// The template contained a `{` without a matching `}`
+    #[error("unterminated placeholder in path template")]
+    Unterminated,
+}
+
+impl Client {
+    /// Like [`get()`][Client::get], but the path is constructed by filling
+    /// in the placeholders in `template` with the fields of `params` as
+    /// described in [`expand_path()`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use minigh::Client;
+    /// # use std::collections::HashMap;
+    /// # let client = Client::new("token").unwrap();
+    /// let params = HashMap::from([("owner", "octocat"), ("repo", "hello-world")]);
+    /// let repo = client.get_fmt::<serde_json::Value, _>("/repos/{owner}/{repo}", &params)?;
+    /// # Ok::<(), minigh::RequestError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RequestError::PathTemplate`] error if the path cannot be
+    /// constructed; see [`get()`][Client::get] for other errors.
+    pub fn get_fmt<T, P>(&self, template: &str, params: &P) -> Result<T, RequestError>
+    where
+        T: DeserializeOwned,
+        P: Serialize + ?Sized,
+    {
+        self.get(&fill(template, params)?)
+    }
+
+    /// Like [`post()`][Client::post], but the path is constructed by filling
+    /// in the placeholders in `template` with the fields of `params` as
+    /// described in [`expand_path()`]
+    pub fn post_fmt<T, U, P>(
+        &self,
+        template: &str,
+        params: &P,
+        payload: &T,
+    ) -> Result<U, RequestError>
+    where
+        T: Serialize,
+        U: DeserializeOwned,
+        P: Serialize + ?Sized,
+    {
+        self.post(&fill(template, params)?, payload)
+    }
+
+    /// Like [`put()`][Client::put], but the path is constructed by filling
+    /// in the placeholders in `template` with the fields of `params` as
+    /// described in [`expand_path()`]
+    pub fn put_fmt<T, U, P>(
+        &self,
+        template: &str,
+        params: &P,
+        payload: &T,
+    ) -> Result<U, RequestError>
+    where
+        T: Serialize,
+        U: DeserializeOwned,
+        P: Serialize + ?Sized,
+    {
+        self.put(&fill(template, params)?, payload)
+    }
+
+    /// Like [`patch()`][Client::patch], but the path is constructed by
+    /// filling in the placeholders in `template` with the fields of `params`
+    /// as described in [`expand_path()`]
+    pub fn patch_fmt<T, U, P>(
+        &self,
+        template: &str,
+        params: &P,
+        payload: &T,
+    ) -> Result<U, RequestError>
+    where
+        T: Serialize,
+        U: DeserializeOwned,
+        P: Serialize + ?Sized,
+    {
+        self.patch(&fill(template, params)?, payload)
+    }
+
+    /// Like [`delete()`][Client::delete], but the path is constructed by
+    /// filling in the placeholders in `template` with the fields of `params`
+    /// as described in [`expand_path()`]
+    pub fn delete_fmt<P>(&self, template: &str, params: &P) -> Result<(), RequestError>
+    where
+        P: Serialize + ?Sized,
+    {
+        self.delete(&fill(template, params)?)
+    }
+
+    /// Like [`paginate()`][Client::paginate], but the path is constructed by
+    /// filling in the placeholders in `template` with the fields of `params`
+    /// as described in [`expand_path()`]
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RequestError::PathTemplate`] error if the path cannot be
+    /// constructed
+    pub fn paginate_fmt<T, P>(
+        &self,
+        template: &str,
+        params: &P,
+    ) -> Result<PaginationIter<'_, T>, RequestError>
+    where
+        T: DeserializeOwned,
+        P: Serialize + ?Sized,
+    {
+        Ok(self.paginate(&fill(template, params)?))
+    }
+}
+
+/// Call [`expand_path()`] and convert any error to a [`RequestError`]
+pub(crate) fn fill<P: Serialize + ?Sized>(
+    template: &str,
+    params: &P,
+) -> Result<String, RequestError> {
+    expand_path(template, params).map_err(|source| RequestError::PathTemplate {
+        template: template.to_owned(),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockResponse, MockServer};
+    use rstest::rstest;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[test]
+    fn expand_map() {
+        let params = HashMap::from([("owner", "octocat"), ("repo", "hello-world")]);
+        assert_eq!(
+            expand_path("/repos/{owner}/{repo}/issues", &params).unwrap(),
+            "/repos/octocat/hello-world/issues"
+        );
+    }
+
+    #[test]
+    fn expand_struct() {
+        #[derive(Serialize)]
+        struct Params {
+            owner: &'static str,
+            repo: &'static str,
+            number: u64,
+        }
+
+        let params = Params {
+            owner: "octocat",
+            repo: "hello-world",
+            number: 42,
+        };
+        assert_eq!(
+            expand_path("/repos/{owner}/{repo}/issues/{number}", &params).unwrap(),
+            "/repos/octocat/hello-world/issues/42"
+        );
+    }
+
+    #[test]
+    fn expand_encodes() {
+        let params = json!({"ref": "heads/feature?x#y", "name": "café"});
+        assert_eq!(
+            expand_path("/git/ref/{ref}/{name}", &params).unwrap(),
+            "/git/ref/heads%2Ffeature%3Fx%23y/caf%C3%A9"
+        );
+    }
+
+    #[rstest]
+    #[case("..")]
+    #[case(".")]
+    #[case("")]
+    fn expand_rejects_invalid_segments(#[case] value: &str) {
+        let params = json!({"owner": value, "repo": "hello-world"});
+        assert_eq!(
+            expand_path("/repos/{owner}/{repo}/issues", &params),
+            Err(PathTemplateError::InvalidSegment("owner".into()))
+        );
+        let params = json!({"owner": "octocat", "repo": value});
+        assert_eq!(
+            expand_path("/repos/{owner}/{repo}/issues", &params),
+            Err(PathTemplateError::InvalidSegment("repo".into()))
+        );
+    }
+
+    #[test]
+    fn expand_allows_dots_within_segments() {
+        let params = json!({"owner": "...", "repo": ".github"});
+        assert_eq!(
+            expand_path("/repos/{owner}/{repo}", &params).unwrap(),
+            "/repos/.../.github"
+        );
+    }
+
+    #[test]
+    fn expand_errors() {
+        let params = json!({"owner": "octocat", "labels": ["bug"]});
+        assert_eq!(
+            expand_path("/repos/{owner}/{repo}", &params),
+            Err(PathTemplateError::Missing("repo".into()))
+        );
+        assert_eq!(
+            expand_path("/labels/{labels}", &params),
+            Err(PathTemplateError::NotScalar("labels".into()))
+        );
+        assert_eq!(
+            expand_path("/repos/{owner", &params),
+            Err(PathTemplateError::Unterminated)
+        );
+        assert_eq!(
+            expand_path("/repos/{owner}", &["octocat"]),
+            Err(PathTemplateError::NotAMap)
+        );
+    }
+
+    #[test]
+    fn fmt_methods() {
+        let server = MockServer::start(|_| MockResponse::json(200, &json!({"ok": true})));
+        let client = server.client_builder().build().unwrap();
+        let params = json!({"owner": "octocat", "repo": "hello-world", "name": "a/b"});
+        let payload = json!({"x": 1});
+        let _: Value = client.get_fmt("/repos/{owner}/{repo}", &params).unwrap();
+        let _: Value = client
+            .post_fmt("/repos/{owner}/{repo}/labels", &params, &payload)
+            .unwrap();
+        let _: Value = client
+            .put_fmt("/repos/{owner}/{repo}/topics", &params, &payload)
+            .unwrap();
+        let _: Value = client
+            .patch_fmt("/repos/{owner}/{repo}/labels/{name}", &params, &payload)
+            .unwrap();
+        client
+            .delete_fmt("/repos/{owner}/{repo}/labels/{name}", &params)
+            .unwrap();
+        let reqs = server
+            .requests()
+            .into_iter()
+            .map(|r| (r.method, r.path))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            reqs,
+            [
+                ("GET".into(), "/repos/octocat/hello-world".into()),
+                ("POST".into(), "/repos/octocat/hello-world/labels".into()),
+                ("PUT".into(), "/repos/octocat/hello-world/topics".into()),
+                (
+                    "PATCH".into(),
+                    "/repos/octocat/hello-world/labels/a%2Fb".into()
+                ),
+                (
+                    "DELETE".into(),
+                    "/repos/octocat/hello-world/labels/a%2Fb".into()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn fmt_path_error() {
+        let client = Client::builder().build().unwrap();
+        let r = client.delete_fmt("/repos/{owner}/{repo}", &json!({"owner": "octocat"}));
+        assert!(matches!(
+            r,
+            Err(RequestError::PathTemplate {
+                source: PathTemplateError::Missing(ref name),
+                ..
+            }) if name == "repo"
+        ));
+    }
+}
@@ -0,0 +1,170 @@
+//! A minimal plain-HTTP server for testing the client's request handling
+use super::ClientBuilder;
+use crate::util::lock;
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use url::Url;
+
+/// A request received by a [`MockServer`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct MockRequest {
+    pub(crate) method: String,
+
+    /// The request target, i.e., the path plus any query string
+    pub(crate) path: String,
+
+    /// The request's headers, with lowercased names
+    pub(crate) headers: Vec<(String, String)>,
+
+    pub(crate) body: Vec<u8>,
+}
+
+impl MockRequest {
+    /// Returns the value of the header with the given lowercase name
+    pub(crate) fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// A response to be sent by a [`MockServer`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct MockResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    delay: Duration,
+}
+
+impl MockResponse {
+    pub(crate) fn new<B: Into<Vec<u8>>>(status: u16, body: B) -> MockResponse {
+        MockResponse {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    pub(crate) fn json(status: u16, body: &serde_json::Value) -> MockResponse {
+        MockResponse::new(status, body.to_string()).with_header("Content-Type", "application/json")
+    }
+
+    pub(crate) fn with_header(mut self, name: &str, value: &str) -> MockResponse {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// Wait for `delay` before sending the response
+    pub(crate) fn with_delay(mut self, delay: Duration) -> MockResponse {
+        self.delay = delay;
+        self
+    }
+}
+
+type Handler = dyn Fn(&MockRequest) -> MockResponse + Send + Sync;
+
+/// A local HTTP server that answers each request by calling a handler and
+/// records the requests it receives.  Each connection serves one request.
+#[derive(Debug)]
+pub(crate) struct MockServer {
+    url: Url,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+}
+
+impl MockServer {
+    pub(crate) fn start<F>(handler: F) -> MockServer
+    where
+        F: Fn(&MockRequest) -> MockResponse + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+        let reqs = Arc::clone(&requests);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let handler = Arc::clone(&handler);
+                let reqs = Arc::clone(&reqs);
+                thread::spawn(move || serve(stream, &*handler, &reqs));
+            }
+        });
+        MockServer {
+            url: Url::parse(&format!("http://{addr}")).unwrap(),
+            requests,
+        }
+    }
+
+    /// Returns a `ClientBuilder` with the server as its base API URL
+    pub(crate) fn client_builder(&self) -> ClientBuilder {
+        ClientBuilder::new()
+            .with_token("hunter2")
+            .with_api_url(self.url.clone())
+            .allow_http()
+    }
+
+    /// Returns the requests received so far
+    pub(crate) fn requests(&self) -> Vec<MockRequest> {
+        lock(&self.requests).clone()
+    }
+}
+
+fn serve(stream: TcpStream, handler: &Handler, requests: &Mutex<Vec<MockRequest>>) {
+    let mut reader = BufReader::new(&stream);
+    let mut line = String::new();
+    if reader.read_line(&mut line).is_err() {
+        return;
+    }
+    let mut words = line.split_whitespace();
+    let (Some(method), Some(path)) = (words.next(), words.next()) else {
+        return;
+    };
+    let (method, path) = (method.to_owned(), path.to_owned());
+    let mut headers = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).is_err() {
+            return;
+        }
+        let Some((name, value)) = line.trim_end().split_once(':') else {
+            break;
+        };
+        headers.push((name.trim().to_ascii_lowercase(), value.trim().to_owned()));
+    }
+    let length = headers
+        .iter()
+        .find(|(k, _)| k == "content-length")
+        .and_then(|(_, v)| v.parse::<usize>().ok())
+        .unwrap_or_default();
+    let mut body = vec![0; length];
+    if reader.read_exact(&mut body).is_err() {
+        return;
+    }
+    let req = MockRequest {
+        method,
+        path,
+        headers,
+        body,
+    };
+    let resp = handler(&req);
+    lock(requests).push(req);
+    thread::sleep(resp.delay);
+    let mut out = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n",
+        resp.status,
+        resp.body.len()
+    );
+    for (name, value) in &resp.headers {
+        let _ = write!(out, "{name}: {value}\r\n");
+    }
+    out.push_str("\r\n");
+    let mut stream = &stream;
+    let _ = stream.write_all(out.as_bytes());
+    let _ = stream.write_all(&resp.body);
+}
@@ -0,0 +1,199 @@
+use super::{Client, Method, RequestError};
+use serde::{Serialize, de::DeserializeOwned};
+use ureq::http::{
+    header::{ETAG, HeaderMap, HeaderValue, IF_MATCH},
+    status::StatusCode,
+};
+
+impl Client {
+    /// Perform a compare-and-swap update of the resource at `path`: fetch it
+    /// with a GET request, pass the decoded value to `edit` to produce a
+    /// payload, and send the payload with a `method` (typically PUT or PATCH)
+    /// request carrying an `If-Match` header with the `ETag` of the fetched
+    /// resource.  If the server responds with 412 (Precondition Failed)
+    /// because the resource changed in the meantime, the whole cycle is
+    /// repeated, up to `max_attempts` times in total.
+    ///
+    /// `path` may be either a complete URL or a URL path to append to the
+    /// base GitHub API URL (e.g., `"/repos/octocat/hello-world"`).  `edit` may
+    /// be called more than once and should not have side effects.  If the GET
+    /// response has no `ETag` header, the update is sent unconditionally.  As
+    /// `If-Match` only accepts strong validators, a weak `ETag` (one starting
+    /// with `W/`, as GitHub returns for many resources) is sent with the `W/`
+    /// prefix removed.
+    ///
+    /// Deserializes the response body of the update as `U` and returns the
+    /// result.
+    ///
+    /// The GET requests made by this method bypass the deduplication of
+    /// concurrent GET requests described for [`get()`][Client::get].  See
+    /// [`request()`][Client::request] for information on lower-level
+    /// behavior.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any request fails.  If the server responds with
+    /// 412 on the last attempt, that error is returned.
+    pub fn read_modify_write<T, P, U, F>(
+        &self,
+        path: &str,
+        method: Method,
+        max_attempts: u32,
+        mut edit: F,
+    ) -> Result<U, RequestError>
+    where
+        T: DeserializeOwned,
+        P: Serialize,
+        U: DeserializeOwned,
+        F: FnMut(T) -> P,
+    {
+        let url = self.mkurl(path)?;
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let resp =
+                self.request_buffered::<()>(Method::Get, url.clone(), None, &HeaderMap::new())?;
+            let current = self.decode_json::<T>(Method::Get, url.clone(), resp.body())?;
+            let mut headers = HeaderMap::new();
+            if let Some(etag) = resp.headers().get(ETAG) {
+                headers.insert(IF_MATCH, strong_etag(etag));
+            }
+            let payload = edit(current);
+            match self.request_buffered(method, url.clone(), Some(&payload), &headers) {
+                Ok(r) => return self.decode_json(method, url, r.body()),
+                Err(RequestError::Status(e))
+                    if e.status == StatusCode::PRECONDITION_FAILED && attempts < max_attempts =>
+                {
+                    log::debug!(
+                        "Resource at {url} changed before {method} request could be applied; retrying read-modify-write cycle"
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Remove the `W/` prefix from a weak `ETag` value so that it can be used in
+/// an `If-Match` header
+fn strong_etag(etag: &HeaderValue) -> HeaderValue {
+    match etag.as_bytes().strip_prefix(b"W/") {
+        Some(tag) => {
+            let Ok(value) = HeaderValue::from_bytes(tag) else {
+                unreachable!("A substring of a valid header value should be valid");
+            };
+            value
+        }
+        None => etag.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockResponse, MockServer};
+    use serde_json::{Value, json};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Start a server holding a resource with a `count` field whose first
+    /// `conflicts` updates fail with 412
+    fn conflicting_server(conflicts: u32) -> MockServer {
+        let updates = AtomicU32::new(0);
+        MockServer::start(move |req| {
+            if req.method == "GET" {
+                MockResponse::json(200, &json!({"count": 41})).with_header("ETag", r#"W/"abc123""#)
+            } else if updates.fetch_add(1, Ordering::SeqCst) < conflicts {
+                MockResponse::json(412, &json!({"message": "Precondition Failed"}))
+            } else {
+                MockResponse::new(200, req.body.clone())
+                    .with_header("Content-Type", "application/json")
+            }
+        })
+    }
+
+    fn increment(v: Value) -> Value {
+        json!({"count": v["count"].as_u64().unwrap() + 1})
+    }
+
+    #[test]
+    fn strip_weak_etag() {
+        assert_eq!(
+            strong_etag(&HeaderValue::from_static(r#"W/"abc123""#)),
+            r#""abc123""#
+        );
+        assert_eq!(
+            strong_etag(&HeaderValue::from_static(r#""abc123""#)),
+            r#""abc123""#
+        );
+    }
+
+    #[test]
+    fn success() {
+        let server = conflicting_server(0);
+        let client = server.client_builder().build().unwrap();
+        let r = client
+            .read_modify_write::<Value, Value, Value, _>(
+                "/repos/octocat/hello-world",
+                Method::Patch,
+                3,
+                increment,
+            )
+            .unwrap();
+        assert_eq!(r, json!({"count": 42}));
+        let reqs = server.requests();
+        assert_eq!(reqs.len(), 2);
+        assert_eq!(reqs[0].method, "GET");
+        assert_eq!(reqs[1].method, "PATCH");
+        assert_eq!(reqs[1].header("if-match"), Some(r#""abc123""#));
+    }
+
+    #[test]
+    fn conflict_then_success() {
+        let server = conflicting_server(1);
+        let client = server.client_builder().build().unwrap();
+        let r = client
+            .read_modify_write::<Value, Value, Value, _>(
+                "/repos/octocat/hello-world",
+                Method::Patch,
+                3,
+                increment,
+            )
+            .unwrap();
+        assert_eq!(r, json!({"count": 42}));
+        let methods = server
+            .requests()
+            .into_iter()
+            .map(|r| r.method)
+            .collect::<Vec<_>>();
+        assert_eq!(methods, ["GET", "PATCH", "GET", "PATCH"]);
+    }
+
+    #[test]
+    fn conflicts_exhaust_attempts() {
+        let server = conflicting_server(u32::MAX);
+        let client = server.client_builder().build().unwrap();
+        let r = client.read_modify_write::<Value, Value, Value, _>(
+            "/repos/octocat/hello-world",
+            Method::Patch,
+            2,
+            increment,
+        );
+        assert!(matches!(
+            r,
+            Err(RequestError::Status(ref e)) if e.status == StatusCode::PRECONDITION_FAILED
+        ));
+        assert_eq!(server.requests().len(), 4);
+    }
+
+    #[test]
+    fn bad_path() {
+        let client = Client::builder().build().unwrap();
+        let r = client.read_modify_write::<Value, Value, Value, _>(
+            "http://[::1",
+            Method::Patch,
+            3,
+            |v| v,
+        );
+        assert!(matches!(r, Err(RequestError::Path { .. })));
+    }
+}
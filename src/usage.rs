@@ -0,0 +1,61 @@
+use std::fmt;
+use std::time::Duration;
+
+/// A summary of the requests made by a [`Client`][crate::Client] since it was
+/// created, as returned by
+/// [`Client::usage_report()`][crate::Client::usage_report]
+///
+/// The `Display` implementation produces a one-line summary suitable for
+/// printing at the end of a program run.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct UsageReport {
+    /// The total number of HTTP requests sent, including retries
+    pub requests: u64,
+
+    /// The number of requests that were retries of earlier failed requests
+    pub retries: u64,
+
+    /// The total amount of time spent sleeping in response to rate-limit
+    /// errors
+    pub rate_limit_wait: Duration,
+
+    /// The total amount of time spent sleeping between mutating requests
+    pub mutation_wait: Duration,
+
+    /// The amount of time elapsed since the client was created
+    pub elapsed: Duration,
+}
+
+impl fmt::Display for UsageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} requests ({} retries) in {:.1}s; {:.1}s waiting on rate limits, {:.1}s pacing mutations",
+            self.requests,
+            self.retries,
+            self.elapsed.as_secs_f64(),
+            self.rate_limit_wait.as_secs_f64(),
+            self.mutation_wait.as_secs_f64(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        let report = UsageReport {
+            requests: 120,
+            retries: 3,
+            rate_limit_wait: Duration::from_millis(61500),
+            mutation_wait: Duration::from_millis(2300),
+            elapsed: Duration::from_secs(300),
+        };
+        assert_eq!(
+            report.to_string(),
+            "120 requests (3 retries) in 300.0s; 61.5s waiting on rate limits, 2.3s pacing mutations"
+        );
+    }
+}
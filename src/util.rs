@@ -1,10 +1,20 @@
-use super::{Method, RequestError, StatusError};
+use super::{
+    Method, RateLimitMode, RequestError, RequestSnapshot, RetryReason, StatusError,
+    TransportErrorKind,
+};
+use crate::bucket::RetryBudget;
 use mime::{JSON, Mime};
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher, RandomState};
+use std::io::ErrorKind;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use ureq::{
-    Body,
+    Body, Timeout,
     http::{
-        header::{CONTENT_TYPE, HeaderName, LINK, RETRY_AFTER},
+        Extensions,
+        header::{CONTENT_TYPE, HeaderMap, HeaderName, LINK, RETRY_AFTER},
         response::{Parts, Response},
         status::StatusCode,
     },
@@ -14,6 +24,9 @@ use url::Url;
 /// Maximum number of times to retry a request
 const RETRIES: i32 = 10;
 
+/// Maximum number of attempts made for a request
+pub(super) const MAX_ATTEMPTS: u32 = RETRIES.unsigned_abs() + 1;
+
 /// Multiplier for exponential backoff delays
 const BACKOFF_FACTOR: f64 = 1.0;
 
@@ -26,11 +39,23 @@ const BACKOFF_MAX: f64 = 120.0;
 /// Maximum amount of time to spend retrying a request
 const TOTAL_WAIT: Duration = Duration::from_secs(300);
 
+/// Default amount of time to wait before retrying after a legacy "abuse
+/// detection mechanism" error
+pub(super) const ABUSE_COOLDOWN: Duration = Duration::from_secs(120);
+
+/// Maximum size in bytes of a JSON error body that will be pretty-printed;
+/// larger bodies are kept as-is to avoid the cost of re-serializing them
+const PRETTY_PRINT_LIMIT: usize = 64 * 1024;
+
+/// The name of the `X-GitHub-Request-Id` header
+pub(super) const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-github-request-id");
+
 /// The name of the `X-Ratelimit-Remaining` header
-const RATELIMIT_REMAINING_HEADER: HeaderName = HeaderName::from_static("x-ratelimit-remaining");
+pub(super) const RATELIMIT_REMAINING_HEADER: HeaderName =
+    HeaderName::from_static("x-ratelimit-remaining");
 
 /// The name of the `X-Ratelimit-Reset` header
-const RATELIMIT_RESET_HEADER: HeaderName = HeaderName::from_static("x-ratelimit-reset");
+pub(super) const RATELIMIT_RESET_HEADER: HeaderName = HeaderName::from_static("x-ratelimit-reset");
 
 /// A struct for determining retries for a single request
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -46,6 +71,30 @@ pub(super) struct Retrier {
 
     /// Timestamp beyond which we should stop retrying
     stop_time: Instant,
+
+    /// The maximum amount of time to wait in response to a rate-limit error
+    /// that specifies when to retry
+    max_wait: Option<Duration>,
+
+    /// Whether to assume that the server enforces rate limits
+    rate_limit_mode: RateLimitMode,
+
+    /// A snapshot of the request to attach to errors
+    request: Option<Box<RequestSnapshot>>,
+
+    /// Non-2xx status codes to treat as successful responses
+    expected_statuses: Arc<[StatusCode]>,
+
+    /// The client-wide budget from which retries after server & transport
+    /// errors are taken, if any
+    retry_budget: Option<RetryBudget>,
+
+    /// The value for [`StatusError::display_body_limit`] in errors
+    display_body_limit: Option<usize>,
+
+    /// How long to wait before retrying after a legacy "abuse detection
+    /// mechanism" error
+    abuse_cooldown: Duration,
 }
 
 impl Retrier {
@@ -55,6 +104,107 @@ impl Retrier {
             url,
             attempts: 0,
             stop_time: Instant::now() + TOTAL_WAIT,
+            max_wait: None,
+            rate_limit_mode: RateLimitMode::default(),
+            request: None,
+            expected_statuses: Arc::default(),
+            retry_budget: None,
+            display_body_limit: None,
+            abuse_cooldown: ABUSE_COOLDOWN,
+        }
+    }
+
+    /// Set the snapshot of the request to attach to errors
+    pub(super) fn with_snapshot(mut self, snapshot: RequestSnapshot) -> Retrier {
+        self.request = Some(Box::new(snapshot));
+        self
+    }
+
+    /// Set the non-2xx status codes to return as successful responses rather
+    /// than as errors or retries
+    pub(super) fn with_expected_statuses(mut self, statuses: Arc<[StatusCode]>) -> Retrier {
+        self.expected_statuses = statuses;
+        self
+    }
+
+    /// Set the client-wide budget from which retries after server & transport
+    /// errors are taken
+    pub(super) fn with_retry_budget(mut self, budget: Option<RetryBudget>) -> Retrier {
+        self.retry_budget = budget;
+        self
+    }
+
+    /// Set the maximum number of bytes of the response body to show when
+    /// displaying status errors with `{}`
+    pub(super) fn with_display_body_limit(mut self, limit: Option<usize>) -> Retrier {
+        self.display_body_limit = limit;
+        self
+    }
+
+    /// Returns `true` if a retry after a server or transport error is
+    /// permitted by the retry budget, spending a retry from the budget if so
+    fn take_retry_budget(&self) -> bool {
+        let ok = self.retry_budget.as_ref().is_none_or(RetryBudget::take);
+        if !ok {
+            log::debug!("Client retry budget exhausted; not retrying");
+        }
+        ok
+    }
+
+    /// Set how long to wait before retrying after a legacy "abuse detection
+    /// mechanism" error
+    pub(super) fn with_abuse_cooldown(mut self, cooldown: Duration) -> Retrier {
+        self.abuse_cooldown = cooldown;
+        self
+    }
+
+    /// Set whether to assume that the server enforces rate limits
+    pub(super) fn with_rate_limit_mode(mut self, mode: RateLimitMode) -> Retrier {
+        self.rate_limit_mode = mode;
+        self
+    }
+
+    /// Set the maximum amount of time to wait in response to a rate-limit
+    /// error that specifies when to retry.  If the server asks for a longer
+    /// wait, [`RequestError::RateLimitWait`] is returned instead.
+    pub(super) fn with_max_wait(mut self, max_wait: Option<Duration>) -> Retrier {
+        self.max_wait = max_wait;
+        self
+    }
+
+    /// Change the URL used in errors to `url`, as when a request fails over
+    /// to a different base API URL
+    pub(super) fn set_url(&mut self, url: Url) {
+        if let Some(snapshot) = self.request.as_mut() {
+            snapshot.url = url.clone();
+        }
+        self.url = url;
+    }
+
+    /// Construct the error for a response with a 4xx or 5xx status code
+    fn status_error(&self, rr: ReadableResponse) -> RequestError {
+        let mut e = StatusError::from(rr);
+        e.request.clone_from(&self.request);
+        e.display_body_limit = self.display_body_limit;
+        RequestError::Status(Box::new(e))
+    }
+
+    /// If `delay` exceeds the maximum wait, return the error to return to the
+    /// caller
+    fn check_max_wait(&self, delay: Duration, retry_at: SystemTime) -> Result<(), RequestError> {
+        if let Some(max_wait) = self.max_wait
+            && delay > max_wait
+        {
+            log::debug!(
+                "Server requested waiting {delay:?}, which exceeds maximum of {max_wait:?}; not retrying"
+            );
+            Err(RequestError::RateLimitWait {
+                method: self.method,
+                url: self.url.clone(),
+                retry_at,
+            })
+        } else {
+            Ok(())
         }
     }
 
@@ -66,7 +216,7 @@ impl Retrier {
     ///   `Ok(RetryDecision::Success(response))`.
     ///
     /// - If the request should be retried, returns
-    ///   `Ok(RetryDecision::Retry(delay))`.
+    ///   `Ok(RetryDecision::Retry { .. })`.
     ///
     /// - If the request was a failure (possibly due to status code) and should
     ///   not be retried (possibly due to all retries having been exhausted),
@@ -76,6 +226,12 @@ impl Retrier {
         resp: Result<Response<Body>, ureq::Error>,
     ) -> Result<RetryDecision, RequestError> {
         self.attempts += 1;
+        if let Ok(r) = &resp
+            && self.expected_statuses.contains(&r.status())
+        {
+            log::debug!("Server returned expected status {}", r.status());
+            return self.finalize(resp);
+        }
         if self.attempts > RETRIES {
             log::debug!("Retries exhausted");
             return self.finalize(resp);
@@ -95,6 +251,7 @@ impl Retrier {
             (BACKOFF_FACTOR * BACKOFF_BASE.powi(self.attempts - 1)).clamp(0.0, BACKOFF_MAX)
         };
         let backoff = Duration::from_secs_f64(backoff);
+        let reason;
         let delay = match resp {
             Ok(r) if r.status() == StatusCode::FORBIDDEN => {
                 let mut rr = ReadableResponse::new(self.method, self.url.clone(), r);
@@ -102,15 +259,37 @@ impl Retrier {
                     let secs = v.parse::<u64>().ok().map(|n| n + 1);
                     if let Some(delay) = secs {
                         log::debug!("Server responded with 403 and Retry-After header");
-                        if time_left < Duration::from_secs(delay) {
+                        let delay = Duration::from_secs(delay);
+                        reason = RetryReason::RetryAfter { duration: delay };
+                        self.check_max_wait(delay, SystemTime::now() + delay)?;
+                        if time_left < delay {
                             log::debug!(
                                 "Retrying after Retry-After would exceed maximum total retry wait time; not retrying"
                             );
-                            return Err(RequestError::Status(StatusError::from(rr)));
+                            return Err(self.status_error(rr));
                         }
+                    } else {
+                        reason = RetryReason::SecondaryRateLimit;
                     }
                     Duration::from_secs(secs.unwrap_or_default())
-                } else if rr.body().is_some_and(|s| s.contains("rate limit")) {
+                } else if self.rate_limit_mode != RateLimitMode::Disabled
+                    && rr.body().is_some_and(is_abuse_detection)
+                {
+                    reason = RetryReason::AbuseDetection;
+                    if time_left < self.abuse_cooldown {
+                        log::debug!(
+                            "Abuse detection mechanism triggered; cooling down would exceed maximum total retry wait time; not retrying"
+                        );
+                        return Err(self.status_error(rr));
+                    }
+                    log::debug!(
+                        "Abuse detection mechanism triggered; cooling down for {:?}",
+                        self.abuse_cooldown
+                    );
+                    self.abuse_cooldown
+                } else if self.rate_limit_mode.expects_rate_limit(&rr.parts.headers)
+                    && rr.body().is_some_and(|s| s.contains("rate limit"))
+                {
                     if rr
                         .header(RATELIMIT_REMAINING_HEADER)
                         .is_some_and(|v| v == "0")
@@ -121,49 +300,81 @@ impl Retrier {
                         {
                             let delay = time_till_timestamp(reset).unwrap_or_default()
                                 + Duration::from_secs(1);
+                            reason = RetryReason::PrimaryRateLimit {
+                                reset: Some(UNIX_EPOCH + Duration::from_secs(reset)),
+                            };
+                            self.check_max_wait(
+                                delay,
+                                UNIX_EPOCH + Duration::from_secs(reset.saturating_add(1)),
+                            )?;
                             if time_left < delay {
                                 log::debug!(
                                     "Primary rate limit exceeded; waiting for reset would exceed maximum total retry wait time; not retrying"
                                 );
-                                return Err(RequestError::Status(StatusError::from(rr)));
+                                return Err(self.status_error(rr));
                             } else {
                                 log::debug!("Primary rate limit exceeded; waiting for reset");
                             }
                             delay
                         } else {
-                            Duration::ZERO
+                            log::debug!(
+                                "Primary rate limit exceeded, but server did not say when it resets"
+                            );
+                            reason = RetryReason::PrimaryRateLimit { reset: None };
+                            backoff
                         }
                     } else {
                         log::debug!("Secondary rate limit triggered");
+                        reason = RetryReason::SecondaryRateLimit;
                         backoff
                     }
                 } else {
-                    return Err(RequestError::Status(StatusError::from(rr)));
+                    return Err(self.status_error(rr));
+                }
+            }
+            Ok(r) if r.status().is_server_error() => {
+                if !self.take_retry_budget() {
+                    return self.finalize(Ok(r));
                 }
+                reason = RetryReason::ServerError { status: r.status() };
+                backoff
             }
-            Ok(r) if r.status().is_server_error() => backoff,
             Ok(ref r) if r.status().is_client_error() => return self.finalize(resp),
-            Err(_) => backoff,
+            Err(e) => {
+                if !self.take_retry_budget() {
+                    return self.finalize(Err(e));
+                }
+                reason = RetryReason::Transport {
+                    kind: TransportErrorKind::from_error(&e),
+                };
+                backoff
+            }
             Ok(_) => return self.finalize(resp),
         };
-        Ok(RetryDecision::Retry(delay.min(time_left)))
+        Ok(RetryDecision::Retry {
+            delay: delay.min(time_left),
+            attempt: u32::try_from(self.attempts).unwrap_or_default(),
+            reason,
+        })
     }
 
-    fn finalize(
+    /// Convert the result of a request into a [`RetryDecision::Success`] or
+    /// an error without considering retrying
+    pub(super) fn finalize(
         &self,
         resp: Result<Response<Body>, ureq::Error>,
     ) -> Result<RetryDecision, RequestError> {
         match resp {
+            Ok(r) if self.expected_statuses.contains(&r.status()) => Ok(RetryDecision::Success(r)),
             Ok(r) if r.status().is_client_error() || r.status().is_server_error() => {
-                Err(RequestError::Status(StatusError::from(
-                    ReadableResponse::new(self.method, self.url.clone(), r),
-                )))
+                Err(self.status_error(ReadableResponse::new(self.method, self.url.clone(), r)))
             }
             Ok(r) => Ok(RetryDecision::Success(r)),
             Err(source) => Err(RequestError::Send {
                 method: self.method,
                 url: self.url.clone(),
                 source: Box::new(source),
+                request: self.request.clone(),
             }),
         }
     }
@@ -177,7 +388,16 @@ pub(super) enum RetryDecision {
     Success(Response<Body>),
 
     /// Sleep for the given duration and then retry the request
-    Retry(Duration),
+    Retry {
+        /// The duration to sleep for
+        delay: Duration,
+
+        /// The number of attempts made so far
+        attempt: u32,
+
+        /// Why the request is being retried
+        reason: RetryReason,
+    },
 }
 
 /// A decomposed HTTP response that can read & store the response body.
@@ -219,10 +439,15 @@ impl ReadableResponse {
     }
 
     /// Returns the response body if it can be successfully read as a string.
-    /// If the response's headers indicate the body is JSON, the body is
-    /// pretty-printed.
+    /// If the response's headers indicate the body is JSON and the body is
+    /// not too large, the body is pretty-printed.
     fn pretty_body(&mut self) -> Option<String> {
-        if self.header(CONTENT_TYPE).is_some_and(is_json_content_type) {
+        if self.header(CONTENT_TYPE).is_some_and(is_json_content_type)
+            && self
+                .body
+                .as_str()
+                .is_some_and(|s| s.len() <= PRETTY_PRINT_LIMIT)
+        {
             self.body
                 .as_str()
                 .and_then(|s| serde_json::from_str::<serde_json::Value>(s).ok())
@@ -242,11 +467,17 @@ impl ReadableResponse {
 impl From<ReadableResponse> for StatusError {
     fn from(mut value: ReadableResponse) -> StatusError {
         let body = value.pretty_body();
+        let request_id = value.header(REQUEST_ID_HEADER).map(ToOwned::to_owned);
+        let redirects = get_redirects(&value.parts.extensions);
         StatusError {
             method: value.method,
             url: value.url,
             status: value.parts.status,
             body,
+            request_id,
+            redirects,
+            request: None,
+            display_body_limit: None,
         }
     }
 }
@@ -258,9 +489,9 @@ enum ReadableBody {
     /// A body that has not yet been read
     Unread(Body),
 
-    /// A body that has been read.  If the body was successfully read as a
-    /// string, this variant contains the string; otherwise, it contains
-    /// `None`.
+    /// A body that has been read.  If the body was successfully read, this
+    /// variant contains it as a string, with any invalid UTF-8 sequences
+    /// replaced; otherwise, it contains `None`.
     Read(Option<String>),
 }
 
@@ -269,7 +500,10 @@ impl ReadableBody {
     /// Returns the read body.
     fn as_str(&mut self) -> Option<&str> {
         if let ReadableBody::Unread(body) = self {
-            *self = ReadableBody::Read(body.read_to_string().ok());
+            *self = ReadableBody::Read(body.read_to_vec().ok().map(|bytes| {
+                String::from_utf8(bytes)
+                    .unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned())
+            }));
         }
         let &mut ReadableBody::Read(ref s) = self else {
             unreachable!("ReadableBody should be Read after reading");
@@ -278,15 +512,128 @@ impl ReadableBody {
     }
 }
 
-/// Return the `rel="next"` URL, if any, from the response's "Link" header
-pub(super) fn get_next_link(r: &Response<Body>) -> Option<Url> {
-    let header_value = r.headers().get(LINK)?.to_str().ok()?;
+/// Return the URL with the given relation type, if any, from the "Link" header
+/// in `headers`
+pub(super) fn get_link(headers: &HeaderMap, rel: &str) -> Option<Url> {
+    let header_value = headers.get(LINK)?.to_str().ok()?;
     parse_link_header::parse_with_rel(header_value)
         .ok()?
-        .get("next")
+        .get(rel)
         .map(|link| link.uri.clone())
 }
 
+/// Return a map from relation types to URLs for all of the links in the
+/// "Link" header in `headers`
+pub(super) fn get_links(headers: &HeaderMap) -> HashMap<String, Url> {
+    headers
+        .get(LINK)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| parse_link_header::parse_with_rel(v).ok())
+        .map(|links| {
+            links
+                .into_iter()
+                .map(|(rel, link)| (rel, link.uri))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Response extension recording the URLs that responded with redirects before
+/// the final response was received, in the order in which they were
+/// requested
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(super) struct RedirectChain(pub(super) Vec<Url>);
+
+/// If `resp` was received after following one or more redirects, log the
+/// redirects and record them in a [`RedirectChain`] extension on `resp`
+pub(super) fn record_redirects(resp: &mut Response<Body>) {
+    use ureq::ResponseExt as _;
+    let Some((_, hops)) = resp.get_redirect_history().and_then(<[_]>::split_last) else {
+        return;
+    };
+    if hops.is_empty() {
+        return;
+    }
+    let hops = hops
+        .iter()
+        .filter_map(|uri| Url::parse(&uri.to_string()).ok())
+        .collect::<Vec<_>>();
+    log::debug!(
+        "Followed redirects: {} -> {}",
+        hops.iter()
+            .map(Url::as_str)
+            .collect::<Vec<_>>()
+            .join(" -> "),
+        resp.get_uri()
+    );
+    resp.extensions_mut().insert(RedirectChain(hops));
+}
+
+/// Return the redirect chain recorded in `extensions` by
+/// [`record_redirects()`], if any
+pub(super) fn get_redirects(extensions: &Extensions) -> Vec<Url> {
+    extensions
+        .get::<RedirectChain>()
+        .map(|chain| chain.0.clone())
+        .unwrap_or_default()
+}
+
+/// A secret string, such as an access token, whose `Debug` representation is
+/// redacted
+#[derive(Clone, Eq, PartialEq)]
+pub(crate) struct Secret(String);
+
+impl Secret {
+    pub(crate) fn new(s: &str) -> Secret {
+        Secret(s.to_owned())
+    }
+
+    /// Return the secret value
+    pub(crate) fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+/// Return the value of the given environment variable, treating empty values
+/// as unset
+pub(crate) fn getenv(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|s| !s.is_empty())
+}
+
+/// Lock a mutex, ignoring poisoning.  The state guarded by the client's
+/// mutexes is always left consistent, so a panic in another thread while
+/// holding a lock is no reason to fail.
+pub(crate) fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Returns a random duration between `min` and `max` inclusive.  The
+/// randomness comes from the standard library's randomly-keyed hasher and is
+/// not suitable for anything but spreading out delays.
+pub(super) fn random_duration(min: Duration, max: Duration) -> Duration {
+    let Some(span) = max.checked_sub(min) else {
+        return min;
+    };
+    let span = u64::try_from(span.as_nanos()).unwrap_or(u64::MAX);
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos()),
+    );
+    let offset = match span.checked_add(1) {
+        Some(modulus) => hasher.finish() % modulus,
+        None => hasher.finish(),
+    };
+    min + Duration::from_nanos(offset)
+}
+
 /// Given the value of a `Content-Type` header, returns `true` if the value
 /// is for a JSON payload
 fn is_json_content_type(ct_value: &str) -> bool {
@@ -295,6 +642,43 @@ fn is_json_content_type(ct_value: &str) -> bool {
     })
 }
 
+/// Returns `true` if `body` is that of the legacy "abuse detection mechanism"
+/// 403 error returned by older GitHub Enterprise Server versions
+pub(super) fn is_abuse_detection(body: &str) -> bool {
+    body.contains("abuse detection mechanism")
+}
+
+/// Returns `true` if `e` is a transport-level error, such as a dropped
+/// connection or a truncated response body, after which the request may
+/// succeed if retried
+pub(super) fn is_transient(e: &ureq::Error) -> bool {
+    matches!(
+        e,
+        ureq::Error::Io(_)
+            | ureq::Error::Timeout(_)
+            | ureq::Error::Protocol(_)
+            | ureq::Error::ConnectionFailed
+    )
+}
+
+/// Returns `true` if `e` indicates that the request never reached the
+/// server because the host could not be resolved or connected to
+pub(super) fn is_connect_error(e: &ureq::Error) -> bool {
+    match e {
+        ureq::Error::HostNotFound
+        | ureq::Error::ConnectionFailed
+        | ureq::Error::Timeout(Timeout::Resolve | Timeout::Connect) => true,
+        ureq::Error::Io(e) => matches!(
+            e.kind(),
+            ErrorKind::ConnectionRefused
+                | ErrorKind::HostUnreachable
+                | ErrorKind::NetworkUnreachable
+                | ErrorKind::AddrNotAvailable
+        ),
+        _ => false,
+    }
+}
+
 /// Calculate the [`Duration`] until the system time is at the given number of
 /// seconds since the Unix epoch
 fn time_till_timestamp(ts: u64) -> Option<Duration> {
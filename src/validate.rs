@@ -0,0 +1,224 @@
+use super::{Client, ClientBuilder, Method, RequestError, path_under};
+use serde_json::Value;
+use std::fmt;
+use std::sync::Arc;
+use url::Url;
+
+/// A validation function registered with
+/// [`ClientBuilder::with_response_validator()`]
+type ValidateFn = dyn Fn(&Value) -> Result<(), Vec<String>> + Send + Sync;
+
+/// A validation function for the response bodies of URLs matching a path
+/// pattern
+#[derive(Clone)]
+pub(crate) struct ResponseValidator {
+    /// The path pattern, split into segments
+    pattern: Arc<[String]>,
+    validate: Arc<ValidateFn>,
+}
+
+impl ResponseValidator {
+    /// Returns `true` if the URL path `path` (relative to the base API URL
+    /// and possibly including a query string) matches the validator's
+    /// pattern
+    fn matches(&self, path: &str) -> bool {
+        let path = path.split_once('?').map_or(path, |(p, _)| p);
+        let mut segments = path.trim_matches('/').split('/');
+        self.pattern.iter().all(|pat| {
+            segments.next().is_some_and(|seg| {
+                if pat.starts_with('{') && pat.ends_with('}') {
+                    !seg.is_empty()
+                } else {
+                    seg == pat
+                }
+            })
+        }) && segments.next().is_none()
+    }
+}
+
+impl fmt::Debug for ResponseValidator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseValidator")
+            .field("pattern", &self.pattern.join("/"))
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for ResponseValidator {
+    fn eq(&self, other: &ResponseValidator) -> bool {
+        self.pattern == other.pattern && Arc::ptr_eq(&self.validate, &other.validate)
+    }
+}
+
+impl Eq for ResponseValidator {}
+
+impl ClientBuilder {
+    /// Register a function for validating the JSON bodies of responses to
+    /// requests for URLs matching `pattern`, such as a check against the
+    /// corresponding schema from GitHub's published API description.
+    ///
+    /// `pattern` is a URL path relative to the base API URL in which
+    /// `{name}` segments match any single path segment (e.g.,
+    /// `"/repos/{owner}/{repo}/issues/{number}"`).  Query strings are ignored
+    /// when matching.
+    ///
+    /// Whenever a response to a matching request is about to be deserialized,
+    /// including each page fetched when paginating, its body is parsed as
+    /// JSON and passed to `validate`.  If `validate` returns `Err` with a list
+    /// of problems, the request fails with [`RequestError::ResponseValidation`]
+    /// before deserialization is attempted, giving early warning when the
+    /// API's responses drift from what the program was written against.
+    ///
+    /// If multiple validators match a URL, all of them are run, in the order
+    /// in which they were registered.  Responses that are not deserialized
+    /// (e.g., those returned by [`Client::request()`]) are not validated.
+    ///
+    /// This method is only available when the `validation` feature is
+    /// enabled.
+    pub fn with_response_validator<F>(mut self, pattern: &str, validate: F) -> Self
+    where
+        F: Fn(&Value) -> Result<(), Vec<String>> + Send + Sync + 'static,
+    {
+        let pattern = pattern
+            .trim_matches('/')
+            .split('/')
+            .map(ToOwned::to_owned)
+            .collect();
+        self.response_validators.push(ResponseValidator {
+            pattern,
+            validate: Arc::new(validate),
+        });
+        self
+    }
+}
+
+impl Client {
+    /// Run any response validators whose patterns match `url` on `body`
+    pub(crate) fn validate_response(
+        &self,
+        method: Method,
+        url: &Url,
+        body: &[u8],
+    ) -> Result<(), RequestError> {
+        let Some(path) = path_under(url, &self.api_url) else {
+            return Ok(());
+        };
+        let mut validators = self
+            .response_validators
+            .iter()
+            .filter(|v| v.matches(path))
+            .peekable();
+        if validators.peek().is_none() {
+            return Ok(());
+        }
+        // If the body is not valid JSON, leave it to deserialization to
+        // report the error
+        let Ok(value) = serde_json::from_slice::<Value>(body) else {
+            return Ok(());
+        };
+        let mut errors = Vec::new();
+        for v in validators {
+            if let Err(errs) = (v.validate)(&value) {
+                errors.extend(errs);
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            log::debug!(
+                "Response to {method} request to {url} failed validation: {}",
+                errors.join("; ")
+            );
+            Err(RequestError::ResponseValidation {
+                method,
+                url: url.clone(),
+                errors,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockResponse, MockServer};
+    use serde_json::json;
+
+    fn require_title(value: &Value) -> Result<(), Vec<String>> {
+        if value.get("title").is_some_and(Value::is_string) {
+            Ok(())
+        } else {
+            Err(vec!["/title: expected a string".to_owned()])
+        }
+    }
+
+    #[test]
+    fn pattern_matching() {
+        let client = Client::builder()
+            .with_response_validator("/repos/{owner}/{repo}/issues/{number}", require_title)
+            .build()
+            .unwrap();
+        let v = &client.response_validators[0];
+        assert!(v.matches("/repos/octocat/hello-world/issues/1"));
+        assert!(v.matches("/repos/octocat/hello-world/issues/1?per_page=100"));
+        assert!(!v.matches("/repos/octocat/hello-world/issues"));
+        assert!(!v.matches("/repos/octocat/hello-world/issues/1/comments"));
+        assert!(!v.matches("/repos/octocat/hello-world/pulls/1"));
+    }
+
+    #[test]
+    fn validation_failure() {
+        let client = Client::builder()
+            .with_response_validator("/repos/{owner}/{repo}/issues/{number}", require_title)
+            .build()
+            .unwrap();
+        let url = Url::parse("https://api.github.com/repos/octocat/hello-world/issues/1").unwrap();
+        assert!(
+            client
+                .validate_response(Method::Get, &url, br#"{"title": "Bug"}"#)
+                .is_ok()
+        );
+        let r = client.validate_response(Method::Get, &url, br#"{"title": null}"#);
+        assert!(matches!(
+            r,
+            Err(RequestError::ResponseValidation { ref errors, .. })
+                if errors == &["/title: expected a string"]
+        ));
+        let url = Url::parse("https://api.github.com/repos/octocat/hello-world").unwrap();
+        assert!(client.validate_response(Method::Get, &url, b"{}").is_ok());
+    }
+
+    #[test]
+    fn paginated_validation() {
+        let server = MockServer::start(|req| {
+            if req.path.starts_with("/repos/octocat/hello-world/issues") {
+                MockResponse::json(200, &json!([{"title": "Bug"}, {"title": null}]))
+            } else {
+                MockResponse::json(200, &json!([{"title": null}]))
+            }
+        });
+        let client = server
+            .client_builder()
+            .with_response_validator("/repos/{owner}/{repo}/issues", |value| {
+                value
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .try_for_each(require_title)
+            })
+            .build()
+            .unwrap();
+        let mut iter = client.paginate::<Value>("/repos/octocat/hello-world/issues");
+        assert!(matches!(
+            iter.next(),
+            Some(Err(RequestError::ResponseValidation { ref errors, .. }))
+                if errors == &["/title: expected a string"]
+        ));
+        assert!(iter.next().is_none());
+        let items = client
+            .paginate::<Value>("/repos/octocat/hello-world/pulls")
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(items, [json!({"title": null})]);
+    }
+}
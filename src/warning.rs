@@ -0,0 +1,186 @@
+use super::{Client, Method, ProgressEvent};
+use crate::util::lock;
+use std::fmt;
+use ureq::http::header::{HeaderMap, WARNING};
+use url::Url;
+
+/// A warning reported by the server in a response's `Warning` header, such as
+/// a notice that the request used a deprecated feature
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ServerWarning {
+    /// The warning code (e.g., 299 for miscellaneous persistent warnings)
+    pub code: u16,
+
+    /// The name of the server or software that added the warning (often `-`)
+    pub agent: String,
+
+    /// The warning text
+    pub text: String,
+}
+
+impl ServerWarning {
+    /// Parse all of the warnings in the `Warning` headers in `headers`.
+    /// Malformed warnings are skipped.
+    pub fn from_headers(headers: &HeaderMap) -> Vec<ServerWarning> {
+        headers
+            .get_all(WARNING)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(parse_warnings)
+            .collect()
+    }
+}
+
+impl fmt::Display for ServerWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.code, self.text)
+    }
+}
+
+/// Parse a `Warning` header value containing one or more comma-separated
+/// warnings of the form `code agent "text" ["date"]`, stopping at the first
+/// malformed warning
+fn parse_warnings(value: &str) -> Vec<ServerWarning> {
+    let mut warnings = Vec::new();
+    let mut rest = value.trim_start();
+    while !rest.is_empty() {
+        let Some((warning, after)) = parse_warning(rest) else {
+            break;
+        };
+        warnings.push(warning);
+        rest = after.trim_start();
+        match rest.strip_prefix(',') {
+            Some(r) => rest = r.trim_start(),
+            None => break,
+        }
+    }
+    warnings
+}
+
+/// Parse a single warning at the start of `s`, returning the warning and the
+/// remainder of `s`
+fn parse_warning(s: &str) -> Option<(ServerWarning, &str)> {
+    let (code, rest) = s.split_once(' ')?;
+    let code = code.parse::<u16>().ok()?;
+    let (agent, rest) = rest.trim_start().split_once(' ')?;
+    let (text, mut rest) = parse_quoted(rest.trim_start())?;
+    // Skip the optional warn-date
+    if let Some((_, after)) = parse_quoted(rest.trim_start()) {
+        rest = after;
+    }
+    Some((
+        ServerWarning {
+            code,
+            agent: agent.to_owned(),
+            text,
+        },
+        rest,
+    ))
+}
+
+/// Parse a quoted string at the start of `s`, returning its unescaped
+/// contents and the remainder of `s`
+fn parse_quoted(s: &str) -> Option<(String, &str)> {
+    let body = s.strip_prefix('"')?;
+    let mut text = String::new();
+    let mut chars = body.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => text.push(chars.next()?.1),
+            '"' => return Some((text, &body[(i + 1)..])),
+            c => text.push(c),
+        }
+    }
+    None
+}
+
+impl Client {
+    /// Log any warnings in the `Warning` headers of a response to a request
+    /// with the given method & URL and report them to the progress callback.
+    /// Each distinct warning text is only logged once per client.
+    pub(crate) fn record_warnings(&self, method: Method, url: &Url, headers: &HeaderMap) {
+        for warning in ServerWarning::from_headers(headers) {
+            if lock(&self.warnings_seen).insert(warning.text.clone()) {
+                log::warn!(
+                    "Server warning for {method} request to {url}{}: {warning}",
+                    self.operation_tag()
+                );
+            }
+            self.report_progress(ProgressEvent::ServerWarning {
+                method,
+                url: url.clone(),
+                warning,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use ureq::http::header::HeaderValue;
+
+    #[test]
+    fn parse_single() {
+        assert_eq!(
+            parse_warnings(
+                r#"299 - "Deprecated API usage; see \"docs\"" "Wed, 21 Oct 2026 07:28:00 GMT""#
+            ),
+            [ServerWarning {
+                code: 299,
+                agent: "-".into(),
+                text: r#"Deprecated API usage; see "docs""#.into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_multiple_headers() {
+        let mut headers = HeaderMap::new();
+        headers.append(
+            WARNING,
+            HeaderValue::from_static(r#"299 - "first", 199 proxy.example.com "second""#),
+        );
+        headers.append(WARNING, HeaderValue::from_static(r#"299 - "third""#));
+        headers.append(WARNING, HeaderValue::from_static("garbage"));
+        let texts = ServerWarning::from_headers(&headers)
+            .into_iter()
+            .map(|w| (w.code, w.text))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            [
+                (299, "first".to_owned()),
+                (199, "second".to_owned()),
+                (299, "third".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_unterminated() {
+        assert!(parse_warnings(r#"299 - "oops"#).is_empty());
+    }
+
+    #[test]
+    fn record_warnings_reports_each_and_logs_once() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let client = Client::builder()
+            .with_progress_hook(move |e| sink.lock().unwrap().push(e.clone()))
+            .build()
+            .unwrap();
+        let url = Url::parse("https://api.github.com/user").unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            WARNING,
+            HeaderValue::from_static(r#"299 - "Basic auth is deprecated""#),
+        );
+        client.record_warnings(Method::Get, &url, &headers);
+        client.record_warnings(Method::Get, &url, &headers);
+        assert_eq!(events.lock().unwrap().len(), 2);
+        assert_eq!(lock(&client.warnings_seen).len(), 1);
+        assert!(events.lock().unwrap()[0].resume_at().is_none());
+    }
+}
@@ -0,0 +1,97 @@
+use super::{Client, PaginationIter};
+use crate::datetime::iso8601_timestamp;
+use serde::de::DeserializeOwned;
+use std::time::SystemTime;
+
+/// Format a time as an ISO 8601 timestamp in UTC of the form
+/// `YYYY-MM-DDTHH:MM:SSZ`, as expected by the `since` and `until` query
+/// parameters of GitHub API endpoints.  Fractional seconds are dropped.
+///
+/// `t` may be a [`SystemTime`] or anything that converts into one, such as a
+/// `time::OffsetDateTime`.
+pub fn iso8601<T: Into<SystemTime>>(t: T) -> String {
+    iso8601_timestamp(t.into())
+}
+
+/// Append `since` and/or `until` query parameters for the given times to
+/// `path`, which may be either a URL or a URL path, with or without an
+/// existing query string.
+///
+/// # Example
+///
+/// ```
+/// use minigh::with_time_window;
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let since = UNIX_EPOCH + Duration::from_secs(1_750_000_000);
+/// assert_eq!(
+///     with_time_window("/repos/octocat/hello-world/commits?sha=main", Some(since), None),
+///     "/repos/octocat/hello-world/commits?sha=main&since=2025-06-15T15:06:40Z",
+/// );
+/// ```
+pub fn with_time_window(
+    path: &str,
+    since: Option<SystemTime>,
+    until: Option<SystemTime>,
+) -> String {
+    let mut s = path.to_owned();
+    for (key, t) in [("since", since), ("until", until)] {
+        if let Some(t) = t {
+            s.push(if s.contains('?') { '&' } else { '?' });
+            s.push_str(key);
+            s.push('=');
+            s.push_str(&iso8601_timestamp(t));
+        }
+    }
+    s
+}
+
+impl Client {
+    /// Like [`paginate()`][Client::paginate], but adds a `since` query
+    /// parameter for the given time to `path`, for listing only items created
+    /// or updated after that time on endpoints that support it
+    pub fn paginate_since<T, S>(&self, path: &str, since: S) -> PaginationIter<'_, T>
+    where
+        T: DeserializeOwned,
+        S: Into<SystemTime>,
+    {
+        self.paginate(&with_time_window(path, Some(since.into()), None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn window_both() {
+        let since = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let until = since + Duration::from_millis(86_400_500);
+        assert_eq!(
+            with_time_window(
+                "/repos/octocat/hello-world/commits",
+                Some(since),
+                Some(until)
+            ),
+            "/repos/octocat/hello-world/commits?since=2023-11-14T22:13:20Z&until=2023-11-15T22:13:20Z"
+        );
+    }
+
+    #[test]
+    fn window_none() {
+        assert_eq!(
+            with_time_window("/notifications", None, None),
+            "/notifications"
+        );
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn iso8601_offset_datetime() {
+        let t = time::OffsetDateTime::from_unix_timestamp_nanos(1_714_460_400_750_000_000)
+            .unwrap()
+            .to_offset(time::UtcOffset::from_hms(5, 30, 0).unwrap());
+        assert_eq!(iso8601(t), "2024-04-30T07:00:00Z");
+    }
+}